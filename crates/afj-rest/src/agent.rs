@@ -1,5 +1,7 @@
 use reqwest::Url;
+use siera_agent::agent::{Agent, Capability, HttpOptions};
 use siera_agent::error::{Error, Result};
+use siera_agent::http::HttpClient;
 
 /// AFJ REST agent
 #[derive(Debug)]
@@ -15,6 +17,9 @@ pub struct CloudAgentAfjRest {
 
     /// Agent version
     pub version: CloudAgentAfjRestVersion,
+
+    /// Http client that is reused for every request
+    pub http: HttpClient,
 }
 
 /// AFJ REST supported versions
@@ -40,21 +45,43 @@ impl std::fmt::Display for CloudAgentAfjRest {
     }
 }
 
+impl Agent for CloudAgentAfjRest {
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::Api,
+            Capability::BasicMessage,
+            Capability::Connection,
+            Capability::CredentialDefinition,
+            Capability::Schema,
+        ]
+    }
+
+    fn is_dry_run(&self) -> bool {
+        self.http.options.dry_run
+    }
+}
+
 impl CloudAgentAfjRest {
     /// Create a new instance of an AFJ REST agent
-    #[must_use]
-    pub const fn new(
+    ///
+    /// # Errors
+    ///
+    /// When the http client could not be built from the http options
+    pub fn new(
         endpoint: String,
         version: CloudAgentAfjRestVersion,
         api_key: Option<String>,
         auth_token: Option<String>,
-    ) -> Self {
-        Self {
+        http: HttpOptions,
+    ) -> Result<Self> {
+        let http = HttpClient::new(http, vec![])?;
+        Ok(Self {
             endpoint,
             api_key,
             auth_token,
             version,
-        }
+            http,
+        })
     }
 
     /// Create a url based on the base url and a list of paths
@@ -64,7 +91,7 @@ impl CloudAgentAfjRest {
     /// When it could not parse the url
     pub fn create_url(&self, paths: &[&str]) -> Result<Url> {
         let mut url = Url::parse(&self.endpoint)
-            .map_err(|_| Box::new(Error::UnreachableUrl) as Box<dyn std::error::Error>)?;
+            .map_err(|_| Error::InvalidAgentUrl(self.endpoint.clone()))?;
         url.set_path(&paths.join("/"));
         Ok(url)
     }
@@ -0,0 +1,30 @@
+use crate::agent::CloudAgentAfjRest;
+use async_trait::async_trait;
+use reqwest::Method;
+use serde_json::Value;
+use siera_agent::error::{Error, Result};
+use siera_agent::modules::api::{ApiModule, ApiRequestOptions};
+
+#[async_trait]
+impl ApiModule for CloudAgentAfjRest {
+    async fn request(&self, options: ApiRequestOptions) -> Result<Value> {
+        let method = Method::from_bytes(options.method.to_uppercase().as_bytes())
+            .map_err(|_| Error::from(format!("Invalid http method {}", options.method)))?;
+        let (path, query) = options
+            .path
+            .split_once('?')
+            .unwrap_or((options.path.as_str(), ""));
+        let mut url = self.create_url(&[path.trim_start_matches('/')])?;
+        url.set_query((!query.is_empty()).then_some(query));
+
+        let client = self.http.request(method, url).query(&options.query);
+        let client = match &options.body {
+            Some(body) => client.json(body),
+            None => client,
+        };
+
+        trace!({ "message": "Api request", "query": options.query, "body": options.body });
+
+        self.http.send::<Value>(client).await
+    }
+}
@@ -13,6 +13,6 @@ impl BasicMessageModule for CloudAgentAfjRest {
           "content": options.message,
         });
 
-        self.post(url, None, Some(body)).await
+        self.http.post(url, None, Some(body)).await
     }
 }
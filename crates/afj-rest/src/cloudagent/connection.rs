@@ -39,13 +39,13 @@ impl ConnectionModule for CloudAgentAfjRest {
         }
         let url = self.create_url(&["connections"])?;
 
-        self.get(url, None).await
+        self.http.get(url, None).await
     }
 
     async fn get_by_id(&self, id: String) -> Result<Connection> {
         let url = self.create_url(&["connections", &id])?;
 
-        self.get(url, None).await
+        self.http.get(url, None).await
     }
 
     async fn create_invitation(
@@ -59,7 +59,7 @@ impl ConnectionModule for CloudAgentAfjRest {
             "alias": options.alias,
         });
 
-        let invitation: Response = self.post(url, None, Some(body)).await?;
+        let invitation: Response = self.http.post(url, None, Some(body)).await?;
 
         Ok(Invitation {
             invitation_url: invitation.invitation_url,
@@ -84,6 +84,6 @@ impl ConnectionModule for CloudAgentAfjRest {
             },
         });
 
-        self.post(url, None, Some(body)).await
+        self.http.post(url, None, Some(body)).await
     }
 }
@@ -1,10 +1,10 @@
 use crate::agent::CloudAgentAfjRest;
 use async_trait::async_trait;
 use serde_json::json;
-use siera_agent::error::{Error, Result};
+use siera_agent::error::Result;
 use siera_agent::modules::credential_definition::{
     CredentialDefinition, CredentialDefinitionCreateOptions, CredentialDefinitionCreateResponse,
-    CredentialDefinitionGetAllResponse, CredentialDefinitionModule,
+    CredentialDefinitionModule,
 };
 
 #[async_trait]
@@ -21,15 +21,11 @@ impl CredentialDefinitionModule for CloudAgentAfjRest {
             "schemaId": options.schema_id
         });
 
-        self.post(url, None, Some(body)).await
+        self.http.post(url, None, Some(body)).await
     }
 
     async fn get_by_id(&self, id: String) -> Result<CredentialDefinition> {
         let url = self.create_url(&["credential-definitions", &id])?;
-        self.get(url, None).await
-    }
-
-    async fn get_all(&self) -> Result<CredentialDefinitionGetAllResponse> {
-        Err(Error::CommandNotAvailable(format!("{self}")).into())
+        self.http.get(url, None).await
     }
 }
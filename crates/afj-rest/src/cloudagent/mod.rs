@@ -1,3 +1,6 @@
+/// Module for raw requests to an AFJ REST agent
+pub mod api;
+
 /// Module for schemas specific for an AFJ REST agent
 pub mod schema;
 
@@ -9,3 +12,6 @@ pub mod credential_definition;
 
 /// Module for basic message specific for an AFJ REST agent
 pub mod basic_message;
+
+/// Modules that are not supported by an AFJ REST agent
+pub mod unsupported;
@@ -1,10 +1,8 @@
 use crate::agent::CloudAgentAfjRest;
 use async_trait::async_trait;
 use serde_json::json;
-use siera_agent::error::{Error, Result};
-use siera_agent::modules::schema::{
-    Schema, SchemaCreateOptions, SchemaModule, SchemasGetAllResponse,
-};
+use siera_agent::error::Result;
+use siera_agent::modules::schema::{Schema, SchemaCreateOptions, SchemaModule};
 
 #[async_trait]
 impl SchemaModule for CloudAgentAfjRest {
@@ -13,16 +11,12 @@ impl SchemaModule for CloudAgentAfjRest {
 
         let body = json!(options);
 
-        self.post(url, None, Some(body)).await
+        self.http.post(url, None, Some(body)).await
     }
 
     async fn get_by_id(&self, id: String) -> Result<Schema> {
         let url = self.create_url(&["schemas", &id])?;
 
-        self.get(url, None).await
-    }
-
-    async fn get_all(&self) -> Result<SchemasGetAllResponse> {
-        Err(Error::CommandNotAvailable(format!("{self}")).into())
+        self.http.get(url, None).await
     }
 }
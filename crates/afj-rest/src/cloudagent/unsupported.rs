@@ -0,0 +1,33 @@
+use crate::agent::CloudAgentAfjRest;
+use async_trait::async_trait;
+use siera_agent::modules::credential::CredentialModule;
+use siera_agent::modules::feature::FeatureModule;
+use siera_agent::modules::multitenancy::MultitenancyModule;
+use siera_agent::modules::oob::OobModule;
+use siera_agent::modules::proof::ProofModule;
+use siera_agent::modules::wallet::WalletModule;
+use siera_agent::modules::webhook::WebhookModule;
+
+// The modules below are not supported by AFJ yet, so every method returns the default
+// `Error::CommandNotAvailable`
+
+#[async_trait]
+impl CredentialModule for CloudAgentAfjRest {}
+
+#[async_trait]
+impl FeatureModule for CloudAgentAfjRest {}
+
+#[async_trait]
+impl MultitenancyModule for CloudAgentAfjRest {}
+
+#[async_trait]
+impl OobModule for CloudAgentAfjRest {}
+
+#[async_trait]
+impl ProofModule for CloudAgentAfjRest {}
+
+#[async_trait]
+impl WalletModule for CloudAgentAfjRest {}
+
+#[async_trait]
+impl WebhookModule for CloudAgentAfjRest {}
@@ -14,6 +14,3 @@ pub mod cloudagent;
 
 /// An AFJ REST structure
 pub mod agent;
-
-/// Simple web bindings for the module to interact with the cloudagent
-mod web;
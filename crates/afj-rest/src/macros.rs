@@ -6,7 +6,7 @@
 macro_rules! has_any_value_in_struct {
     ($options:expr; $($field:ident),+ $(,)?) => {
         {
-            vec![$($options.$field),+].iter().any(|v| v.is_some())
+            [$($options.$field.is_some()),+].iter().any(|v| *v)
         }
     };
 }
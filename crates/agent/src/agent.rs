@@ -0,0 +1,379 @@
+use crate::cassette::CassetteOptions;
+use crate::error::{Error, Result};
+use crate::modules::api::ApiModule;
+use crate::modules::basic_message::BasicMessageModule;
+use crate::modules::connection::ConnectionModule;
+use crate::modules::credential::CredentialModule;
+use crate::modules::credential_definition::CredentialDefinitionModule;
+use crate::modules::feature::FeatureModule;
+use crate::modules::multitenancy::MultitenancyModule;
+use crate::modules::oob::OobModule;
+use crate::modules::proof::ProofModule;
+use crate::modules::schema::SchemaModule;
+use crate::modules::wallet::WalletModule;
+use crate::modules::webhook::WebhookModule;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A module that an agent can support
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Capability {
+    /// Sending requests to any endpoint of the admin api
+    Api,
+
+    /// Sending basic messages
+    BasicMessage,
+
+    /// Creating, receiving and listing connections
+    Connection,
+
+    /// Issuing credentials
+    Credential,
+
+    /// Registering and fetching credential definitions
+    CredentialDefinition,
+
+    /// Discovering the features of the agent
+    Feature,
+
+    /// Managing sub wallets
+    Multitenancy,
+
+    /// Out of band invitations
+    Oob,
+
+    /// Requesting proofs
+    Proof,
+
+    /// Registering and fetching schemas
+    Schema,
+
+    /// Managing DIDs in the wallet
+    Wallet,
+
+    /// Listening to the events of the agent
+    Webhook,
+}
+
+impl Capability {
+    /// Every capability an agent can declare
+    pub const ALL: &'static [Self] = &[
+        Self::Api,
+        Self::BasicMessage,
+        Self::Connection,
+        Self::Credential,
+        Self::CredentialDefinition,
+        Self::Feature,
+        Self::Multitenancy,
+        Self::Oob,
+        Self::Proof,
+        Self::Schema,
+        Self::Wallet,
+        Self::Webhook,
+    ];
+}
+
+impl Display for Capability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Api => "api",
+            Self::BasicMessage => "basic message",
+            Self::Connection => "connection",
+            Self::Credential => "credential",
+            Self::CredentialDefinition => "credential definition",
+            Self::Feature => "feature",
+            Self::Multitenancy => "multitenancy",
+            Self::Oob => "oob",
+            Self::Proof => "proof",
+            Self::Schema => "schema",
+            Self::Wallet => "wallet",
+            Self::Webhook => "webhook",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A cloudagent that can be driven by a frontend
+///
+/// Every module trait is a supertrait so a single agent instance can be passed around. Every
+/// method of a module returns `Error::CommandNotAvailable` unless the backend implements it, so
+/// a backend only needs an empty `impl` for the modules it does not support. Those must be left
+/// out of `capabilities`, so frontends can report them before calling into the agent.
+pub trait Agent:
+    ApiModule
+    + BasicMessageModule
+    + ConnectionModule
+    + CredentialModule
+    + CredentialDefinitionModule
+    + FeatureModule
+    + MultitenancyModule
+    + OobModule
+    + ProofModule
+    + SchemaModule
+    + WalletModule
+    + WebhookModule
+    + Display
+    + Send
+    + Sync
+{
+    /// The modules that this agent supports
+    fn capabilities(&self) -> &'static [Capability];
+
+    /// Whether the agent supports the given module
+    fn supports(&self, capability: Capability) -> bool {
+        self.capabilities().contains(&capability)
+    }
+
+    /// Whether the agent prints its requests instead of sending them. The first request stops
+    /// the command with `Error::DryRun`
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+}
+
+/// Options that are supplied by the frontend to construct an agent
+#[derive(Debug, Clone, Default)]
+pub struct AgentOptions {
+    /// base url of the cloudagent
+    pub endpoint: String,
+
+    /// admin Api key for the cloudagent
+    pub api_key: Option<String>,
+
+    /// Authorization token for a multi tenancy agent
+    pub auth_token: Option<String>,
+
+    /// Options for the http client that is used for every request
+    pub http: HttpOptions,
+}
+
+/// Options for the http client that an agent keeps for its whole lifetime
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpOptions {
+    /// Maximum time it may take to establish a connection with the agent
+    pub connect_timeout: Duration,
+
+    /// Maximum time a single request, including reading the response, may take
+    pub request_timeout: Duration,
+
+    /// How often an idempotent request is retried after a 502, 503 or connection error
+    pub retries: u32,
+
+    /// Delay before the first retry. It is doubled for every following retry
+    pub retry_backoff: Duration,
+
+    /// Optional proxy url that every request is sent through
+    pub proxy: Option<String>,
+
+    /// Optional user agent that replaces the default `siera/<version>`
+    pub user_agent: Option<String>,
+
+    /// Trust roots and client identity used for https and wss connections
+    pub tls: TlsOptions,
+
+    /// Print every request as a `curl` command instead of sending it
+    pub dry_run: bool,
+
+    /// Cassette the requests are recorded to or replayed from, instead of only sending them
+    pub cassette: Option<CassetteOptions>,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(60),
+            retries: 3,
+            retry_backoff: Duration::from_millis(500),
+            proxy: None,
+            user_agent: None,
+            tls: TlsOptions::default(),
+            dry_run: false,
+            cassette: None,
+        }
+    }
+}
+
+/// Headers whose values are replaced when a request is printed
+const SECRET_HEADERS: [&str; 2] = ["authorization", "x-api-key"];
+
+impl HttpOptions {
+    /// The request as an equivalent `curl` command, with the values of secret headers redacted
+    ///
+    /// The query is expected to be part of the url. The proxy, user agent and TLS options are
+    /// added, so the command behaves like the client of the agent
+    #[must_use]
+    pub fn curl_command(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Option<&str>,
+    ) -> String {
+        let mut args = vec![String::from("curl -X"), method.to_owned(), shell_quote(url)];
+        for (name, value) in headers {
+            let value = if SECRET_HEADERS.contains(&name.to_lowercase().as_str()) {
+                redact(value)
+            } else {
+                (*value).to_owned()
+            };
+            args.push(format!("-H {}", shell_quote(&format!("{name}: {value}"))));
+        }
+        if let Some(user_agent) = &self.user_agent {
+            args.push(format!("-A {}", shell_quote(user_agent)));
+        }
+        if let Some(proxy) = &self.proxy {
+            args.push(format!("--proxy {}", shell_quote(proxy)));
+        }
+        let paths = [
+            ("--cacert", &self.tls.ca_cert),
+            ("--cert", &self.tls.client_cert),
+            ("--key", &self.tls.client_key),
+        ];
+        for (flag, path) in paths {
+            if let Some(path) = path {
+                args.push(format!(
+                    "{flag} {}",
+                    shell_quote(&path.display().to_string())
+                ));
+            }
+        }
+        if self.tls.insecure_skip_verify {
+            args.push(String::from("--insecure"));
+        }
+        if let Some(body) = body {
+            args.push(format!("--data-raw {}", shell_quote(body)));
+        }
+        args.join(" ")
+    }
+}
+
+/// Redact a secret header value. The scheme of an authorization header, e.g. `Bearer`, is kept
+fn redact(value: &str) -> String {
+    match value.split_once(' ') {
+        Some((scheme, _)) => format!("{scheme} <redacted>"),
+        None => String::from("<redacted>"),
+    }
+}
+
+/// Quote a value for a POSIX shell
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Options for the TLS connections with the agent
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsOptions {
+    /// Path to a PEM encoded CA bundle that is trusted next to the system roots
+    pub ca_cert: Option<PathBuf>,
+
+    /// Path to a PEM encoded client certificate (chain) used for mutual TLS
+    pub client_cert: Option<PathBuf>,
+
+    /// Path to the PEM encoded PKCS #8 private key of the client certificate
+    pub client_key: Option<PathBuf>,
+
+    /// Accept any certificate and hostname of the agent. Only use this for development
+    pub insecure_skip_verify: bool,
+}
+
+impl TlsOptions {
+    /// Read every certificate in the CA bundle as a separate PEM block
+    ///
+    /// # Errors
+    ///
+    /// When the CA bundle could not be read or does not contain a certificate
+    pub fn ca_certificates(&self) -> Result<Vec<Vec<u8>>> {
+        /// End of a single certificate in a PEM bundle
+        const END_CERTIFICATE: &str = "-----END CERTIFICATE-----";
+
+        let Some(path) = &self.ca_cert else {
+            return Ok(vec![]);
+        };
+        let bundle = read_pem(path)?;
+        let certificates: Vec<Vec<u8>> = bundle
+            .split_inclusive(END_CERTIFICATE)
+            .filter(|block| block.contains("-----BEGIN CERTIFICATE-----"))
+            .map(|block| block.trim().as_bytes().to_vec())
+            .collect();
+
+        if certificates.is_empty() {
+            return Err(Error::InvalidTlsOptions(format!(
+                "{} does not contain a PEM encoded certificate",
+                path.display()
+            )));
+        }
+        Ok(certificates)
+    }
+
+    /// Read the client certificate and private key, if mutual TLS is configured
+    ///
+    /// # Errors
+    ///
+    /// When only one of the certificate and key is configured or they could not be read
+    pub fn client_identity(&self) -> Result<Option<(Vec<u8>, Vec<u8>)>> {
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => Ok(Some((
+                read_pem(cert)?.into_bytes(),
+                read_pem(key)?.into_bytes(),
+            ))),
+            (None, None) => Ok(None),
+            _ => Err(Error::InvalidTlsOptions(String::from(
+                "client_cert and client_key must be supplied together",
+            ))),
+        }
+    }
+}
+
+/// Read a PEM file that is referenced from the TLS options
+fn read_pem(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| Error::InvalidTlsOptions(format!("unable to read {}: {e}", path.display())))
+}
+
+/// Function that constructs an agent from the supplied options
+pub type AgentConstructor = fn(AgentOptions) -> Result<Box<dyn Agent>>;
+
+/// Registry of every agent type a frontend can use, keyed by name (e.g. `aca-py`)
+#[derive(Debug, Default)]
+pub struct AgentRegistry {
+    /// Constructors for the registered agents
+    constructors: BTreeMap<String, AgentConstructor>,
+}
+
+impl AgentRegistry {
+    /// Register a new agent type. Registering an existing name overwrites it
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        constructor: AgentConstructor,
+    ) -> &mut Self {
+        self.constructors.insert(name.into(), constructor);
+        self
+    }
+
+    /// Whether an agent with this name is registered
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.constructors.contains_key(name)
+    }
+
+    /// Names of all the registered agents
+    #[must_use]
+    pub fn names(&self) -> Vec<&str> {
+        self.constructors.keys().map(String::as_str).collect()
+    }
+
+    /// Construct the agent that is registered under `name`. Returns `None` when no agent is
+    /// registered under that name
+    ///
+    /// # Errors
+    ///
+    /// When the agent could not be constructed from the options
+    #[must_use]
+    pub fn build(&self, name: &str, options: AgentOptions) -> Option<Result<Box<dyn Agent>>> {
+        self.constructors.get(name).map(|c| c(options))
+    }
+}
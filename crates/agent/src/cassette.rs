@@ -0,0 +1,191 @@
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Whether a cassette records the requests of an agent or replays them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send every request to the agent and write it, with its response, to the cassette
+    Record,
+
+    /// Answer every request from the cassette without sending it
+    Replay,
+}
+
+/// Cassette that an agent uses as its transport
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CassetteOptions {
+    /// Path of the JSON file with the interactions
+    pub path: PathBuf,
+
+    /// Whether the interactions are recorded or replayed
+    pub mode: CassetteMode,
+}
+
+/// A request as it is stored in a cassette. Headers are left out, so the api key and the token
+/// never end up in a cassette
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// Http method of the request
+    pub method: String,
+
+    /// Path and query of the url, without the scheme and host, so a cassette can be replayed
+    /// against any agent url
+    pub path: String,
+
+    /// Body of the request. Parsed as JSON when possible, otherwise the raw text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+impl RecordedRequest {
+    /// Request with the method, full url and raw body as it is sent to the agent
+    #[must_use]
+    pub fn new(method: impl Into<String>, url: &str, body: Option<&[u8]>) -> Self {
+        let path = url.split_once("://").map_or(url, |(_, rest)| {
+            rest.find('/').map_or("/", |index| &rest[index..])
+        });
+        Self {
+            method: method.into(),
+            path: path.to_owned(),
+            body: body.and_then(|b| parse_body(&String::from_utf8_lossy(b))),
+        }
+    }
+}
+
+/// A response as it is stored in a cassette
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// Status code of the response
+    pub status: u16,
+
+    /// Body of the response. Parsed as JSON when possible, otherwise the raw text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+impl RecordedResponse {
+    /// Response with the status code and raw body as it is received from the agent
+    #[must_use]
+    pub fn new(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            body: parse_body(body),
+        }
+    }
+
+    /// Body of the response as text, like the agent sent it
+    #[must_use]
+    pub fn text(&self) -> String {
+        match &self.body {
+            Some(Value::String(text)) => text.clone(),
+            Some(body) => body.to_string(),
+            None => String::new(),
+        }
+    }
+}
+
+/// A request and the response the agent gave to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The request that was sent to the agent
+    pub request: RecordedRequest,
+
+    /// The response of the agent
+    pub response: RecordedResponse,
+}
+
+/// Content of a cassette file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    /// Interactions in the order they happened
+    interactions: Vec<Interaction>,
+}
+
+/// Interactions with an agent that are recorded to, or replayed from, a file
+///
+/// On a replay every request is answered with the first interaction that was not replayed yet
+/// and has the same method, path and body. Requests that happen more than once, like polling
+/// the state of an exchange, are therefore answered in the order they were recorded
+#[derive(Debug)]
+pub struct Cassette {
+    /// Where the cassette is stored and whether it is recorded or replayed
+    options: CassetteOptions,
+
+    /// Interactions that are recorded so far, or that are not replayed yet
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl Cassette {
+    /// Open the cassette. A cassette that is recorded starts empty and replaces the file on the
+    /// first interaction
+    ///
+    /// # Errors
+    ///
+    /// When the cassette that is replayed could not be read or is not a valid cassette
+    pub fn open(options: CassetteOptions) -> Result<Self> {
+        let interactions = match options.mode {
+            CassetteMode::Record => vec![],
+            CassetteMode::Replay => {
+                let content = std::fs::read_to_string(&options.path).map_err(|e| {
+                    Error::InvalidCassette(format!("{}: {e}", options.path.display()))
+                })?;
+                let file: CassetteFile = serde_json::from_str(&content).map_err(|e| {
+                    Error::InvalidCassette(format!("{}: {e}", options.path.display()))
+                })?;
+                file.interactions
+            }
+        };
+        Ok(Self {
+            options,
+            interactions: Mutex::new(interactions),
+        })
+    }
+
+    /// Whether the requests are answered from the cassette
+    #[must_use]
+    pub fn is_replaying(&self) -> bool {
+        self.options.mode == CassetteMode::Replay
+    }
+
+    /// The recorded response to the request, if there is one that was not replayed yet
+    ///
+    /// # Panics
+    ///
+    /// When another request panicked while it held the interactions
+    #[must_use]
+    pub fn replay(&self, request: &RecordedRequest) -> Option<RecordedResponse> {
+        let mut interactions = self.interactions.lock().unwrap();
+        let index = interactions.iter().position(|i| &i.request == request)?;
+        Some(interactions.remove(index).response)
+    }
+
+    /// Add the interaction to the cassette and write the whole cassette to its file
+    ///
+    /// # Errors
+    ///
+    /// When the cassette could not be written
+    ///
+    /// # Panics
+    ///
+    /// When another request panicked while it held the interactions
+    pub fn record(&self, request: RecordedRequest, response: RecordedResponse) -> Result<()> {
+        let mut interactions = self.interactions.lock().unwrap();
+        interactions.push(Interaction { request, response });
+        let file = CassetteFile {
+            interactions: interactions.clone(),
+        };
+        std::fs::write(&self.options.path, serde_json::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+}
+
+/// Body as JSON when possible, otherwise as text. An empty body is left out
+fn parse_body(body: &str) -> Option<Value> {
+    match body.trim() {
+        "" => None,
+        b => Some(serde_json::from_str(b).unwrap_or_else(|_| Value::String(b.to_owned()))),
+    }
+}
@@ -1,35 +1,62 @@
+use serde::Serialize;
+use serde_json::Value;
 use std::fmt::{Display, Formatter};
 
 /// User-level errors that can be thrown at runtime
 #[derive(Debug)]
 pub enum Error {
     /// The cloudagent did not allow the request without proper authorization
-    AuthorizationFailed,
+    AuthorizationFailed(HttpContext),
 
     /// The specified environment does not exist inside the configuration file
     InvalidAgentUrl(String),
 
     /// The server gave a response that was not expected and therefore not deserializeable
-    UnableToParseResponse,
+    UnableToParseResponse(HttpContext),
 
     /// Provided url does not exist
-    UrlDoesNotExist,
+    UrlDoesNotExist(HttpContext),
+
+    /// The cloudagent rejected the request, e.g. because of invalid attributes
+    InvalidRequest(HttpContext),
 
     /// The server supplied a status code which is not handled accordingly
-    UnknownResponseStatusCode(String),
+    UnknownResponseStatusCode(HttpContext),
 
     /// The server responded with a 5xx status code. Not our fault
-    InternalServerError(u16, String),
+    InternalServerError(HttpContext),
 
     /// Supplied url is not reachable
-    UnreachableUrl,
+    UnreachableUrl(HttpContext, String),
+
+    /// The cloudagent did not respond within the request timeout
+    Timeout(HttpContext),
 
     /// Specific handle case for a 5xx status code which means that the cloudagent might be offline
-    HttpServiceUnavailable,
+    HttpServiceUnavailable(HttpContext),
+
+    /// The request was printed instead of sent, because of a dry run. This stops the command,
+    /// as nothing can follow a request without its response
+    DryRun(HttpContext),
+
+    /// A replayed request is not in the cassette, or no longer, because it was already replayed
+    UnrecordedRequest(HttpContext),
+
+    /// The cassette could not be read
+    InvalidCassette(String),
 
     /// Subcommand is not available for this agent
     CommandNotAvailable(String),
 
+    /// No credential in the wallet can satisfy these referents of the proof request
+    UnsatisfiableProofRequest(Vec<String>),
+
+    /// The http client could not be created with the supplied options
+    InvalidHttpOptions(String),
+
+    /// The certificates or keys for the TLS connection are invalid
+    InvalidTlsOptions(String),
+
     // TODO: why is this here?
     /// Predicate structure is invalid
     UnableToParseOutValue(String),
@@ -37,27 +64,250 @@ pub enum Error {
     // TODO: why is this here?
     /// Predicate used an invalid operator
     InvalidOperator(String),
+
+    /// Any other error that occurred while talking to the agent
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Error of a method of a module that the agent does not support
+pub(crate) fn unsupported<T>(agent: &(impl Display + ?Sized)) -> Result<T> {
+    Err(Error::CommandNotAvailable(agent.to_string()))
+}
+
+/// Broad category of an error so frontends can react to it without matching every variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// Missing or invalid credentials for the agent
+    Authorization,
+
+    /// The requested resource or endpoint does not exist
+    NotFound,
+
+    /// The agent rejected the request
+    InvalidRequest,
+
+    /// The agent failed while handling the request
+    Server,
+
+    /// The agent could not be reached or is unavailable
+    Network,
+
+    /// The agent did not respond in time
+    Timeout,
+
+    /// The response of the agent could not be understood
+    Response,
+
+    /// The options that were supplied to the agent are invalid
+    Configuration,
+
+    /// The agent does not support the command
+    Unsupported,
+
+    /// Anything else
+    Other,
+}
+
+impl Display for ErrorCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Authorization => "authorization",
+            Self::NotFound => "not_found",
+            Self::InvalidRequest => "invalid_request",
+            Self::Server => "server",
+            Self::Network => "network",
+            Self::Timeout => "timeout",
+            Self::Response => "response",
+            Self::Configuration => "configuration",
+            Self::Unsupported => "unsupported",
+            Self::Other => "other",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// The request, and the response if there was one, that resulted in an error
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HttpContext {
+    /// Http method of the request
+    pub method: String,
+
+    /// Full url of the request
+    pub url: String,
+
+    /// Status code of the response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+
+    /// Body of the response. Parsed as JSON when possible, otherwise the raw text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+impl HttpContext {
+    /// Context for a request that did not receive a response
+    pub fn new(method: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            method: method.into(),
+            url: url.into(),
+            status: None,
+            body: None,
+        }
+    }
+
+    /// Add the status code and body of the response
+    #[must_use]
+    pub fn with_response(self, status: u16, body: &str) -> Self {
+        let body = body.trim();
+        let body = match body {
+            "" => None,
+            b => Some(serde_json::from_str(b).unwrap_or_else(|_| Value::String(b.to_owned()))),
+        };
+        Self {
+            status: Some(status),
+            body,
+            ..self
+        }
+    }
+}
+
+impl Display for HttpContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "  request: {} {}", self.method, self.url)?;
+        if let Some(status) = self.status {
+            write!(f, "\n  status: {status}")?;
+        }
+        match &self.body {
+            Some(Value::String(body)) => write!(f, "\n  response: {body}"),
+            Some(body) => write!(f, "\n  response: {body}"),
+            None => Ok(()),
+        }
+    }
 }
 
-impl std::error::Error for Error {}
+impl Error {
+    /// Error for a response of the agent with a status code outside of the 2xx range
+    #[must_use]
+    pub fn from_response(context: HttpContext) -> Self {
+        match context.status {
+            Some(400 | 422) => Self::InvalidRequest(context),
+            Some(401 | 403) => Self::AuthorizationFailed(context),
+            Some(404) => Self::UrlDoesNotExist(context),
+            Some(503) => Self::HttpServiceUnavailable(context),
+            Some(500..=599) => Self::InternalServerError(context),
+            _ => Self::UnknownResponseStatusCode(context),
+        }
+    }
+
+    /// The category of the error
+    #[must_use]
+    pub const fn category(&self) -> ErrorCategory {
+        match self {
+            Self::AuthorizationFailed(_) => ErrorCategory::Authorization,
+            Self::UrlDoesNotExist(_) => ErrorCategory::NotFound,
+            Self::InvalidRequest(_)
+            | Self::UnableToParseOutValue(_)
+            | Self::InvalidOperator(_)
+            | Self::UnsatisfiableProofRequest(_) => ErrorCategory::InvalidRequest,
+            Self::InternalServerError(_) => ErrorCategory::Server,
+            Self::UnreachableUrl(..) | Self::HttpServiceUnavailable(_) => ErrorCategory::Network,
+            Self::Timeout(_) => ErrorCategory::Timeout,
+            Self::UnableToParseResponse(_) | Self::UnknownResponseStatusCode(_) => {
+                ErrorCategory::Response
+            }
+            Self::InvalidAgentUrl(_)
+            | Self::InvalidHttpOptions(_)
+            | Self::InvalidTlsOptions(_)
+            | Self::InvalidCassette(_) => ErrorCategory::Configuration,
+            Self::CommandNotAvailable(_) => ErrorCategory::Unsupported,
+            Self::DryRun(_) | Self::UnrecordedRequest(_) | Self::Other(_) => ErrorCategory::Other,
+        }
+    }
+
+    /// The request and response that caused the error, if it came from the agent
+    #[must_use]
+    pub const fn http_context(&self) -> Option<&HttpContext> {
+        match self {
+            Self::AuthorizationFailed(c)
+            | Self::UnableToParseResponse(c)
+            | Self::UrlDoesNotExist(c)
+            | Self::InvalidRequest(c)
+            | Self::UnknownResponseStatusCode(c)
+            | Self::InternalServerError(c)
+            | Self::UnreachableUrl(c, _)
+            | Self::Timeout(c)
+            | Self::HttpServiceUnavailable(c)
+            | Self::DryRun(c)
+            | Self::UnrecordedRequest(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Human readable message of the error, without the http context
+    #[must_use]
+    pub fn message(&self) -> String {
+        match self {
+            Self::AuthorizationFailed(_) => String::from("Failed to authorize. Api-key or authorization token is either wrong or missing."),
+            Self::UnableToParseResponse(_) => String::from("Unable to parse the response from the server. Is the cloudagent the correct version?"),
+            Self::UrlDoesNotExist(_) => String::from("Path does not exist on agent URL. This can happen when querying by id and the id is not valid or when attempting to use a feature that is not supported on the cloudagent."),
+            Self::InvalidRequest(_) => String::from("The cloudagent rejected the request. Check the supplied values."),
+            Self::UnknownResponseStatusCode(_) => String::from("Received unknown status code from the server. Agent URL is likely incorrect. If the agent URL is correct, please report this error at https://github.com/animo/siera/issues/new"),
+            Self::InternalServerError(_) => String::from("Internal Server Error! The cloudagent was unable to handle the request."),
+            Self::UnreachableUrl(_, reason) => format!("Provided url is unreachable. Is the provided agent URL valid? Additional info: {reason}"),
+            Self::Timeout(_) => String::from("The cloudagent did not respond in time. Increase the timeout with --request-timeout or check whether the agent is overloaded."),
+            Self::HttpServiceUnavailable(_) => String::from("Cloudagent is currently unavailable. Are you sure the agent is online?"),
+            Self::UnableToParseOutValue(val) => format!("Unable to parse the predicate values from: {val}. The following structure is required: (name,operator,value)"),
+            Self::InvalidOperator(op) => format!("Invalid Operator ({op}). \">=\", \"<=\", \"=\", \"<\" and \">\" are allowed."),
+            Self::InvalidAgentUrl(url) => format!("Invalid agent url ({url})"),
+            Self::DryRun(_) => String::from("Dry run: the request was not sent. Anything that depends on its response, like the requests that would follow it, could not be simulated."),
+            Self::UnrecordedRequest(_) => String::from("The request is not in the cassette. Record the cassette again when the requests of the command changed."),
+            Self::InvalidCassette(msg) => format!("Unable to read the cassette. Additional info: {msg}"),
+            Self::CommandNotAvailable(agent) => format!("Agent '{agent}' does not support this command"),
+            Self::UnsatisfiableProofRequest(referents) => format!("No credential in the wallet can satisfy the referents {} of the proof request. The presentation was not sent.", referents.join(", ")),
+            Self::InvalidTlsOptions(msg) => format!("Invalid TLS configuration. Check the ca_cert, client_cert and client_key of the environment. Additional info: {msg}"),
+            Self::InvalidHttpOptions(msg) => format!("Unable to create the http client. Are the proxy and timeout options valid? Additional info: {msg}"),
+            Self::Other(e) => e.to_string(),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Other(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 /// Generic result type which binds the error to be an instance of the `Error` enum
-pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+pub type Result<T> = std::result::Result<T, Error>;
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Error::AuthorizationFailed => write!(f, "Failed to authorize. Api-key or authorization token is either wrong or missing."),
-            Error::UnableToParseResponse => write!(f, "Unable to parse the response from the server. Is the cloudagent the correct version?"),
-            Error::UrlDoesNotExist => write!(f, "Path does not exist on agent URL. This can happen when querying by id and the id is not valid or when attempting to use a feature that is not supported on the cloudagent."),
-            Error::UnknownResponseStatusCode(msg) => write!(f, "Received unknown status code from the server. Agent URL is likely incorrect. If the agent URL is correct, please report this error at https://github.com/animo/siera/issues/new Additional info: {msg}"),
-            Error::InternalServerError(status, msg) => write!(f, "Internal Server Error (status code: {status})! Message: {msg}"),
-            Error::UnreachableUrl => write!(f, "Provided url is unreachable. Is the provided agent URL valid?"),
-            Error::HttpServiceUnavailable => write!(f, "Cloudagent is currently unavailable. Are you sure the agent is online?"),
-            Error::UnableToParseOutValue(val) => write!(f, "Unable to parse the predicate values from: {val}. The following structure is required: (name,operator,value)"),
-            Error::InvalidOperator(op) => write!(f, "Invalid Operator ({op}). \">=\", \"<=\", \"=\", \"<\" and \">\" are allowed."),
-            Error::InvalidAgentUrl(url) => write!(f, "Invalid agent url ({url})"),
-            Error::CommandNotAvailable(agent) => write!(f, "Agent '{agent}' does not support this command"),
+        write!(f, "{}", self.message())?;
+        match self.http_context() {
+            Some(context) => write!(f, "\n{context}"),
+            None => Ok(()),
         }
     }
 }
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Other(Box::new(e))
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Other(Box::new(e))
+    }
+}
+
+impl From<String> for Error {
+    fn from(e: String) -> Self {
+        Self::Other(e.into())
+    }
+}
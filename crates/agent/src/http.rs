@@ -0,0 +1,325 @@
+use crate::agent::HttpOptions;
+use crate::cassette::{Cassette, RecordedRequest, RecordedResponse};
+use crate::error::{Error, HttpContext, Result};
+use reqwest::{
+    Certificate, Client, Identity, Method, Proxy, Request, RequestBuilder, Response, Url,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Http transport that is shared by the cloudagents. It sends every request with the headers of
+/// the agent, retries idempotent requests and records them to, or replays them from, a cassette
+#[derive(Debug)]
+pub struct HttpClient {
+    /// Options the client was built with, also used to retry requests
+    pub options: HttpOptions,
+
+    /// Client that is reused for every request
+    client: Client,
+
+    /// Cassette the requests are recorded to or replayed from
+    cassette: Option<Cassette>,
+
+    /// Headers that are added to every request, like the authentication of the agent
+    headers: Vec<(&'static str, String)>,
+}
+
+impl HttpClient {
+    /// Builds the client from the http options of the agent
+    ///
+    /// # Errors
+    ///
+    /// When the proxy or TLS options are invalid, the client could not be initialized or the
+    /// cassette could not be opened
+    pub fn new(options: HttpOptions, headers: Vec<(&'static str, String)>) -> Result<Self> {
+        let client = build_client(&options)?;
+        let cassette = options.cassette.clone().map(Cassette::open).transpose()?;
+        Ok(Self {
+            options,
+            client,
+            cassette,
+            headers,
+        })
+    }
+
+    /// Builds a request with any method, for requests that do not fit the helpers below
+    pub fn request(&self, method: Method, url: Url) -> RequestBuilder {
+        self.client.request(method, url)
+    }
+
+    /// Builds a get request and calls the sender
+    ///
+    /// # Errors
+    ///
+    /// When it could not fulfill a GET request
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        url: Url,
+        query: Option<Vec<(&str, String)>>,
+    ) -> Result<T> {
+        let client = match &query {
+            Some(q) => self.client.get(url).query(&q),
+            None => self.client.get(url),
+        };
+
+        trace!({ "message": "Get request query", "query": query });
+
+        self.send::<T>(client).await
+    }
+
+    /// Builds a patch request and calls the sender
+    ///
+    /// # Errors
+    ///
+    /// When it could not fulfill a PATCH request
+    pub async fn patch<T: DeserializeOwned>(
+        &self,
+        url: Url,
+        query: Option<Vec<(&str, String)>>,
+    ) -> Result<T> {
+        let client = match &query {
+            Some(q) => self.client.patch(url).query(&q),
+            None => self.client.patch(url),
+        };
+
+        trace!({ "message": "Patch request query", "query": query});
+
+        self.send::<T>(client).await
+    }
+
+    /// Builds a delete request and calls the sender
+    ///
+    /// # Errors
+    ///
+    /// When it could not fulfill a DELETE request
+    pub async fn delete<T: DeserializeOwned>(&self, url: Url) -> Result<T> {
+        let client = self.client.delete(url);
+
+        self.send::<T>(client).await
+    }
+
+    /// Builds a post request and calls the sender
+    ///
+    /// # Errors
+    ///
+    /// When it could not fulfill a POST request
+    pub async fn post<T: DeserializeOwned>(
+        &self,
+        url: Url,
+        query: Option<Vec<(&str, String)>>,
+        body: Option<Value>,
+    ) -> Result<T> {
+        let client = self.client.post(url).query(&query);
+
+        let client = match &body {
+            Some(b) => client.json(&b),
+            None => client,
+        };
+
+        trace!({ "message": "Post request body", "body": body, "query": query });
+
+        self.send::<T>(client).await
+    }
+
+    /// Sends any request
+    ///
+    /// # Errors
+    ///
+    /// When it could not fulfill the given request
+    pub async fn send<T: DeserializeOwned>(&self, client: RequestBuilder) -> Result<T> {
+        let client = self
+            .headers
+            .iter()
+            .fold(client, |client, (name, value)| client.header(*name, value));
+
+        let request = client
+            .build()
+            .map_err(|e| Error::InvalidAgentUrl(e.to_string()))?;
+        let context = HttpContext::new(request.method().as_str(), request.url().as_str());
+
+        if self.options.dry_run {
+            log!({ "curl": curl_command(&self.options, &request) });
+            return Err(Error::DryRun(context));
+        }
+
+        let (status_code, text) = match &self.cassette {
+            Some(cassette) if cassette.is_replaying() => {
+                let response = cassette
+                    .replay(&recorded_request(&request))
+                    .ok_or_else(|| Error::UnrecordedRequest(context.clone()))?;
+                debug!({ "message": "Replayed response from the cassette" });
+                (response.status, response.text())
+            }
+            Some(cassette) => {
+                let recorded = recorded_request(&request);
+                let (status_code, text) = self.transmit(request, &context).await?;
+                cassette.record(recorded, RecordedResponse::new(status_code, &text))?;
+                (status_code, text)
+            }
+            None => self.transmit(request, &context).await?,
+        };
+        let context = context.with_response(status_code, &text);
+
+        if !(200..=299).contains(&status_code) {
+            return Err(Error::from_response(context));
+        }
+
+        let parsed: Value = if text.trim().is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&text).map_err(|e| {
+                warn!({ "error": e.to_string() });
+                Error::UnableToParseResponse(context.clone())
+            })?
+        };
+        debug!({ "response": parsed });
+        let parsed = if parsed.is_null() {
+            serde_json::json!(())
+        } else {
+            parsed
+        };
+        serde_json::from_value(parsed).map_err(|e| {
+            warn!({ "error": e.to_string() });
+            Error::UnableToParseResponse(context)
+        })
+    }
+
+    /// Sends the request and reads the status code and the body of the response
+    ///
+    /// # Errors
+    ///
+    /// When the agent could not be reached or did not respond in time
+    async fn transmit(&self, request: Request, context: &HttpContext) -> Result<(u16, String)> {
+        trace!({ "message": "About to send request" });
+        let response = match self.execute(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!({ "message": "request failed", "error": e.to_string() });
+                if e.is_timeout() {
+                    return Err(Error::Timeout(context.clone()));
+                }
+                return Err(Error::UnreachableUrl(context.clone(), e.to_string()));
+            }
+        };
+
+        let status_code = response.status().as_u16();
+        debug!({ "status_code": status_code });
+        let text = response.text().await.map_err(|e| {
+            if e.is_timeout() {
+                Error::Timeout(context.clone())
+            } else {
+                Error::UnreachableUrl(context.clone(), e.to_string())
+            }
+        })?;
+        Ok((status_code, text))
+    }
+
+    /// Executes a request. Idempotent requests are retried with an exponential backoff when the
+    /// agent could not be reached or responded with a 502 or 503
+    async fn execute(&self, request: Request) -> reqwest::Result<Response> {
+        let retries = if request.method().is_idempotent() {
+            self.options.retries
+        } else {
+            0
+        };
+
+        let mut attempt = 0;
+        loop {
+            let result = match request.try_clone() {
+                Some(r) => self.client.execute(r).await,
+                None => return self.client.execute(request).await,
+            };
+            let should_retry = match &result {
+                Ok(response) => matches!(response.status().as_u16(), 502 | 503),
+                Err(e) => e.is_connect(),
+            };
+            if !should_retry || attempt >= retries {
+                return result;
+            }
+
+            let delay = self
+                .options
+                .retry_backoff
+                .saturating_mul(2_u32.saturating_pow(attempt));
+            attempt += 1;
+            debug!({ "message": format!("Request failed, retrying ({attempt}/{retries}) in {}ms", delay.as_millis()) });
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Builds the http client that is shared by every request of the agent
+///
+/// # Errors
+///
+/// When the proxy or TLS options are invalid or the client could not be initialized
+fn build_client(options: &HttpOptions) -> Result<Client> {
+    let user_agent = options
+        .user_agent
+        .clone()
+        .unwrap_or_else(|| format!("siera/{}", env!("CARGO_PKG_VERSION")));
+
+    let builder = Client::builder()
+        .connect_timeout(options.connect_timeout)
+        .timeout(options.request_timeout)
+        .user_agent(user_agent);
+
+    let builder = match &options.proxy {
+        Some(proxy) => {
+            builder.proxy(Proxy::all(proxy).map_err(|e| Error::InvalidHttpOptions(e.to_string()))?)
+        }
+        None => builder,
+    };
+
+    let builder = options
+        .tls
+        .ca_certificates()?
+        .iter()
+        .try_fold(builder, |builder, pem| {
+            Certificate::from_pem(pem)
+                .map(|certificate| builder.add_root_certificate(certificate))
+                .map_err(|e| Error::InvalidTlsOptions(e.to_string()))
+        })?;
+
+    let builder = match options.tls.client_identity()? {
+        Some((cert, key)) => builder.identity(
+            Identity::from_pkcs8_pem(&cert, &key)
+                .map_err(|e| Error::InvalidTlsOptions(e.to_string()))?,
+        ),
+        None => builder,
+    };
+
+    builder
+        .danger_accept_invalid_certs(options.tls.insecure_skip_verify)
+        .danger_accept_invalid_hostnames(options.tls.insecure_skip_verify)
+        .build()
+        .map_err(|e| Error::InvalidHttpOptions(e.to_string()))
+}
+
+/// The request as it is stored in a cassette
+fn recorded_request(request: &Request) -> RecordedRequest {
+    RecordedRequest::new(
+        request.method().as_str(),
+        request.url().as_str(),
+        request.body().and_then(reqwest::Body::as_bytes),
+    )
+}
+
+/// The request as a `curl` command that is printed on a dry run
+fn curl_command(options: &HttpOptions, request: &Request) -> String {
+    let headers: Vec<(&str, &str)> = request
+        .headers()
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.to_str().unwrap_or_default()))
+        .collect();
+    let body = request
+        .body()
+        .and_then(reqwest::Body::as_bytes)
+        .map(String::from_utf8_lossy);
+    options.curl_command(
+        request.method().as_str(),
+        request.url().as_str(),
+        &headers,
+        body.as_deref(),
+    )
+}
@@ -2,8 +2,30 @@
 //! or aries-framework-javascript REST. This does not contain any functionality as that should be
 //! handled by the other crates implementing this crate
 
+#[cfg(feature = "http")]
+#[macro_use]
+extern crate siera_logger;
+
+/// Generic agent that combines every module and the registry of available agents
+pub mod agent;
+
+/// Recording and replaying of the requests of an agent, so agents can be tested without a network
+pub mod cassette;
+
+/// Http transport that the cloudagents send their requests with
+#[cfg(feature = "http")]
+pub mod http;
+
+/// In-memory agent that implements every module, for testing without a cloudagent
+#[cfg(feature = "mock")]
+pub mod mock;
+
 /// Error module that includes the user-level errors and the result type
 pub mod error;
 
+/// Enums that are represented by a string, like states, roles and protocols
+#[macro_use]
+pub mod string_enum;
+
 /// Traits and structures for a generic cloudagent
 pub mod modules;
@@ -0,0 +1,1568 @@
+use crate::agent::{Agent, Capability};
+use crate::error::{Error, HttpContext, Result};
+use crate::modules::api::{ApiModule, ApiRequestOptions};
+use crate::modules::basic_message::{BasicMessageModule, SendBasicMessageOptions};
+use crate::modules::connection::{
+    Connection, ConnectionCreateInvitationOptions, ConnectionGetAllOptions, ConnectionModule,
+    ConnectionReceiveInvitationOptions, ConnectionRole, ConnectionState, Invitation, Rfc23State,
+};
+use crate::modules::credential::{
+    CredentialAttribute, CredentialCreateOfferOptions, CredentialExchangeState,
+    CredentialGetAllOptions, CredentialModule, CredentialOfferOptions, CredentialOfferResponse,
+};
+use crate::modules::credential_definition::{
+    CredentialDefinition, CredentialDefinitionCreateOptions, CredentialDefinitionCreateResponse,
+    CredentialDefinitionGetAllResponse, CredentialDefinitionModule,
+};
+use crate::modules::feature::{Disclose, DiscoverFeaturesResponse, FeatureModule, Protocol};
+use crate::modules::multitenancy::{MultitenancyCreateResponse, MultitenancyModule};
+use crate::modules::oob::{
+    OobAttachment, OobConnection, OobConnectionCreateInvitationOptions,
+    OobConnectionCreateInvitationResponse, OobConnectionReceiveInvitationOptions, OobModule,
+    OobReceiveOptions,
+};
+use crate::modules::proof::{
+    PresentationExchangeState, ProofCreateRequestOptions, ProofGetAllOptions, ProofModule,
+    ProofRequestOptions, ProofRequestResponse,
+};
+use crate::modules::schema::{Schema, SchemaCreateOptions, SchemaModule, SchemasGetAllResponse};
+use crate::modules::wallet::{
+    CreateLocalDidOptions, Did, DidEndpoint, DidList, DidMethod, DidPosture, SetDidEndpointOptions,
+    WalletModule,
+};
+use crate::modules::webhook::WebhookModule;
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::sync::{Mutex, MutexGuard};
+
+/// Url that is used in the http context of the errors, and as the endpoint of the invitations
+/// unless another one is supplied
+const MOCK_URL: &str = "mock://agent";
+
+/// Label of the invitations, unless another one is supplied
+const MOCK_LABEL: &str = "Mock agent";
+
+/// Timestamp of every record that the mock agent creates
+const CREATED_AT: &str = "2022-01-01T00:00:00.000000Z";
+
+/// Protocols that are disclosed when the features of the mock agent are discovered
+const PROTOCOLS: [&str; 6] = [
+    "https://didcomm.org/basicmessage/1.0",
+    "https://didcomm.org/connections/1.0",
+    "https://didcomm.org/didexchange/1.0",
+    "https://didcomm.org/issue-credential/1.0",
+    "https://didcomm.org/out-of-band/1.1",
+    "https://didcomm.org/present-proof/1.0",
+];
+
+/// A call that was made on the mock agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockCall {
+    /// Module of the trait that was called
+    pub module: Capability,
+
+    /// Name of the method that was called, e.g. `send_offer`
+    pub method: &'static str,
+
+    /// Arguments of the call as JSON
+    pub arguments: Value,
+}
+
+/// Records of the mock agent and everything that the tests scripted
+#[derive(Debug, Default)]
+struct MockState {
+    /// Counter used to generate unique ids
+    counter: u64,
+
+    /// Every call in the order it was made
+    calls: Vec<MockCall>,
+
+    /// Errors that are returned by the next calls to a method, instead of handling them
+    failures: HashMap<(Capability, &'static str), VecDeque<Error>>,
+
+    /// Responses of the admin api, keyed by the method and path
+    api_responses: HashMap<(String, String), Value>,
+
+    /// Webhook events that are not delivered yet
+    events: VecDeque<Value>,
+
+    /// Connection ids of the invitations, keyed by their recipient key
+    invitation_keys: HashMap<String, String>,
+
+    /// Connections, keyed by id
+    connections: BTreeMap<String, Connection>,
+
+    /// Credential exchanges, keyed by id
+    credential_exchanges: BTreeMap<String, CredentialOfferResponse>,
+
+    /// Presentation exchanges, keyed by id
+    proof_exchanges: BTreeMap<String, ProofRequestResponse>,
+
+    /// Basic messages that were sent, with the connection id they were sent to
+    messages: Vec<(String, String)>,
+
+    /// Schemas, keyed by id
+    schemas: BTreeMap<String, Schema>,
+
+    /// Credential definitions, keyed by id
+    credential_definitions: BTreeMap<String, CredentialDefinition>,
+
+    /// DIDs in the wallet, keyed by DID
+    dids: BTreeMap<String, Did>,
+
+    /// Endpoints of the DIDs, keyed by DID
+    endpoints: BTreeMap<String, String>,
+
+    /// Sub wallets, keyed by wallet id
+    wallets: BTreeMap<String, MultitenancyCreateResponse>,
+
+    /// Service endpoints of the agents whose out of band invitations were received, keyed by the
+    /// connection id
+    inviter_endpoints: HashMap<String, String>,
+}
+
+impl MockState {
+    /// Next unique number
+    fn next(&mut self) -> u64 {
+        self.counter += 1;
+        self.counter
+    }
+
+    /// Unique id formatted like the uuids of the cloudagents
+    fn uuid(&mut self) -> String {
+        format!("00000000-0000-4000-8000-{:012x}", self.next())
+    }
+
+    /// Unique unqualified DID formatted like an indy DID
+    fn did(&mut self) -> String {
+        format!("MockDid{:015}", self.next())
+    }
+
+    /// Queue a webhook event with the topic and record, like aca-py sends them
+    fn emit(&mut self, topic: &str, record: &impl Serialize) {
+        self.events
+            .push_back(json!({ "topic": topic, "payload": record }));
+    }
+
+    /// Add a connection and emit its event
+    fn insert_connection(&mut self, connection: Connection) -> Connection {
+        self.emit("connections", &connection);
+        self.connections
+            .insert(connection.id.clone(), connection.clone());
+        connection
+    }
+
+    /// The `requests~attach` of an invitation with the messages of the attached exchanges
+    fn attachment_requests(&self, attachments: &[OobAttachment], path: &str) -> Result<Vec<Value>> {
+        attachments
+            .iter()
+            .enumerate()
+            .map(|(index, attachment)| {
+                let message = match attachment {
+                    OobAttachment::CredentialOffer(id) => self
+                        .credential_exchanges
+                        .get(id)
+                        .map(|e| e.credential_offer_dict.clone()),
+                    OobAttachment::PresentationRequest(id) => self
+                        .proof_exchanges
+                        .get(id)
+                        .map(|e| e.presentation_request_dict.clone()),
+                };
+                message
+                    .map(|message| {
+                        json!({
+                            "@id": format!("request-{index}"),
+                            "mime-type": "application/json",
+                            "data": { "json": message },
+                        })
+                    })
+                    .ok_or_else(|| not_found("POST", path))
+            })
+            .collect()
+    }
+
+    /// A usable connection with the agent that has the endpoint, which is how the inviter of an
+    /// invitation is recognized, as the mock agent has no public DIDs
+    fn connection_with_inviter(&self, endpoint: &str) -> Option<Connection> {
+        self.inviter_endpoints
+            .iter()
+            .filter(|(_, e)| *e == endpoint)
+            .filter_map(|(id, _)| self.connections.get(id))
+            .find(|c| {
+                matches!(
+                    c.state,
+                    ConnectionState::Active | ConnectionState::Completed
+                )
+            })
+            .cloned()
+    }
+
+    /// The connection with the id, which must be ready to send messages over
+    fn active_connection(&self, id: &str, path: &str) -> Result<&Connection> {
+        let connection = self
+            .connections
+            .get(id)
+            .ok_or_else(|| not_found("POST", path))?;
+        match connection.state {
+            ConnectionState::Active | ConnectionState::Response | ConnectionState::Completed => {
+                Ok(connection)
+            }
+            ref state => Err(invalid_request(
+                "POST",
+                path,
+                &format!("Connection {id} is not ready (state: {state})"),
+            )),
+        }
+    }
+
+    /// Add a credential exchange of the issuer for a new offer
+    fn offer_credential(
+        &mut self,
+        connection_id: String,
+        cred_def_id: &str,
+        attributes: &[CredentialAttribute],
+        path: &str,
+    ) -> Result<CredentialOfferResponse> {
+        let schema_id = self
+            .credential_definitions
+            .get(cred_def_id)
+            .map(|d| d.schema_id.clone())
+            .ok_or_else(|| not_found("POST", path))?;
+        let preview = json!({
+            "@type": "https://didcomm.org/issue-credential/1.0/credential-preview",
+            "attributes": attributes,
+        });
+        let exchange = CredentialOfferResponse {
+            auto_issue: true,
+            auto_offer: false,
+            connection_id,
+            created_at: CREATED_AT.to_owned(),
+            credential_definition_id: cred_def_id.to_owned(),
+            credential_exchange_id: self.uuid(),
+            credential_offer: json!({ "cred_def_id": cred_def_id, "schema_id": schema_id }),
+            credential_offer_dict: json!({ "credential_preview": preview }),
+            credential_proposal_dict: json!({ "credential_proposal": preview }),
+            initiator: String::from("self"),
+            role: String::from("issuer"),
+            schema_id,
+            state: CredentialExchangeState::OfferSent,
+            thread_id: self.uuid(),
+            updated_at: CREATED_AT.to_owned(),
+        };
+        self.emit("issue_credential", &exchange);
+        self.credential_exchanges
+            .insert(exchange.credential_exchange_id.clone(), exchange.clone());
+        Ok(exchange)
+    }
+
+    /// Add a presentation exchange of the verifier for a new proof request
+    fn request_proof(
+        &mut self,
+        connection_id: String,
+        name: &str,
+        attributes: &[String],
+        predicates: &[(String, String, i32)],
+    ) -> ProofRequestResponse {
+        let requested_attributes: serde_json::Map<String, Value> = attributes
+            .iter()
+            .map(|name| (format!("0_{name}_uuid"), json!({ "name": name })))
+            .collect();
+        let requested_predicates: serde_json::Map<String, Value> = predicates
+            .iter()
+            .map(|(name, p_type, p_value)| {
+                (
+                    format!("0_{name}_GE_uuid"),
+                    json!({ "name": name, "p_type": p_type, "p_value": p_value }),
+                )
+            })
+            .collect();
+        let presentation_request = json!({
+            "name": name,
+            "version": "1.0",
+            "nonce": self.next().to_string(),
+            "requested_attributes": requested_attributes,
+            "requested_predicates": requested_predicates,
+        });
+        let exchange = ProofRequestResponse {
+            state: PresentationExchangeState::RequestSent,
+            presentation_request: presentation_request.clone(),
+            updated_at: CREATED_AT.to_owned(),
+            connection_id,
+            thread_id: self.uuid(),
+            presentation_request_dict: json!({
+                "@type": "https://didcomm.org/present-proof/1.0/request-presentation",
+                "request_presentations~attach": [{ "data": { "json": presentation_request } }],
+            }),
+            role: String::from("verifier"),
+            auto_present: false,
+            presentation_exchange_id: self.uuid(),
+            created_at: CREATED_AT.to_owned(),
+            initiator: String::from("self"),
+            presentation: Value::Null,
+            verified: None,
+        };
+        self.emit("present_proof", &exchange);
+        self.proof_exchanges
+            .insert(exchange.presentation_exchange_id.clone(), exchange.clone());
+        exchange
+    }
+
+    /// Move the credential exchange on, when it is in the state the action of the path expects
+    fn move_credential_exchange(
+        &mut self,
+        id: &str,
+        action: &str,
+        from: &CredentialExchangeState,
+        to: CredentialExchangeState,
+    ) -> Result<CredentialOfferResponse> {
+        let path = format!("/issue-credential/records/{id}/{action}");
+        let exchange = self
+            .credential_exchanges
+            .get_mut(id)
+            .ok_or_else(|| not_found("POST", &path))?;
+        if &exchange.state != from {
+            return Err(invalid_request(
+                "POST",
+                &path,
+                &format!("Credential exchange {id} is in state {}", exchange.state),
+            ));
+        }
+        exchange.state = to;
+        let exchange = exchange.clone();
+        self.emit("issue_credential", &exchange);
+        Ok(exchange)
+    }
+
+    /// Move the presentation exchange on, when it is in the state the action of the path expects
+    fn move_proof_exchange(
+        &mut self,
+        id: &str,
+        action: &str,
+        from: &PresentationExchangeState,
+        update: impl FnOnce(&mut ProofRequestResponse),
+    ) -> Result<ProofRequestResponse> {
+        let path = format!("/present-proof/records/{id}/{action}");
+        let exchange = self
+            .proof_exchanges
+            .get_mut(id)
+            .ok_or_else(|| not_found("POST", &path))?;
+        if &exchange.state != from {
+            return Err(invalid_request(
+                "POST",
+                &path,
+                &format!("Presentation exchange {id} is in state {}", exchange.state),
+            ));
+        }
+        update(exchange);
+        let exchange = exchange.clone();
+        self.emit("present_proof", &exchange);
+        Ok(exchange)
+    }
+}
+
+/// In-memory agent that implements every module, for testing frontends and automations without
+/// a cloudagent
+///
+/// Records are kept in memory and move through the states of the protocols when the tests
+/// advance them, as if the other party responded. Every call is recorded and errors can be
+/// scripted for the next calls to a method. Records that do not exist result in the same errors
+/// as a cloudagent returns, e.g. `Error::UrlDoesNotExist`.
+#[derive(Debug)]
+pub struct MockAgent {
+    /// Service endpoint of the invitations
+    endpoint: String,
+
+    /// Label of the invitations
+    label: String,
+
+    /// Records, calls and scripted behaviour, shared by every module
+    state: Mutex<MockState>,
+}
+
+impl Default for MockAgent {
+    fn default() -> Self {
+        Self::with_endpoint(MOCK_URL, MOCK_LABEL)
+    }
+}
+
+impl MockAgent {
+    /// Mock agent without any records
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mock agent that uses the endpoint and label in its invitations, so another agent can
+    /// respond to them
+    pub fn with_endpoint(endpoint: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            label: label.into(),
+            state: Mutex::default(),
+        }
+    }
+
+    /// Label that is used in the invitations
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Unique id formatted like the uuids of the cloudagents
+    #[must_use]
+    pub fn next_id(&self) -> String {
+        self.state().uuid()
+    }
+
+    /// Unique unqualified DID formatted like an indy DID
+    #[must_use]
+    pub fn next_did(&self) -> String {
+        self.state().did()
+    }
+
+    /// Lock the state. A test that panicked while holding the lock does not poison it for the
+    /// assertions that follow
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Record a call and return the scripted error for it, if there is one
+    fn call(
+        &self,
+        module: Capability,
+        method: &'static str,
+        arguments: Value,
+    ) -> Result<MutexGuard<'_, MockState>> {
+        let mut state = self.state();
+        state.calls.push(MockCall {
+            module,
+            method,
+            arguments,
+        });
+        let failure = state
+            .failures
+            .get_mut(&(module, method))
+            .and_then(VecDeque::pop_front);
+        match failure {
+            Some(error) => Err(error),
+            None => Ok(state),
+        }
+    }
+
+    /// Return the error from the next call to the method of the module, instead of handling it.
+    /// Errors that are scripted for the same method are returned in order
+    pub fn fail_next(&self, module: Capability, method: &'static str, error: Error) -> &Self {
+        self.state()
+            .failures
+            .entry((module, method))
+            .or_default()
+            .push_back(error);
+        self
+    }
+
+    /// Respond to a request to the admin api with the method and path
+    pub fn stub_api(
+        &self,
+        method: impl Into<String>,
+        path: impl Into<String>,
+        response: Value,
+    ) -> &Self {
+        self.state()
+            .api_responses
+            .insert((method.into().to_uppercase(), path.into()), response);
+        self
+    }
+
+    /// Queue a webhook event that is delivered by the next `listen`
+    pub fn push_event(&self, event: Value) -> &Self {
+        self.state().events.push_back(event);
+        self
+    }
+
+    /// Remove and return the webhook events that are not delivered yet
+    #[must_use]
+    pub fn take_events(&self) -> Vec<Value> {
+        self.state().events.drain(..).collect()
+    }
+
+    /// Every call that was made, in order
+    #[must_use]
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state().calls.clone()
+    }
+
+    /// The calls that were made to the method of the module, in order
+    #[must_use]
+    pub fn calls_to(&self, module: Capability, method: &str) -> Vec<MockCall> {
+        self.state()
+            .calls
+            .iter()
+            .filter(|c| c.module == module && c.method == method)
+            .cloned()
+            .collect()
+    }
+
+    /// Add a connection with the state, as if it was established with another agent
+    pub fn add_connection(&self, state: ConnectionState) -> Connection {
+        let mut mock = self.state();
+        let id = mock.uuid();
+        let did = mock.did();
+        let their_did = mock.did();
+        mock.insert_connection(Connection {
+            id,
+            created_at: CREATED_AT.to_owned(),
+            did: Some(did),
+            state,
+            role: ConnectionRole::Inviter,
+            auto_accept: None,
+            alias: None,
+            their_label: Some(String::from("Mock counterparty")),
+            their_did: Some(their_did),
+            verkey: None,
+            invitation_msg_id: None,
+        })
+    }
+
+    /// Every connection
+    #[must_use]
+    pub fn connections(&self) -> Vec<Connection> {
+        self.state().connections.values().cloned().collect()
+    }
+
+    /// The connection that was created with the invitation that has the recipient key
+    #[must_use]
+    pub fn connection_by_invitation_key(&self, key: &str) -> Option<Connection> {
+        let mock = self.state();
+        mock.invitation_keys
+            .get(key)
+            .and_then(|id| mock.connections.get(id))
+            .cloned()
+    }
+
+    /// Change the connection, e.g. when the other party responded, and emit its event
+    ///
+    /// # Errors
+    ///
+    /// When the connection does not exist
+    pub fn update_connection(
+        &self,
+        id: &str,
+        update: impl FnOnce(&mut Connection),
+    ) -> Result<Connection> {
+        let mut mock = self.state();
+        let connection = mock
+            .connections
+            .get_mut(id)
+            .ok_or_else(|| not_found("GET", &format!("/connections/{id}")))?;
+        update(connection);
+        let connection = connection.clone();
+        mock.emit("connections", &connection);
+        Ok(connection)
+    }
+
+    /// Add a credential exchange that was started by another agent and emit its event
+    pub fn add_credential_exchange(&self, exchange: CredentialOfferResponse) -> &Self {
+        let mut mock = self.state();
+        mock.emit("issue_credential", &exchange);
+        mock.credential_exchanges
+            .insert(exchange.credential_exchange_id.clone(), exchange);
+        self
+    }
+
+    /// Change the credential exchange and emit its event
+    ///
+    /// # Errors
+    ///
+    /// When the exchange does not exist
+    pub fn update_credential_exchange(
+        &self,
+        id: &str,
+        update: impl FnOnce(&mut CredentialOfferResponse),
+    ) -> Result<CredentialOfferResponse> {
+        let mut mock = self.state();
+        let exchange = mock
+            .credential_exchanges
+            .get_mut(id)
+            .ok_or_else(|| not_found("GET", &format!("/issue-credential/records/{id}")))?;
+        update(exchange);
+        let exchange = exchange.clone();
+        mock.emit("issue_credential", &exchange);
+        Ok(exchange)
+    }
+
+    /// Add a presentation exchange that was started by another agent and emit its event
+    pub fn add_proof_exchange(&self, exchange: ProofRequestResponse) -> &Self {
+        let mut mock = self.state();
+        mock.emit("present_proof", &exchange);
+        mock.proof_exchanges
+            .insert(exchange.presentation_exchange_id.clone(), exchange);
+        self
+    }
+
+    /// Change the presentation exchange and emit its event
+    ///
+    /// # Errors
+    ///
+    /// When the exchange does not exist
+    pub fn update_proof_exchange(
+        &self,
+        id: &str,
+        update: impl FnOnce(&mut ProofRequestResponse),
+    ) -> Result<ProofRequestResponse> {
+        let mut mock = self.state();
+        let exchange = mock
+            .proof_exchanges
+            .get_mut(id)
+            .ok_or_else(|| not_found("GET", &format!("/present-proof/records/{id}")))?;
+        update(exchange);
+        let exchange = exchange.clone();
+        mock.emit("present_proof", &exchange);
+        Ok(exchange)
+    }
+
+    /// Every credential exchange
+    #[must_use]
+    pub fn credential_exchanges(&self) -> Vec<CredentialOfferResponse> {
+        self.state()
+            .credential_exchanges
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Every presentation exchange
+    #[must_use]
+    pub fn proof_exchanges(&self) -> Vec<ProofRequestResponse> {
+        self.state().proof_exchanges.values().cloned().collect()
+    }
+
+    /// Every basic message that was sent, with the connection id it was sent to
+    #[must_use]
+    pub fn messages(&self) -> Vec<(String, String)> {
+        self.state().messages.clone()
+    }
+
+    /// Move the connection to its next state, as if the other party responded
+    ///
+    /// `invitation` -> `request` -> `response` -> `active`
+    ///
+    /// # Errors
+    ///
+    /// When the connection does not exist or can not move to another state
+    pub fn advance_connection(&self, id: &str) -> Result<ConnectionState> {
+        let mut mock = self.state();
+        let path = format!("/connections/{id}");
+        let their_did = mock.did();
+        let connection = mock
+            .connections
+            .get_mut(id)
+            .ok_or_else(|| not_found("GET", &path))?;
+        connection.state = match connection.state {
+            ConnectionState::Start => ConnectionState::Invitation,
+            ConnectionState::Invitation => ConnectionState::Request,
+            ConnectionState::Request => ConnectionState::Response,
+            ConnectionState::Response => ConnectionState::Active,
+            ref state => return Err(final_state("GET", &path, state)),
+        };
+        connection.their_did.get_or_insert(their_did);
+        let connection = connection.clone();
+        mock.emit("connections", &connection);
+        Ok(connection.state)
+    }
+
+    /// Move the credential exchange to its next state, as if the holder responded
+    ///
+    /// `offer_sent` -> `request_received` -> `credential_issued` -> `credential_acked`
+    ///
+    /// # Errors
+    ///
+    /// When the exchange does not exist or can not move to another state
+    pub fn advance_credential_exchange(&self, id: &str) -> Result<CredentialExchangeState> {
+        let mut mock = self.state();
+        let path = format!("/issue-credential/records/{id}");
+        let exchange = mock
+            .credential_exchanges
+            .get_mut(id)
+            .ok_or_else(|| not_found("GET", &path))?;
+        exchange.state = match exchange.state {
+            CredentialExchangeState::ProposalReceived => CredentialExchangeState::OfferSent,
+            CredentialExchangeState::OfferSent => CredentialExchangeState::RequestReceived,
+            CredentialExchangeState::RequestReceived => CredentialExchangeState::CredentialIssued,
+            CredentialExchangeState::CredentialIssued => CredentialExchangeState::CredentialAcked,
+            ref state => return Err(final_state("GET", &path, state)),
+        };
+        let exchange = exchange.clone();
+        mock.emit("issue_credential", &exchange);
+        Ok(exchange.state)
+    }
+
+    /// Move the presentation exchange to its next state, as if the prover responded
+    ///
+    /// `request_sent` -> `presentation_received` -> `verified`
+    ///
+    /// # Errors
+    ///
+    /// When the exchange does not exist or can not move to another state
+    pub fn advance_proof_exchange(&self, id: &str) -> Result<PresentationExchangeState> {
+        let mut mock = self.state();
+        let path = format!("/present-proof/records/{id}");
+        let exchange = mock
+            .proof_exchanges
+            .get_mut(id)
+            .ok_or_else(|| not_found("GET", &path))?;
+        exchange.state = match exchange.state {
+            PresentationExchangeState::ProposalReceived => PresentationExchangeState::RequestSent,
+            PresentationExchangeState::RequestSent => {
+                PresentationExchangeState::PresentationReceived
+            }
+            PresentationExchangeState::PresentationReceived => PresentationExchangeState::Verified,
+            ref state => return Err(final_state("GET", &path, state)),
+        };
+        let exchange = exchange.clone();
+        mock.emit("present_proof", &exchange);
+        Ok(exchange.state)
+    }
+}
+
+/// Error of a cloudagent for a record or endpoint that does not exist
+fn not_found(method: &str, path: &str) -> Error {
+    Error::UrlDoesNotExist(
+        HttpContext::new(method, format!("{MOCK_URL}{path}"))
+            .with_response(404, "Record not found"),
+    )
+}
+
+/// Error of a cloudagent for a request that it rejected
+fn invalid_request(method: &str, path: &str, reason: &str) -> Error {
+    Error::InvalidRequest(
+        HttpContext::new(method, format!("{MOCK_URL}{path}")).with_response(400, reason),
+    )
+}
+
+/// Error for a record that can not move to another state
+fn final_state(method: &str, path: &str, state: &impl Display) -> Error {
+    invalid_request(
+        method,
+        path,
+        &format!("Record can not move on from state {state}"),
+    )
+}
+
+impl Display for MockAgent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "mock")
+    }
+}
+
+impl Agent for MockAgent {
+    fn capabilities(&self) -> &'static [Capability] {
+        Capability::ALL
+    }
+}
+
+#[async_trait]
+impl ApiModule for MockAgent {
+    async fn request(&self, options: ApiRequestOptions) -> Result<Value> {
+        let method = options.method.to_uppercase();
+        let mock = self.call(
+            Capability::Api,
+            "request",
+            json!({
+                "method": method,
+                "path": options.path,
+                "query": options.query,
+                "body": options.body,
+            }),
+        )?;
+        mock.api_responses
+            .get(&(method.clone(), options.path.clone()))
+            .cloned()
+            .ok_or_else(|| not_found(&method, &options.path))
+    }
+}
+
+#[async_trait]
+impl BasicMessageModule for MockAgent {
+    async fn send_message(&self, options: SendBasicMessageOptions) -> Result<()> {
+        let mut mock = self.call(
+            Capability::BasicMessage,
+            "send_message",
+            json!({ "connection_id": options.connection_id, "message": options.message }),
+        )?;
+        let path = format!("/connections/{}/send-message", options.connection_id);
+        mock.active_connection(&options.connection_id, &path)?;
+        mock.messages.push((options.connection_id, options.message));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ConnectionModule for MockAgent {
+    async fn get_all(&self, options: ConnectionGetAllOptions) -> Result<Vec<Connection>> {
+        let mock = self.call(Capability::Connection, "get_all", json!(options))?;
+        Ok(mock
+            .connections
+            .values()
+            .filter(|c| options.alias.is_none() || c.alias == options.alias)
+            .filter(|c| options.my_did.is_none() || c.did == options.my_did)
+            .filter(|c| options.their_did.is_none() || c.their_did == options.their_did)
+            .filter(|c| options.state.as_ref().is_none_or(|s| &c.state == s))
+            .filter(|c| options.their_role.as_ref().is_none_or(|r| &c.role == r))
+            .filter(|c| {
+                options.invitation_msg_id.is_none()
+                    || c.invitation_msg_id == options.invitation_msg_id
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<Connection> {
+        let mock = self.call(Capability::Connection, "get_by_id", json!({ "id": id }))?;
+        mock.connections
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| not_found("GET", &format!("/connections/{id}")))
+    }
+
+    async fn create_invitation(
+        &self,
+        options: ConnectionCreateInvitationOptions,
+    ) -> Result<Invitation> {
+        let mut mock = self.call(
+            Capability::Connection,
+            "create_invitation",
+            json!({
+                "auto_accept": options.auto_accept,
+                "multi_use": options.multi_use,
+                "alias": options.alias,
+            }),
+        )?;
+        let id = mock.uuid();
+        let recipient_key = mock.did();
+        let invitation = json!({
+            "@type": "https://didcomm.org/connections/1.0/invitation",
+            "@id": mock.uuid(),
+            "label": self.label,
+            "recipientKeys": [recipient_key],
+            "serviceEndpoint": self.endpoint,
+        });
+        mock.invitation_keys.insert(recipient_key, id.clone());
+        mock.insert_connection(Connection {
+            id: id.clone(),
+            created_at: CREATED_AT.to_owned(),
+            did: None,
+            state: ConnectionState::Invitation,
+            role: ConnectionRole::Invitee,
+            auto_accept: Some(json!(if options.auto_accept {
+                "auto"
+            } else {
+                "manual"
+            })),
+            alias: options.alias,
+            their_label: None,
+            their_did: None,
+            verkey: None,
+            invitation_msg_id: None,
+        });
+        Ok(Invitation {
+            invitation_url: format!("{}?c_i={id}", self.endpoint),
+            invitation,
+            id,
+        })
+    }
+
+    async fn receive_invitation(
+        &self,
+        invitation: ConnectionReceiveInvitationOptions,
+    ) -> Result<Connection> {
+        let mut mock = self.call(
+            Capability::Connection,
+            "receive_invitation",
+            json!(invitation),
+        )?;
+        if invitation.service_endpoint.is_none() && invitation.did.is_none() {
+            return Err(invalid_request(
+                "POST",
+                "/connections/receive-invitation",
+                "Invitation has neither a did nor a service endpoint",
+            ));
+        }
+        let connection = Connection {
+            id: mock.uuid(),
+            created_at: CREATED_AT.to_owned(),
+            did: Some(mock.did()),
+            state: ConnectionState::Request,
+            role: ConnectionRole::Inviter,
+            auto_accept: None,
+            alias: None,
+            their_label: invitation.label,
+            their_did: invitation.did,
+            verkey: None,
+            invitation_msg_id: None,
+        };
+        Ok(mock.insert_connection(connection))
+    }
+}
+
+#[async_trait]
+impl CredentialModule for MockAgent {
+    async fn send_offer(&self, options: CredentialOfferOptions) -> Result<CredentialOfferResponse> {
+        let mut mock = self.call(
+            Capability::Credential,
+            "send_offer",
+            json!({
+                "connection_id": options.connection_id,
+                "cred_def_id": options.cred_def_id,
+                "attributes": options.attributes,
+            }),
+        )?;
+        let path = "/issue-credential/send-offer";
+        mock.active_connection(&options.connection_id, path)?;
+        mock.offer_credential(
+            options.connection_id,
+            &options.cred_def_id,
+            &options.attributes,
+            path,
+        )
+    }
+
+    async fn create_offer(
+        &self,
+        options: CredentialCreateOfferOptions,
+    ) -> Result<CredentialOfferResponse> {
+        let mut mock = self.call(
+            Capability::Credential,
+            "create_offer",
+            json!({
+                "cred_def_id": options.cred_def_id,
+                "attributes": options.attributes,
+            }),
+        )?;
+        mock.offer_credential(
+            String::new(),
+            &options.cred_def_id,
+            &options.attributes,
+            "/issue-credential/create-offer",
+        )
+    }
+
+    async fn get_all(
+        &self,
+        options: CredentialGetAllOptions,
+    ) -> Result<Vec<CredentialOfferResponse>> {
+        let mock = self.call(Capability::Credential, "get_all", json!(options))?;
+        Ok(mock
+            .credential_exchanges
+            .values()
+            .filter(|e| {
+                options
+                    .connection_id
+                    .as_ref()
+                    .is_none_or(|c| &e.connection_id == c)
+            })
+            .filter(|e| options.role.as_ref().is_none_or(|r| &e.role == r))
+            .filter(|e| options.state.as_ref().is_none_or(|s| &e.state == s))
+            .filter(|e| options.thread_id.as_ref().is_none_or(|t| &e.thread_id == t))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<CredentialOfferResponse> {
+        let mock = self.call(Capability::Credential, "get_by_id", json!({ "id": id }))?;
+        mock.credential_exchanges
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| not_found("GET", &format!("/issue-credential/records/{id}")))
+    }
+
+    async fn accept_offer(&self, id: String) -> Result<CredentialOfferResponse> {
+        let mut mock = self.call(Capability::Credential, "accept_offer", json!({ "id": id }))?;
+        mock.move_credential_exchange(
+            &id,
+            "send-request",
+            &CredentialExchangeState::OfferReceived,
+            CredentialExchangeState::RequestSent,
+        )
+    }
+
+    async fn issue(&self, id: String) -> Result<CredentialOfferResponse> {
+        let mut mock = self.call(Capability::Credential, "issue", json!({ "id": id }))?;
+        mock.move_credential_exchange(
+            &id,
+            "issue",
+            &CredentialExchangeState::RequestReceived,
+            CredentialExchangeState::CredentialIssued,
+        )
+    }
+
+    async fn store(&self, id: String) -> Result<CredentialOfferResponse> {
+        let mut mock = self.call(Capability::Credential, "store", json!({ "id": id }))?;
+        mock.move_credential_exchange(
+            &id,
+            "store",
+            &CredentialExchangeState::CredentialReceived,
+            CredentialExchangeState::CredentialAcked,
+        )
+    }
+}
+
+#[async_trait]
+impl CredentialDefinitionModule for MockAgent {
+    async fn create(
+        &self,
+        options: CredentialDefinitionCreateOptions,
+    ) -> Result<CredentialDefinitionCreateResponse> {
+        let mut mock = self.call(Capability::CredentialDefinition, "create", json!(options))?;
+        let seq_no = mock
+            .schemas
+            .get(&options.schema_id)
+            .and_then(|s| s.seq_no)
+            .ok_or_else(|| not_found("POST", "/credential-definitions"))?;
+        let issuer = options
+            .schema_id
+            .split(':')
+            .next()
+            .unwrap_or_default()
+            .to_owned();
+        let tag = if options.tag.is_empty() {
+            String::from("default")
+        } else {
+            options.tag
+        };
+        let id = format!("{issuer}:3:CL:{seq_no}:{tag}");
+        let revocation = options
+            .support_revocation
+            .then(|| json!({ "max_cred_num": options.revocation_registry_size.unwrap_or(1000) }));
+        mock.credential_definitions.insert(
+            id.clone(),
+            CredentialDefinition {
+                ver: String::from("1.0"),
+                id: id.clone(),
+                schema_id: options.schema_id,
+                type_field: String::from("CL"),
+                tag,
+                value: json!({ "primary": {}, "revocation": revocation }),
+            },
+        );
+        Ok(CredentialDefinitionCreateResponse {
+            credential_definition_id: id,
+        })
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<CredentialDefinition> {
+        let mock = self.call(
+            Capability::CredentialDefinition,
+            "get_by_id",
+            json!({ "id": id }),
+        )?;
+        mock.credential_definitions
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| not_found("GET", &format!("/credential-definitions/{id}")))
+    }
+
+    async fn get_all(&self) -> Result<CredentialDefinitionGetAllResponse> {
+        let mock = self.call(Capability::CredentialDefinition, "get_all", Value::Null)?;
+        Ok(CredentialDefinitionGetAllResponse {
+            credential_definition_ids: mock.credential_definitions.keys().cloned().collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl FeatureModule for MockAgent {
+    async fn discover_features(&self) -> Result<DiscoverFeaturesResponse> {
+        let mut mock = self.call(Capability::Feature, "discover_features", Value::Null)?;
+        Ok(DiscoverFeaturesResponse {
+            query_msg: json!({
+                "@type": "https://didcomm.org/discover-features/1.0/query",
+                "query": "*",
+            }),
+            disclose: Disclose {
+                type_field: String::from("https://didcomm.org/discover-features/1.0/disclose"),
+                id: mock.uuid(),
+                protocols: PROTOCOLS
+                    .iter()
+                    .map(|pid| Protocol {
+                        pid: (*pid).to_owned(),
+                    })
+                    .collect(),
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl MultitenancyModule for MockAgent {
+    async fn create(&self) -> Result<MultitenancyCreateResponse> {
+        let mut mock = self.call(Capability::Multitenancy, "create", Value::Null)?;
+        let wallet_id = mock.uuid();
+        let wallet = MultitenancyCreateResponse {
+            created_at: CREATED_AT.to_owned(),
+            key_management_mode: String::from("managed"),
+            settings: json!({
+                "wallet.type": "askar",
+                "wallet.name": format!("mock-{wallet_id}"),
+                "wallet.id": wallet_id,
+            }),
+            token: format!("mock-token-{}", mock.next()),
+            updated_at: CREATED_AT.to_owned(),
+            wallet_id: wallet_id.clone(),
+        };
+        mock.wallets.insert(wallet_id, wallet.clone());
+        Ok(wallet)
+    }
+
+    async fn remove(&self, wallet_id: String) -> Result<()> {
+        let mut mock = self.call(
+            Capability::Multitenancy,
+            "remove",
+            json!({ "wallet_id": wallet_id }),
+        )?;
+        mock.wallets
+            .remove(&wallet_id)
+            .map(|_| ())
+            .ok_or_else(|| not_found("POST", &format!("/multitenancy/wallet/{wallet_id}/remove")))
+    }
+}
+
+#[async_trait]
+impl OobModule for MockAgent {
+    async fn create_invitation(
+        &self,
+        options: OobConnectionCreateInvitationOptions,
+    ) -> Result<OobConnectionCreateInvitationResponse> {
+        let mut mock = self.call(
+            Capability::Oob,
+            "create_invitation",
+            json!({
+                "auto_accept": options.auto_accept,
+                "handshake_protocol": options.handshake_protocol,
+                "multi_use": options.multi_use,
+                "alias": options.alias,
+                "attachments": options.attachments,
+            }),
+        )?;
+        let path = "/out-of-band/create-invitation";
+        if options.handshake_protocol.is_none() && options.attachments.is_empty() {
+            return Err(invalid_request(
+                "POST",
+                path,
+                "Invitation must have a handshake protocol or an attachment",
+            ));
+        }
+        let requests = mock.attachment_requests(&options.attachments, path)?;
+
+        let message_id = mock.uuid();
+        let recipient_key = mock.did();
+        let mut invitation = json!({
+            "@type": "https://didcomm.org/out-of-band/1.1/invitation",
+            "@id": message_id,
+            "label": self.label,
+            "services": [{
+                "id": "#inline",
+                "type": "did-communication",
+                "recipientKeys": [format!("did:key:{recipient_key}")],
+                "serviceEndpoint": self.endpoint,
+            }],
+        });
+        if !requests.is_empty() {
+            invitation["requests~attach"] = json!(requests);
+        }
+        if let Some(protocol) = &options.handshake_protocol {
+            invitation["handshake_protocols"] = json!([protocol.message_type_uri()]);
+
+            let connection_id = mock.uuid();
+            mock.invitation_keys
+                .insert(format!("did:key:{recipient_key}"), connection_id.clone());
+            let connection = Connection {
+                id: connection_id,
+                created_at: CREATED_AT.to_owned(),
+                did: None,
+                state: ConnectionState::Invitation,
+                role: ConnectionRole::Invitee,
+                auto_accept: Some(json!(if options.auto_accept {
+                    "auto"
+                } else {
+                    "manual"
+                })),
+                alias: options.alias.clone(),
+                their_label: None,
+                their_did: None,
+                verkey: None,
+                invitation_msg_id: Some(message_id.clone()),
+            };
+            mock.insert_connection(connection);
+        }
+        Ok(OobConnectionCreateInvitationResponse {
+            invitation_message_id: message_id.clone(),
+            invitation,
+            oob_id: None,
+            invitation_url: format!("{}?oob={message_id}", self.endpoint),
+            alias: options.alias,
+        })
+    }
+
+    async fn receive_invitation(
+        &self,
+        invitation: OobConnectionReceiveInvitationOptions,
+        options: OobReceiveOptions,
+    ) -> Result<OobConnection> {
+        let mut mock = self.call(
+            Capability::Oob,
+            "receive_invitation",
+            json!({
+                "invitation": invitation,
+                "alias": options.alias,
+                "use_existing_connection": options.use_existing_connection,
+            }),
+        )?;
+        let path = "/out-of-band/receive-invitation";
+        let message_id = invitation
+            .get("@id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid_request("POST", path, "Invitation has no @id"))?
+            .to_owned();
+        let their_label = invitation
+            .get("label")
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+        let endpoint = invitation
+            .pointer("/services/0/serviceEndpoint")
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+
+        let existing = endpoint
+            .as_deref()
+            .filter(|_| options.use_existing_connection)
+            .and_then(|endpoint| mock.connection_with_inviter(endpoint));
+        let reused = existing.is_some();
+        let connection = if let Some(connection) = existing {
+            connection
+        } else {
+            let id = mock.uuid();
+            let did = mock.did();
+            let connection = mock.insert_connection(Connection {
+                id,
+                created_at: CREATED_AT.to_owned(),
+                did: Some(did),
+                state: ConnectionState::Request,
+                role: ConnectionRole::Inviter,
+                auto_accept: Some(json!("auto")),
+                alias: options.alias,
+                their_label: their_label.clone(),
+                their_did: None,
+                verkey: None,
+                invitation_msg_id: Some(message_id.clone()),
+            });
+            if let Some(endpoint) = endpoint {
+                mock.inviter_endpoints
+                    .insert(connection.id.clone(), endpoint);
+            }
+            connection
+        };
+
+        let request_id = mock.uuid();
+        Ok(OobConnection {
+            their_role: connection.role,
+            created_at: connection.created_at.clone(),
+            my_did: connection.did,
+            connection_id: connection.id,
+            their_did: connection.their_did,
+            invitation_key: None,
+            state: connection.state,
+            routing_state: String::from("none"),
+            accept: String::from("auto"),
+            their_label,
+            invitation_mode: String::from("once"),
+            rfc23_state: if reused {
+                Rfc23State::Completed
+            } else {
+                Rfc23State::RequestSent
+            },
+            updated_at: connection.created_at,
+            alias: connection.alias,
+            request_id: Some(request_id),
+            invitation_msg_id: Some(message_id),
+        })
+    }
+}
+
+#[async_trait]
+impl ProofModule for MockAgent {
+    async fn send_request(&self, options: ProofRequestOptions) -> Result<ProofRequestResponse> {
+        let mut mock = self.call(
+            Capability::Proof,
+            "send_request",
+            json!({
+                "connection_id": options.connection_id,
+                "name": options.name,
+                "attributes": options.attributes,
+                "predicates": options.predicates,
+            }),
+        )?;
+        mock.active_connection(&options.connection_id, "/present-proof/send-request")?;
+        Ok(mock.request_proof(
+            options.connection_id,
+            &options.name,
+            &options.attributes,
+            &options.predicates,
+        ))
+    }
+
+    async fn create_request(
+        &self,
+        options: ProofCreateRequestOptions,
+    ) -> Result<ProofRequestResponse> {
+        let mut mock = self.call(
+            Capability::Proof,
+            "create_request",
+            json!({
+                "name": options.name,
+                "attributes": options.attributes,
+                "predicates": options.predicates,
+            }),
+        )?;
+        Ok(mock.request_proof(
+            String::new(),
+            &options.name,
+            &options.attributes,
+            &options.predicates,
+        ))
+    }
+
+    async fn get_all(&self, options: ProofGetAllOptions) -> Result<Vec<ProofRequestResponse>> {
+        let mock = self.call(Capability::Proof, "get_all", json!(options))?;
+        Ok(mock
+            .proof_exchanges
+            .values()
+            .filter(|e| {
+                options
+                    .connection_id
+                    .as_ref()
+                    .is_none_or(|c| &e.connection_id == c)
+            })
+            .filter(|e| options.role.as_ref().is_none_or(|r| &e.role == r))
+            .filter(|e| options.state.as_ref().is_none_or(|s| &e.state == s))
+            .filter(|e| options.thread_id.as_ref().is_none_or(|t| &e.thread_id == t))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<ProofRequestResponse> {
+        let mock = self.call(Capability::Proof, "get_by_id", json!({ "id": id }))?;
+        mock.proof_exchanges
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| not_found("GET", &format!("/present-proof/records/{id}")))
+    }
+
+    async fn present(&self, id: String) -> Result<ProofRequestResponse> {
+        let mut mock = self.call(Capability::Proof, "present", json!({ "id": id }))?;
+        mock.move_proof_exchange(
+            &id,
+            "send-presentation",
+            &PresentationExchangeState::RequestReceived,
+            |e| e.state = PresentationExchangeState::PresentationSent,
+        )
+    }
+
+    async fn verify(&self, id: String) -> Result<ProofRequestResponse> {
+        let mut mock = self.call(Capability::Proof, "verify", json!({ "id": id }))?;
+        mock.move_proof_exchange(
+            &id,
+            "verify-presentation",
+            &PresentationExchangeState::PresentationReceived,
+            |e| {
+                e.state = PresentationExchangeState::Verified;
+                e.verified = Some(String::from("true"));
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl SchemaModule for MockAgent {
+    async fn create(&self, options: SchemaCreateOptions) -> Result<Schema> {
+        let mut mock = self.call(Capability::Schema, "create", json!(options))?;
+        if options.attributes.is_empty() {
+            return Err(invalid_request(
+                "POST",
+                "/schemas",
+                "A schema needs at least one attribute",
+            ));
+        }
+        let issuer = match mock
+            .dids
+            .values()
+            .find(|d| d.posture == Some(DidPosture::Public))
+        {
+            Some(Did { did: Some(did), .. }) => did.clone(),
+            _ => String::from("MockIssuer000000000000"),
+        };
+        let id = format!("{issuer}:2:{}:{}", options.name, options.version);
+        if mock.schemas.contains_key(&id) {
+            return Err(invalid_request(
+                "POST",
+                "/schemas",
+                &format!("Schema {id} already exists"),
+            ));
+        }
+        let seq_no = i32::try_from(mock.next()).unwrap_or(i32::MAX);
+        let schema = Schema {
+            ver: String::from("1.0"),
+            id: id.clone(),
+            name: options.name,
+            version: options.version,
+            attr_names: options.attributes,
+            seq_no: Some(seq_no),
+        };
+        mock.schemas.insert(id, schema.clone());
+        Ok(schema)
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<Schema> {
+        let mock = self.call(Capability::Schema, "get_by_id", json!({ "id": id }))?;
+        mock.schemas
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| not_found("GET", &format!("/schemas/{id}")))
+    }
+
+    async fn get_all(&self) -> Result<SchemasGetAllResponse> {
+        let mock = self.call(Capability::Schema, "get_all", Value::Null)?;
+        Ok(SchemasGetAllResponse {
+            schema_ids: mock.schemas.keys().cloned().collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl WalletModule for MockAgent {
+    async fn get_wallet_dids(&self, options: Did) -> Result<DidList> {
+        let mock = self.call(Capability::Wallet, "get_wallet_dids", json!(options))?;
+        Ok(DidList(
+            mock.dids
+                .values()
+                .filter(|d| options.did.is_none() || d.did == options.did)
+                .filter(|d| options.verkey.is_none() || d.verkey == options.verkey)
+                .filter(|d| options.key_type.is_none() || d.key_type == options.key_type)
+                .filter(|d| options.method.is_none() || d.method == options.method)
+                .filter(|d| options.posture.is_none() || d.posture == options.posture)
+                .cloned()
+                .collect(),
+        ))
+    }
+
+    async fn create_local_did(&self, options: CreateLocalDidOptions) -> Result<Did> {
+        let mut mock = self.call(Capability::Wallet, "create_local_did", json!(options))?;
+        let key = mock.did();
+        let did = match options.method {
+            DidMethod::Key => format!("did:key:z6Mk{key}"),
+            _ => key.clone(),
+        };
+        let record = Did {
+            did: Some(did.clone()),
+            key_type: Some(options.options.key_type),
+            method: Some(options.method),
+            posture: Some(DidPosture::WalletOnly),
+            verkey: Some(format!("{key}Verkey")),
+        };
+        mock.dids.insert(did, record.clone());
+        Ok(record)
+    }
+
+    async fn rotate_keypair(&self, did: String) -> Result<()> {
+        let mut mock = self.call(Capability::Wallet, "rotate_keypair", json!({ "did": did }))?;
+        let counter = mock.next();
+        let record = mock.dids.get_mut(&did).ok_or_else(|| {
+            not_found(
+                "PATCH",
+                &format!("/wallet/did/local/rotate-keypair?did={did}"),
+            )
+        })?;
+        record.verkey = Some(format!("MockVerkey{counter:012}"));
+        Ok(())
+    }
+
+    async fn fetch_public_did(&self) -> Result<Did> {
+        let mock = self.call(Capability::Wallet, "fetch_public_did", Value::Null)?;
+        mock.dids
+            .values()
+            .find(|d| d.posture == Some(DidPosture::Public))
+            .cloned()
+            .ok_or_else(|| not_found("GET", "/wallet/did/public"))
+    }
+
+    async fn assign_public_did(&self, did: String) -> Result<Did> {
+        let mut mock = self.call(
+            Capability::Wallet,
+            "assign_public_did",
+            json!({ "did": did }),
+        )?;
+        let path = format!("/wallet/did/public?did={did}");
+        if !mock.dids.contains_key(&did) {
+            return Err(not_found("POST", &path));
+        }
+        if mock.dids[&did].method == Some(DidMethod::Key) {
+            return Err(invalid_request(
+                "POST",
+                &path,
+                "Only sov DIDs can be the public DID",
+            ));
+        }
+        for record in mock.dids.values_mut() {
+            if record.posture == Some(DidPosture::Public) {
+                record.posture = Some(DidPosture::Posted);
+            }
+        }
+        let record = mock
+            .dids
+            .get_mut(&did)
+            .ok_or_else(|| not_found("POST", &path))?;
+        record.posture = Some(DidPosture::Public);
+        Ok(record.clone())
+    }
+
+    async fn fetch_did_endpoint(&self, did: String) -> Result<DidEndpoint> {
+        let mock = self.call(
+            Capability::Wallet,
+            "fetch_did_endpoint",
+            json!({ "did": did }),
+        )?;
+        mock.endpoints
+            .get(&did)
+            .map(|endpoint| DidEndpoint {
+                did: did.clone(),
+                endpoint: endpoint.clone(),
+            })
+            .ok_or_else(|| not_found("GET", &format!("/wallet/get-did-endpoint?did={did}")))
+    }
+
+    async fn set_did_endpoint(&self, options: SetDidEndpointOptions) -> Result<()> {
+        let mut mock = self.call(Capability::Wallet, "set_did_endpoint", json!(options))?;
+        if !mock.dids.contains_key(&options.did) {
+            return Err(not_found("POST", "/wallet/set-did-endpoint"));
+        }
+        mock.endpoints.insert(options.did, options.endpoint);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl WebhookModule for MockAgent {
+    /// Deliver every queued event, including the events of the records that changed, and
+    /// return. Unlike a cloudagent the mock agent does not keep listening for new events
+    async fn listen(&self, on_event: fn(Value)) -> Result<()> {
+        drop(self.call(Capability::Webhook, "listen", Value::Null)?);
+        for event in self.take_events() {
+            on_event(event);
+        }
+        Ok(())
+    }
+}
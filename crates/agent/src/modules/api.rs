@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use std::fmt::Display;
+
+use crate::error::{unsupported, Result};
+
+/// Options for a request to any endpoint of the admin api of the agent
+#[derive(Debug, Clone, Default)]
+pub struct ApiRequestOptions {
+    /// Http method, e.g. `GET`
+    pub method: String,
+
+    /// Path relative to the endpoint of the agent, e.g. `/connections`. A query string in the
+    /// path is added to the query
+    pub path: String,
+
+    /// Query parameters
+    pub query: Vec<(String, String)>,
+
+    /// JSON body of the request
+    pub body: Option<Value>,
+}
+
+/// Generic cloudagent api module for endpoints that have no dedicated module (yet)
+#[async_trait]
+pub trait ApiModule: Display {
+    /// Send a request to the admin api with the authentication of the agent and return the
+    /// response
+    async fn request(&self, _options: ApiRequestOptions) -> Result<Value> {
+        unsupported(self)
+    }
+}
@@ -1,5 +1,6 @@
-use crate::error::Result;
+use crate::error::{unsupported, Result};
 use async_trait::async_trait;
+use std::fmt::Display;
 
 /// Options that are supplied when sending a basic message to another agent
 pub struct SendBasicMessageOptions {
@@ -12,7 +13,9 @@ pub struct SendBasicMessageOptions {
 
 /// Generic cloudagent basic message module
 #[async_trait]
-pub trait BasicMessageModule {
+pub trait BasicMessageModule: Display {
     /// Send a basic message to another agent via the connection id
-    async fn send_message(&self, options: SendBasicMessageOptions) -> Result<()>;
+    async fn send_message(&self, _options: SendBasicMessageOptions) -> Result<()> {
+        unsupported(self)
+    }
 }
@@ -1,7 +1,8 @@
-use crate::error::Result;
+use crate::error::{unsupported, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::fmt::Display;
 
 /// Query filters applied to retrieving all the connections
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -9,9 +10,8 @@ pub struct ConnectionGetAllOptions {
     /// Optional `alias` to filter on
     pub alias: Option<String>,
 
-    // TODO: enum
     /// Optional `connection protocol` to filter on
-    pub connection_protocol: Option<String>,
+    pub connection_protocol: Option<ConnectionProtocol>,
 
     /// Optional `invitation key` to filter on
     pub invitation_key: Option<String>,
@@ -19,16 +19,114 @@ pub struct ConnectionGetAllOptions {
     /// Optional `my did` to filter on
     pub my_did: Option<String>,
 
-    // TODO: enum
     /// Optional `state` to filter on
-    pub state: Option<String>,
+    pub state: Option<ConnectionState>,
 
     /// Optional `their did` to filter on
     pub their_did: Option<String>,
 
-    // TODO: enum
     /// Optional `their role` to filter on
-    pub their_role: Option<String>,
+    pub their_role: Option<ConnectionRole>,
+
+    /// Optional `invitation message id` to filter on, e.g. to find every connection of a multi
+    /// use invitation
+    pub invitation_msg_id: Option<String>,
+}
+
+string_enum! {
+    /// State of a connection
+    ConnectionState {
+        /// The connection record is created
+        Start => "start",
+        /// An invitation was created or received
+        Invitation => "invitation",
+        /// A connection request was sent or received
+        Request => "request",
+        /// A connection response was sent or received
+        Response => "response",
+        /// The connection can be used
+        Active => "active",
+        /// The connection is completed
+        Completed => "completed",
+        /// The connection failed
+        Error => "error",
+        /// The connection was abandoned by one of the parties
+        Abandoned => "abandoned",
+    }
+}
+
+impl ConnectionState {
+    /// Whether the connection is established, so messages can be sent over it. `active` is the
+    /// final state of RFC 160 connections and `completed` the one of RFC 23 connections
+    #[must_use]
+    pub const fn is_ready(&self) -> bool {
+        matches!(self, Self::Active | Self::Completed)
+    }
+}
+
+string_enum! {
+    /// Role of the other party in a connection
+    ConnectionRole {
+        /// They received the invitation
+        Invitee => "invitee",
+        /// They sent the connection request
+        Requester => "requester",
+        /// They created the invitation
+        Inviter => "inviter",
+        /// They responded to the connection request
+        Responder => "responder",
+    }
+}
+
+string_enum! {
+    /// Protocol used to establish a connection
+    #[derive(Default)]
+    ConnectionProtocol {
+        /// Aries RFC 0160: Connection Protocol
+        Connections => "connections/1.0"
+            | "did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/connections/1.0"
+            | "https://didcomm.org/connections/1.0",
+        /// Aries RFC 0023: DID Exchange Protocol
+        #[default]
+        DidExchange => "didexchange/1.0"
+            | "did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/didexchange/1.0"
+            | "https://didcomm.org/didexchange/1.0",
+    }
+}
+
+impl ConnectionProtocol {
+    /// Message type uri of the protocol, as used in the handshake protocols of an oob invitation
+    #[must_use]
+    pub fn message_type_uri(&self) -> String {
+        match self {
+            Self::Other(uri) => uri.clone(),
+            protocol => format!("did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/{protocol}"),
+        }
+    }
+}
+
+string_enum! {
+    /// State of a connection according to Aries RFC 0023
+    Rfc23State {
+        /// The connection record is created
+        Start => "start",
+        /// We sent the invitation
+        InvitationSent => "invitation-sent",
+        /// We received the invitation
+        InvitationReceived => "invitation-received",
+        /// We sent the request
+        RequestSent => "request-sent",
+        /// We received the request
+        RequestReceived => "request-received",
+        /// We sent the response
+        ResponseSent => "response-sent",
+        /// We received the response
+        ResponseReceived => "response-received",
+        /// The exchange is completed
+        Completed => "completed",
+        /// The exchange was abandoned
+        Abandoned => "abandoned",
+    }
 }
 
 /// Create invitation response
@@ -62,17 +160,21 @@ pub struct Connection {
     pub did: Option<String>,
 
     /// The current connection state
-    pub state: String,
+    pub state: ConnectionState,
 
     /// Their role in the connection process
     #[serde(alias = "their_role")]
-    pub role: String,
+    pub role: ConnectionRole,
 
     /// Auto accept state
     #[serde(alias = "accept", alias = "autoAcceptConnection")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_accept: Option<Value>,
 
+    /// Alias of the connection as set by this agent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+
     /// Their label as set when the connection is initialized
     #[serde(alias = "theirLabel")]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -85,6 +187,10 @@ pub struct Connection {
     /// (AFJ) verkey used
     #[serde(skip_serializing_if = "Option::is_none")]
     pub verkey: Option<String>,
+
+    /// Id of the message of the invitation the connection was created with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invitation_msg_id: Option<String>,
 }
 
 /// Options supplied by the frontend for creating an invitation
@@ -145,22 +251,30 @@ pub struct ConnectionReceiveInvitationOptions {
 
 /// Generic cloudagent connection module
 #[async_trait]
-pub trait ConnectionModule {
+pub trait ConnectionModule: Display {
     /// Gets all the connections
-    async fn get_all(&self, options: ConnectionGetAllOptions) -> Result<Vec<Connection>>;
+    async fn get_all(&self, _options: ConnectionGetAllOptions) -> Result<Vec<Connection>> {
+        unsupported(self)
+    }
 
     /// Get a connection by id
-    async fn get_by_id(&self, id: String) -> Result<Connection>;
+    async fn get_by_id(&self, _id: String) -> Result<Connection> {
+        unsupported(self)
+    }
 
     /// Create an invitation
     async fn create_invitation(
         &self,
-        options: ConnectionCreateInvitationOptions,
-    ) -> Result<Invitation>;
+        _options: ConnectionCreateInvitationOptions,
+    ) -> Result<Invitation> {
+        unsupported(self)
+    }
 
     /// Receive and accept a connection
     async fn receive_invitation(
         &self,
-        invitation: ConnectionReceiveInvitationOptions,
-    ) -> Result<Connection>;
+        _invitation: ConnectionReceiveInvitationOptions,
+    ) -> Result<Connection> {
+        unsupported(self)
+    }
 }
@@ -1,11 +1,12 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::fmt::Display;
 
-use crate::error::Result;
+use crate::error::{unsupported, Result};
 
 /// Response structure gotten from the cloudagent when offering a credential
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CredentialOfferResponse {
     /// Whether it should auto issue the credential
     pub auto_issue: bool,
@@ -13,7 +14,8 @@ pub struct CredentialOfferResponse {
     /// Whether it should auto offer the credential
     pub auto_offer: bool,
 
-    /// The conneciton id used to send the credential to
+    /// The conneciton id used to send the credential to. Empty for an offer without a connection
+    #[serde(default)]
     pub connection_id: String,
 
     /// When the credential offer was created
@@ -44,7 +46,7 @@ pub struct CredentialOfferResponse {
     pub schema_id: String,
 
     /// What the state is in the credential offer flow
-    pub state: String,
+    pub state: CredentialExchangeState,
 
     /// Thread id to refer to this offer
     pub thread_id: String,
@@ -53,6 +55,78 @@ pub struct CredentialOfferResponse {
     pub updated_at: String,
 }
 
+string_enum! {
+    /// State of a credential exchange. The values of issue credential 2.0 are accepted as aliases
+    CredentialExchangeState {
+        /// The holder sent a proposal
+        ProposalSent => "proposal_sent" | "proposal-sent",
+        /// The issuer received a proposal
+        ProposalReceived => "proposal_received" | "proposal-received",
+        /// The issuer sent an offer
+        OfferSent => "offer_sent" | "offer-sent",
+        /// The holder received an offer
+        OfferReceived => "offer_received" | "offer-received",
+        /// The holder sent a request
+        RequestSent => "request_sent" | "request-sent",
+        /// The issuer received a request
+        RequestReceived => "request_received" | "request-received",
+        /// The issuer issued the credential
+        CredentialIssued => "credential_issued" | "credential-issued",
+        /// The holder received the credential
+        CredentialReceived => "credential_received" | "credential-received",
+        /// The holder acknowledged the credential
+        CredentialAcked => "credential_acked",
+        /// The credential was revoked
+        CredentialRevoked => "credential_revoked" | "credential-revoked",
+        /// The exchange is finished
+        Done => "done",
+        /// The exchange was abandoned
+        Abandoned => "abandoned",
+    }
+}
+
+/// A single attribute of a credential
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialAttribute {
+    /// Name of the attribute as it is in the credential definition
+    pub name: String,
+
+    /// Value of the attribute. Binary values, like images, are base64 encoded
+    pub value: String,
+
+    /// MIME type of the value, e.g. `image/png`. Plain text when this is not set
+    #[serde(rename = "mime-type", alias = "mime_type", alias = "mimeType")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+impl CredentialAttribute {
+    /// Plain text attribute without a MIME type
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            mime_type: None,
+        }
+    }
+}
+
+/// Query filters applied to retrieving all the credential exchanges
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CredentialGetAllOptions {
+    /// Optional `connection id` to filter on
+    pub connection_id: Option<String>,
+
+    /// Optional `role` to filter on, `issuer` or `holder`
+    pub role: Option<String>,
+
+    /// Optional `state` to filter on
+    pub state: Option<CredentialExchangeState>,
+
+    /// Optional `thread id` to filter on
+    pub thread_id: Option<String>,
+}
+
 /// Options when offering a credential
 pub struct CredentialOfferOptions {
     /// Connection id to send the credential to
@@ -61,17 +135,64 @@ pub struct CredentialOfferOptions {
     /// Credential definition id used a blueprint for the credential
     pub cred_def_id: String,
 
-    /// Keys that are in the credential definition that must be filled in
-    pub keys: Vec<String>,
+    /// Attributes that are in the credential definition that must be filled in
+    pub attributes: Vec<CredentialAttribute>,
+}
+
+/// Options when creating a credential offer that is not sent to a connection, e.g. to attach it
+/// to an out of band invitation
+pub struct CredentialCreateOfferOptions {
+    /// Credential definition id used a blueprint for the credential
+    pub cred_def_id: String,
 
-    /// Values for the keys
-    /// these are index-matched
-    pub values: Vec<String>,
+    /// Attributes that are in the credential definition that must be filled in
+    pub attributes: Vec<CredentialAttribute>,
 }
 
 /// Generic cloudagent credential module
 #[async_trait]
-pub trait CredentialModule {
+pub trait CredentialModule: Display {
     /// Send a credential offer to the connection id supplied in the options
-    async fn send_offer(&self, options: CredentialOfferOptions) -> Result<CredentialOfferResponse>;
+    async fn send_offer(
+        &self,
+        _options: CredentialOfferOptions,
+    ) -> Result<CredentialOfferResponse> {
+        unsupported(self)
+    }
+
+    /// Create a credential offer without sending it
+    async fn create_offer(
+        &self,
+        _options: CredentialCreateOfferOptions,
+    ) -> Result<CredentialOfferResponse> {
+        unsupported(self)
+    }
+
+    /// Gets all the credential exchanges
+    async fn get_all(
+        &self,
+        _options: CredentialGetAllOptions,
+    ) -> Result<Vec<CredentialOfferResponse>> {
+        unsupported(self)
+    }
+
+    /// Get a credential exchange by id
+    async fn get_by_id(&self, _id: String) -> Result<CredentialOfferResponse> {
+        unsupported(self)
+    }
+
+    /// Accept a received offer by requesting the credential
+    async fn accept_offer(&self, _id: String) -> Result<CredentialOfferResponse> {
+        unsupported(self)
+    }
+
+    /// Issue the credential of a received request
+    async fn issue(&self, _id: String) -> Result<CredentialOfferResponse> {
+        unsupported(self)
+    }
+
+    /// Store a received credential in the wallet
+    async fn store(&self, _id: String) -> Result<CredentialOfferResponse> {
+        unsupported(self)
+    }
 }
@@ -1,7 +1,8 @@
-use crate::error::Result;
+use crate::error::{unsupported, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::fmt::Display;
 
 /// Options provided when registering a credential definition on the ledger
 #[derive(Debug, Deserialize, Serialize, Default)]
@@ -39,7 +40,7 @@ pub struct CredentialDefinitionGetByIdResponse {
 }
 
 /// A credential definition structure
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CredentialDefinition {
     /// Version of the credential definition
     pub ver: String,
@@ -72,16 +73,22 @@ pub struct CredentialDefinitionGetAllResponse {
 
 /// Generic cloudagent credential definition module
 #[async_trait]
-pub trait CredentialDefinitionModule {
+pub trait CredentialDefinitionModule: Display {
     /// Register a credential definition on the ledger
     async fn create(
         &self,
-        options: CredentialDefinitionCreateOptions,
-    ) -> Result<CredentialDefinitionCreateResponse>;
+        _options: CredentialDefinitionCreateOptions,
+    ) -> Result<CredentialDefinitionCreateResponse> {
+        unsupported(self)
+    }
 
     /// Get the registered credential definition by id
-    async fn get_by_id(&self, id: String) -> Result<CredentialDefinition>;
+    async fn get_by_id(&self, _id: String) -> Result<CredentialDefinition> {
+        unsupported(self)
+    }
 
     /// Get all the registered credential definitions
-    async fn get_all(&self) -> Result<CredentialDefinitionGetAllResponse>;
+    async fn get_all(&self) -> Result<CredentialDefinitionGetAllResponse> {
+        unsupported(self)
+    }
 }
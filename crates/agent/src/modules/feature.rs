@@ -1,8 +1,9 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::fmt::Display;
 
-use crate::error::Result;
+use crate::error::{unsupported, Result};
 
 /// Repsonse of the cloudagent for discovering features
 #[derive(Debug, Serialize, Deserialize)]
@@ -38,7 +39,9 @@ pub struct Protocol {
 
 /// Generic cloudagent feature module
 #[async_trait]
-pub trait FeatureModule {
+pub trait FeatureModule: Display {
     /// Requests all the features from the cloudagent
-    async fn discover_features(&self) -> Result<DiscoverFeaturesResponse>;
+    async fn discover_features(&self) -> Result<DiscoverFeaturesResponse> {
+        unsupported(self)
+    }
 }
@@ -1,3 +1,6 @@
+/// Api module for a generic cloudagent
+pub mod api;
+
 /// Connection module for a generic cloudagent
 pub mod connection;
 
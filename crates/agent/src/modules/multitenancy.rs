@@ -1,6 +1,7 @@
 use async_trait::async_trait;
+use std::fmt::Display;
 
-use crate::error::Result;
+use crate::error::{unsupported, Result};
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
@@ -29,10 +30,14 @@ pub struct MultitenancyCreateResponse {
 
 /// Multitenancy module for a generic cloudagent
 #[async_trait]
-pub trait MultitenancyModule {
+pub trait MultitenancyModule: Display {
     /// Create a new subwallet
-    async fn create(&self) -> Result<MultitenancyCreateResponse>;
+    async fn create(&self) -> Result<MultitenancyCreateResponse> {
+        unsupported(self)
+    }
 
     /// Remove a subwallet
-    async fn remove(&self, wallet_id: String) -> Result<()>;
+    async fn remove(&self, _wallet_id: String) -> Result<()> {
+        unsupported(self)
+    }
 }
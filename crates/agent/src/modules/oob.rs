@@ -1,7 +1,9 @@
-use crate::error::Result;
+use crate::error::{unsupported, Result};
+use crate::modules::connection::{ConnectionProtocol, ConnectionRole, ConnectionState, Rfc23State};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::fmt::Display;
 
 /// Response from the cloudagent when an invitation is created
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -28,7 +30,7 @@ pub struct OobConnectionCreateInvitationResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OobConnection {
     /// Their role in the connection process
-    pub their_role: String,
+    pub their_role: ConnectionRole,
 
     /// When the connection is created
     pub created_at: String,
@@ -46,7 +48,7 @@ pub struct OobConnection {
     pub invitation_key: Option<String>,
 
     /// The current connection state
-    pub state: String,
+    pub state: ConnectionState,
 
     /// The routing state of the current connection
     pub routing_state: String,
@@ -61,7 +63,7 @@ pub struct OobConnection {
     pub invitation_mode: String,
 
     /// State of the connection according to rfc23
-    pub rfc23_state: String,
+    pub rfc23_state: Rfc23State,
 
     /// Last time that the connection was updated
     pub updated_at: String,
@@ -76,6 +78,19 @@ pub struct OobConnection {
     pub invitation_msg_id: Option<String>,
 }
 
+/// Exchange that is attached to an invitation, so it can be answered without a connection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id")]
+pub enum OobAttachment {
+    /// Id of the credential exchange of a created credential offer
+    #[serde(rename = "credential-offer")]
+    CredentialOffer(String),
+
+    /// Id of the presentation exchange of a created proof request
+    #[serde(rename = "present-proof")]
+    PresentationRequest(String),
+}
+
 /// Options supplied by the frontend for creating an invitation
 #[derive(Debug, Default)]
 pub struct OobConnectionCreateInvitationOptions {
@@ -85,15 +100,18 @@ pub struct OobConnectionCreateInvitationOptions {
     /// Whether a QR should be outputted to the user
     pub qr: bool,
 
-    /// TODO: Protocol from enum
-    /// The handshake  protocol to use
-    pub handshake_protocol: String,
+    /// The handshake protocol to use. Without one no connection is established, which requires an
+    /// attachment
+    pub handshake_protocol: Option<ConnectionProtocol>,
 
     /// Whether the invitation is reuseable
     pub multi_use: bool,
 
     /// Optional custom alias for the connection
     pub alias: Option<String>,
+
+    /// Exchanges that are attached to the invitation
+    pub attachments: Vec<OobAttachment>,
 }
 
 /// Skip validation and just pass the object - validation happens on aca-py
@@ -101,18 +119,34 @@ pub struct OobConnectionCreateInvitationOptions {
 /// Generic OOB invitation to receive
 pub type OobConnectionReceiveInvitationOptions = Value;
 
+/// Options supplied by the frontend for receiving an invitation
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OobReceiveOptions {
+    /// Optional custom alias for the connection
+    pub alias: Option<String>,
+
+    /// Whether an existing connection with the inviter is reused instead of creating a new one
+    #[serde(default)]
+    pub use_existing_connection: bool,
+}
+
 /// Generic cloudagent oob module
 #[async_trait]
-pub trait OobModule {
+pub trait OobModule: Display {
     /// Create an oob invitation
     async fn create_invitation(
         &self,
-        options: OobConnectionCreateInvitationOptions,
-    ) -> Result<OobConnectionCreateInvitationResponse>;
+        _options: OobConnectionCreateInvitationOptions,
+    ) -> Result<OobConnectionCreateInvitationResponse> {
+        unsupported(self)
+    }
 
     /// Receive and accept an oob connection
     async fn receive_invitation(
         &self,
-        invitation: OobConnectionReceiveInvitationOptions,
-    ) -> Result<OobConnection>;
+        _invitation: OobConnectionReceiveInvitationOptions,
+        _options: OobReceiveOptions,
+    ) -> Result<OobConnection> {
+        unsupported(self)
+    }
 }
@@ -1,15 +1,45 @@
-use crate::error::{Error, Result};
+use crate::error::{unsupported, Error, Result};
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Display;
 use std::str::FromStr;
 
+string_enum! {
+    /// State of a presentation exchange. The values of present proof 2.0 are accepted as aliases
+    #[derive(Default)]
+    PresentationExchangeState {
+        /// The prover sent a proposal
+        ProposalSent => "proposal_sent" | "proposal-sent",
+        /// The verifier received a proposal
+        ProposalReceived => "proposal_received" | "proposal-received",
+        /// The verifier sent a request
+        #[default]
+        RequestSent => "request_sent" | "request-sent",
+        /// The prover received a request
+        RequestReceived => "request_received" | "request-received",
+        /// The prover sent the presentation
+        PresentationSent => "presentation_sent" | "presentation-sent",
+        /// The verifier received the presentation
+        PresentationReceived => "presentation_received" | "presentation-received",
+        /// The verifier verified the presentation
+        Verified => "verified",
+        /// The prover received the acknowledgement
+        PresentationAcked => "presentation_acked",
+        /// The exchange is finished
+        Done => "done",
+        /// The exchange was abandoned
+        Abandoned => "abandoned",
+    }
+}
+
 /// Response from the cloudagent when a proof request is created
 #[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ProofRequestResponse {
     /// The state of the proof request
-    pub state: String,
+    pub state: PresentationExchangeState,
 
     /// The presentation request as an object
     pub presentation_request: Value,
@@ -17,7 +47,9 @@ pub struct ProofRequestResponse {
     /// The last time the proof request was updated
     pub updated_at: String,
 
-    /// The connection id to which the proof request is send to
+    /// The connection id to which the proof request is send to. Empty for a request without a
+    /// connection
+    #[serde(default)]
     pub connection_id: String,
 
     /// The thread id that can be used to reference this proof request
@@ -40,6 +72,55 @@ pub struct ProofRequestResponse {
 
     /// Who the initiator was of the proof request
     pub initiator: String,
+
+    /// The presentation of the prover, once it is received
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub presentation: Value,
+
+    /// Whether the presentation is verified, `true` or `false`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified: Option<String>,
+}
+
+impl ProofRequestResponse {
+    /// Values of the revealed attributes of the presentation, keyed by their name
+    pub fn revealed_attributes(&self) -> BTreeMap<String, String> {
+        let Some(revealed) = self
+            .presentation
+            .pointer("/requested_proof/revealed_attrs")
+            .and_then(Value::as_object)
+        else {
+            return BTreeMap::new();
+        };
+        revealed
+            .iter()
+            .filter_map(|(referent, attribute)| {
+                let name = self
+                    .presentation_request
+                    .pointer(&format!("/requested_attributes/{referent}/name"))
+                    .and_then(Value::as_str)
+                    .unwrap_or(referent);
+                let value = attribute.get("raw").and_then(Value::as_str)?;
+                Some((name.to_owned(), value.to_owned()))
+            })
+            .collect()
+    }
+}
+
+/// Query filters applied to retrieving all the presentation exchanges
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProofGetAllOptions {
+    /// Optional `connection id` to filter on
+    pub connection_id: Option<String>,
+
+    /// Optional `role` to filter on, `verifier` or `prover`
+    pub role: Option<String>,
+
+    /// Optional `state` to filter on
+    pub state: Option<PresentationExchangeState>,
+
+    /// Optional `thread id` to filter on
+    pub thread_id: Option<String>,
 }
 
 /// A simple predicate enum
@@ -77,7 +158,7 @@ fn validate_operator(op: &str) -> Result<()> {
     {
         return Ok(());
     };
-    Err(Error::InvalidOperator(op.to_owned()).into())
+    Err(Error::InvalidOperator(op.to_owned()))
 }
 
 /// Options supplied when a proof request is created
@@ -95,9 +176,52 @@ pub struct ProofRequestOptions {
     pub predicates: Vec<(String, String, i32)>,
 }
 
+/// Options supplied when a proof request is created that is not sent to a connection, e.g. to
+/// attach it to an out of band invitation
+pub struct ProofCreateRequestOptions {
+    /// The name of the proof request
+    pub name: String,
+
+    /// All the attributes that are requested from the other agent
+    pub attributes: Vec<String>,
+
+    /// All the predicates that are requested from the other agent
+    pub predicates: Vec<(String, String, i32)>,
+}
+
 /// Generic cloudagent proof module
 #[async_trait]
-pub trait ProofModule {
+pub trait ProofModule: Display {
     /// Send a proof request via the connection id to another agent
-    async fn send_request(&self, options: ProofRequestOptions) -> Result<ProofRequestResponse>;
+    async fn send_request(&self, _options: ProofRequestOptions) -> Result<ProofRequestResponse> {
+        unsupported(self)
+    }
+
+    /// Create a proof request without sending it
+    async fn create_request(
+        &self,
+        _options: ProofCreateRequestOptions,
+    ) -> Result<ProofRequestResponse> {
+        unsupported(self)
+    }
+
+    /// Gets all the presentation exchanges
+    async fn get_all(&self, _options: ProofGetAllOptions) -> Result<Vec<ProofRequestResponse>> {
+        unsupported(self)
+    }
+
+    /// Get a presentation exchange by id
+    async fn get_by_id(&self, _id: String) -> Result<ProofRequestResponse> {
+        unsupported(self)
+    }
+
+    /// Present a received proof request with the credentials of the wallet
+    async fn present(&self, _id: String) -> Result<ProofRequestResponse> {
+        unsupported(self)
+    }
+
+    /// Verify a received presentation
+    async fn verify(&self, _id: String) -> Result<ProofRequestResponse> {
+        unsupported(self)
+    }
 }
@@ -1,9 +1,10 @@
-use crate::error::Result;
+use crate::error::{unsupported, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
 /// Schema response from the ledger
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Schema {
     /// Version of the schema
     pub ver: String,
@@ -49,13 +50,19 @@ pub struct SchemasGetAllResponse {
 
 /// Generic cloudagent schema module
 #[async_trait]
-pub trait SchemaModule {
+pub trait SchemaModule: Display {
     /// Create a schema on the ledger
-    async fn create(&self, options: SchemaCreateOptions) -> Result<Schema>;
+    async fn create(&self, _options: SchemaCreateOptions) -> Result<Schema> {
+        unsupported(self)
+    }
 
     /// Request the schema by the id
-    async fn get_by_id(&self, id: String) -> Result<Schema>;
+    async fn get_by_id(&self, _id: String) -> Result<Schema> {
+        unsupported(self)
+    }
 
     /// Get all the registerd schemas
-    async fn get_all(&self) -> Result<SchemasGetAllResponse>;
+    async fn get_all(&self) -> Result<SchemasGetAllResponse> {
+        unsupported(self)
+    }
 }
@@ -1,37 +1,66 @@
-use crate::error::Result;
+use crate::error::{unsupported, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
 /// Options that are supplied when querying a wallet for DIDs
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Did {
     /// The DID of interest
     pub did: Option<String>,
 
-    // TODO: enum
     /// The key type to query for eg. ed25519, bls12381g2
-    pub key_type: Option<String>,
+    pub key_type: Option<DidKeyType>,
 
-    // TODO: enum
-    /// DID method to query for. e.g. sov to only fetch indy/sov DIDs Available values : key, sov
-    pub method: Option<String>,
+    /// DID method to query for. e.g. sov to only fetch indy/sov DIDs
+    pub method: Option<DidMethod>,
 
-    // TODO: enum
     /// The DID posture specifying whether the DID is
     /// the current public DID,
     /// posted to ledger but current public DID,
     /// or local to the wallet
-    /// Available values : public, posted, wallet_only
-    pub posture: Option<String>,
+    pub posture: Option<DidPosture>,
 
     /// The verification key of interest
     pub verkey: Option<String>,
 }
 
+string_enum! {
+    /// Key type of a DID
+    DidKeyType {
+        /// Ed25519 signing key
+        Ed25519 => "ed25519",
+        /// BLS12-381 G2 key for BBS+ signatures
+        Bls12381g2 => "bls12381g2",
+    }
+}
+
+string_enum! {
+    /// Method of a DID
+    DidMethod {
+        /// `did:key`
+        Key => "key",
+        /// `did:sov`, used for indy ledgers
+        Sov => "sov",
+    }
+}
+
+string_enum! {
+    /// Posture of a DID in the wallet
+    DidPosture {
+        /// The current public DID
+        Public => "public",
+        /// Posted to the ledger, but not the current public DID
+        Posted => "posted",
+        /// Only known to the wallet
+        WalletOnly => "wallet_only",
+    }
+}
+
 /// Response from the cloudagent when requesting info about dids
 /// of a wallet
 #[derive(Debug, Deserialize, Serialize)]
-pub struct DidList(Vec<Did>);
+pub struct DidList(pub(crate) Vec<Did>);
 
 /// Response from the cloudagent when requesting info about dids
 /// of a wallet
@@ -41,16 +70,15 @@ pub struct DidResult(Did);
 /// Key type in a JSON format k,v pair
 #[derive(Debug, Deserialize, Serialize)]
 pub struct KeyType {
-    // TODO: enum
     /// The key type to query for eg. ed25519, bls12381g2
-    pub key_type: String,
+    pub key_type: DidKeyType,
 }
 
 /// Options that are supplied when querying a wallet for DIDs
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CreateLocalDidOptions {
-    /// DID method to query for. e.g. sov to only fetch indy/sov DIDs Available values : key, sov
-    pub method: String,
+    /// DID method to query for. e.g. sov to only fetch indy/sov DIDs
+    pub method: DidMethod,
 
     /// The key type to query for eg. ed25519, bls12381g2
     pub options: KeyType,
@@ -81,25 +109,39 @@ pub struct SetDidEndpointOptions {
 
 /// Generic cloudagent basic message module
 #[async_trait]
-pub trait WalletModule {
+pub trait WalletModule: Display {
     /// Query a wallet for DIDs
-    async fn get_wallet_dids(&self, options: Did) -> Result<DidList>;
+    async fn get_wallet_dids(&self, _options: Did) -> Result<DidList> {
+        unsupported(self)
+    }
 
     /// Create a local DID
-    async fn create_local_did(&self, options: CreateLocalDidOptions) -> Result<Did>;
+    async fn create_local_did(&self, _options: CreateLocalDidOptions) -> Result<Did> {
+        unsupported(self)
+    }
 
     /// Rotate key pair
-    async fn rotate_keypair(&self, did: String) -> Result<()>;
+    async fn rotate_keypair(&self, _did: String) -> Result<()> {
+        unsupported(self)
+    }
 
     /// Fetch public did
-    async fn fetch_public_did(&self) -> Result<Did>;
+    async fn fetch_public_did(&self) -> Result<Did> {
+        unsupported(self)
+    }
 
     /// Assign the current public DID
-    async fn assign_public_did(&self, did: String) -> Result<Did>;
+    async fn assign_public_did(&self, _did: String) -> Result<Did> {
+        unsupported(self)
+    }
 
     /// Query DID endpoint of wallet
-    async fn fetch_did_endpoint(&self, did: String) -> Result<DidEndpoint>;
+    async fn fetch_did_endpoint(&self, _did: String) -> Result<DidEndpoint> {
+        unsupported(self)
+    }
 
     /// Set DID endpoint of wallet
-    async fn set_did_endpoint(&self, options: SetDidEndpointOptions) -> Result<()>;
+    async fn set_did_endpoint(&self, _options: SetDidEndpointOptions) -> Result<()> {
+        unsupported(self)
+    }
 }
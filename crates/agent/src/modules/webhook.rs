@@ -1,9 +1,12 @@
-use crate::error::Result;
+use crate::error::{unsupported, Result};
 use async_trait::async_trait;
+use std::fmt::Display;
 
 /// Generic cloudagent schema module
 #[async_trait]
-pub trait WebhookModule {
+pub trait WebhookModule: Display {
     /// Listen to all incoming webhook
-    async fn listen(&self, on_event: fn(serde_json::Value)) -> Result<()>;
+    async fn listen(&self, _on_event: fn(serde_json::Value)) -> Result<()> {
+        unsupported(self)
+    }
 }
@@ -0,0 +1,94 @@
+use std::fmt::Display;
+
+/// An enum that is represented by a string on the wire
+///
+/// Frontends can use `VARIANTS` to validate input and to list the allowed values
+pub trait StringEnum: for<'a> From<&'a str> + Display + Clone + Send + Sync + 'static {
+    /// The value of every known variant, together with the aliases that are also accepted
+    const VARIANTS: &'static [(&'static str, &'static [&'static str])];
+}
+
+/// Create an enum that is (de)serialized from and to a string
+///
+/// Every variant maps to one value and optionally some aliases. Values that are not known are
+/// kept in `Other`, so a newer version of an agent does not break deserialization.
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident => $value:literal $(| $alias:literal)*,
+            )+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )+
+
+            /// A value that is not known by siera
+            Other(String),
+        }
+
+        impl $name {
+            /// The value of the variant as it is sent to the cloudagent
+            #[must_use]
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)+
+                    Self::Other(value) => value,
+                }
+            }
+        }
+
+        impl $crate::string_enum::StringEnum for $name {
+            const VARIANTS: &'static [(&'static str, &'static [&'static str])] =
+                &[$(($value, &[$($alias),*])),+];
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "{}", self.as_str())
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($value $(| $alias)* => Self::$variant,)+
+                    other => Self::Other(other.to_owned()),
+                }
+            }
+        }
+
+        impl ::std::str::FromStr for $name {
+            type Err = ::std::convert::Infallible;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                Ok(Self::from(s))
+            }
+        }
+
+        impl ::serde::Serialize for $name {
+            fn serialize<S: ::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> ::std::result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for $name {
+            fn deserialize<D: ::serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> ::std::result::Result<Self, D::Error> {
+                let value = <String as ::serde::Deserialize>::deserialize(deserializer)?;
+                Ok(Self::from(value.as_str()))
+            }
+        }
+    };
+}
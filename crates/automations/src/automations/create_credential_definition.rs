@@ -27,7 +27,7 @@ impl<'a> CreateCredentialDefinition<'a> {
     /// - When the credential definition could not be registered
     pub async fn execute(
         &self,
-        agent: &(impl SchemaModule + CredentialDefinitionModule + Send + Sync),
+        agent: &(impl SchemaModule + CredentialDefinitionModule + Send + Sync + ?Sized),
     ) -> Result<CredentialDefinitionCreateResponse> {
         let schema = SchemaModule::create(
             agent,
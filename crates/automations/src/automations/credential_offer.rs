@@ -1,30 +1,40 @@
 use crate::{
-    automations::create_credential_definition::CreateCredentialDefinition,
+    automations::{
+        create_credential_definition::CreateCredentialDefinition,
+        find_credential_definition::{ExistingDefinition, FindCredentialDefinition},
+    },
     error::{Error, Result},
 };
 use rand::RngCore;
 use siera_agent::modules::{
     connection::ConnectionModule,
-    credential::{CredentialModule, CredentialOfferOptions},
-    credential_definition::CredentialDefinitionModule,
+    credential::{CredentialAttribute, CredentialModule, CredentialOfferOptions},
+    credential_definition::{CredentialDefinitionCreateOptions, CredentialDefinitionModule},
     schema::SchemaModule,
 };
-use std::collections::HashMap;
 
-/// Credential offer Automation which offers an prebuilt credential to a connection
+/// Credential offer Automation which offers a credential to a connection
 pub struct CredentialOfferAutomation {
     /// Connection id to which the credential will be send to
     pub connection_id: String,
-    /// Key Value pair of the attributes
-    pub attributes: HashMap<String, String>,
+    /// Attributes of the credential
+    pub attributes: Vec<CredentialAttribute>,
+    /// Name of the schema of the credential
+    pub schema_name: String,
+    /// Version of the schema. A random version is used for a new schema when it is not supplied
+    pub schema_version: Option<String>,
+    /// Whether a new credential definition is registered, even when a matching one already
+    /// exists. The schema is only registered again when no version is supplied
+    pub new_definition: bool,
 }
 
 impl CredentialOfferAutomation {
     /// Main executor function
-    /// 1. Create a connection
-    /// 2. Register the schema
-    /// 3. Register the credential definition
-    /// 4. Offer the credentail to the connection id
+    /// 1. Check the connection
+    /// 2. Find a credential definition of a matching schema
+    /// 3. Register the credential definition, and the schema when there is no matching one, when
+    ///    there is none or when asked to
+    /// 4. Offer the credential to the connection id
     ///
     /// # Errors
     ///
@@ -33,55 +43,89 @@ impl CredentialOfferAutomation {
     /// - When the credential could not be send
     pub async fn execute(
         &self,
-        agent: impl ConnectionModule
-            + CredentialModule
-            + SchemaModule
-            + CredentialDefinitionModule
-            + Send
-            + Sync,
+        agent: &(impl ConnectionModule
+              + CredentialModule
+              + SchemaModule
+              + CredentialDefinitionModule
+              + Send
+              + Sync
+              + ?Sized),
     ) -> Result<()> {
         info!({ "message": "Starting automation" });
         trace!({
            "connection_id": self.connection_id,
             "attributes": self.attributes
         });
-        let attribute_keys: Vec<&str> = self
-            .attributes
-            .keys()
-            .map(std::string::String::as_str)
-            .collect();
-        let attribute_values: Vec<String> = self.attributes.values().cloned().collect();
+        let attribute_keys: Vec<&str> = self.attributes.iter().map(|a| a.name.as_str()).collect();
 
         // Check if it as a valid connection
         info! ({
             "message": "Fetching the connection..."
         });
 
-        let connection = ConnectionModule::get_by_id(&agent, self.connection_id.clone()).await?;
-        if connection.state != "active" && connection.state != "response" {
+        let connection = ConnectionModule::get_by_id(agent, self.connection_id.clone()).await?;
+        if !connection.state.is_ready() {
             return Err(Error::ConnectionNotReady.into());
         }
 
-        let mut rng = rand::thread_rng();
-        let version_major = rng.next_u32();
-        let version_minor = rng.next_u32();
-        let version = format!("{version_major}.{version_minor}");
-        let create_credential_definition = CreateCredentialDefinition {
-            version: &version,
-            attributes: attribute_keys.clone(),
-            name: "full-credential-offer-automation",
+        // Without a version a new definition gets a new schema, so there is nothing to look for
+        let existing = if self.new_definition && self.schema_version.is_none() {
+            None
+        } else {
+            FindCredentialDefinition {
+                name: &self.schema_name,
+                attributes: attribute_keys.clone(),
+                version: self.schema_version.as_deref(),
+            }
+            .execute(agent)
+            .await?
         };
 
-        let credential_definition = create_credential_definition.execute(&agent).await?;
+        let cred_def_id = match existing {
+            Some(ExistingDefinition {
+                credential_definition_id: Some(id),
+                ..
+            }) if !self.new_definition => id,
+            // A schema can only be registered once for a name and version, so it is reused
+            Some(ExistingDefinition { schema_id, .. }) => {
+                info!({ "message": "Registering the credential definition" });
+                let credential_definition = CredentialDefinitionModule::create(
+                    agent,
+                    CredentialDefinitionCreateOptions {
+                        schema_id,
+                        ..CredentialDefinitionCreateOptions::default()
+                    },
+                )
+                .await?;
+                info!({ "message": "Created credential definition", "credential_definition_id": &credential_definition.credential_definition_id });
+                credential_definition.credential_definition_id
+            }
+            None => {
+                let version = self.schema_version.clone().unwrap_or_else(|| {
+                    let mut rng = rand::thread_rng();
+                    let version_major = rng.next_u32();
+                    let version_minor = rng.next_u32();
+                    format!("{version_major}.{version_minor}")
+                });
+                let create_credential_definition = CreateCredentialDefinition {
+                    version: &version,
+                    attributes: attribute_keys,
+                    name: &self.schema_name,
+                };
+                create_credential_definition
+                    .execute(agent)
+                    .await?
+                    .credential_definition_id
+            }
+        };
 
         info!({ "message": "Offering the credential..." });
 
         let credential_offer_response = agent
             .send_offer(CredentialOfferOptions {
-                keys: attribute_keys.iter().map(|x| String::from(*x)).collect(),
-                values: attribute_values,
+                attributes: self.attributes.clone(),
                 connection_id: self.connection_id.clone(),
-                cred_def_id: credential_definition.credential_definition_id,
+                cred_def_id,
             })
             .await?;
 
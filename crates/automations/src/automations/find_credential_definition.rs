@@ -0,0 +1,93 @@
+use crate::error::Result;
+use siera_agent::modules::credential_definition::CredentialDefinitionModule;
+use siera_agent::modules::schema::{Schema, SchemaModule};
+use std::collections::BTreeSet;
+
+/// Schema that the agent registered before, with its credential definition when it has one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistingDefinition {
+    /// Id of the schema
+    pub schema_id: String,
+
+    /// Id of a credential definition of the schema
+    pub credential_definition_id: Option<String>,
+}
+
+/// Automation for finding a credential definition that the agent registered before
+pub struct FindCredentialDefinition<'a> {
+    /// Schema name
+    pub name: &'a str,
+
+    /// Attributes of the schema. The order does not matter
+    pub attributes: Vec<&'a str>,
+
+    /// Schema version. Any version matches when it is not supplied
+    pub version: Option<&'a str>,
+}
+
+impl FindCredentialDefinition<'_> {
+    /// Main executor function
+    /// 1. Find a schema created by the agent with the same name, attributes and version
+    /// 2. Find a credential definition created by the agent for one of those schemas
+    ///
+    /// Returns the schema and its credential definition, or `None` when there is no matching schema
+    ///
+    /// # Errors
+    ///
+    /// - When the schemas or credential definitions could not be fetched
+    pub async fn execute(
+        &self,
+        agent: &(impl SchemaModule + CredentialDefinitionModule + Send + Sync + ?Sized),
+    ) -> Result<Option<ExistingDefinition>> {
+        info!({ "message": "Looking for an existing credential definition..." });
+        let mut schemas = vec![];
+        for id in SchemaModule::get_all(agent).await?.schema_ids {
+            let schema = SchemaModule::get_by_id(agent, id).await?;
+            if self.matches(&schema) {
+                schemas.push(schema);
+            }
+        }
+        let Some(first) = schemas.first() else {
+            return Ok(None);
+        };
+
+        for id in CredentialDefinitionModule::get_all(agent)
+            .await?
+            .credential_definition_ids
+        {
+            let credential_definition = CredentialDefinitionModule::get_by_id(agent, id).await?;
+            // A ledger refers to the schema by its sequence number instead of its id
+            let schema = schemas.iter().find(|s| {
+                s.id == credential_definition.schema_id
+                    || s.seq_no.map(|n| n.to_string()).as_ref()
+                        == Some(&credential_definition.schema_id)
+            });
+            if let Some(schema) = schema {
+                info!({ "message": "Reusing credential definition", "credential_definition_id": &credential_definition.id });
+                return Ok(Some(ExistingDefinition {
+                    schema_id: schema.id.clone(),
+                    credential_definition_id: Some(credential_definition.id),
+                }));
+            }
+        }
+
+        info!({ "message": "Reusing schema", "schema_id": &first.id });
+        Ok(Some(ExistingDefinition {
+            schema_id: first.id.clone(),
+            credential_definition_id: None,
+        }))
+    }
+
+    /// Whether the schema has the same name, attributes and version
+    fn matches(&self, schema: &Schema) -> bool {
+        let attributes: BTreeSet<&str> = self.attributes.iter().copied().collect();
+        schema.name == self.name
+            && self.version.is_none_or(|v| schema.version == v)
+            && schema
+                .attr_names
+                .iter()
+                .map(String::as_str)
+                .collect::<BTreeSet<_>>()
+                == attributes
+    }
+}
@@ -0,0 +1,357 @@
+use crate::{
+    automations::{create_credential_definition::CreateCredentialDefinition, poll},
+    error::{Error, Result},
+};
+use rand::RngCore;
+use serde::Serialize;
+use siera_agent::modules::{
+    connection::{
+        ConnectionCreateInvitationOptions, ConnectionModule, ConnectionReceiveInvitationOptions,
+    },
+    credential::{
+        CredentialAttribute, CredentialExchangeState, CredentialGetAllOptions, CredentialModule,
+        CredentialOfferOptions,
+    },
+    credential_definition::CredentialDefinitionModule,
+    proof::{PresentationExchangeState, ProofGetAllOptions, ProofModule, ProofRequestOptions},
+    schema::SchemaModule,
+};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Every module an agent needs for the automation
+pub trait IssueAndVerifyAgent:
+    ConnectionModule
+    + CredentialModule
+    + CredentialDefinitionModule
+    + ProofModule
+    + SchemaModule
+    + Send
+    + Sync
+{
+}
+
+impl<T> IssueAndVerifyAgent for T where
+    T: ConnectionModule
+        + CredentialModule
+        + CredentialDefinitionModule
+        + ProofModule
+        + SchemaModule
+        + Send
+        + Sync
+        + ?Sized
+{
+}
+
+/// Automation that runs a full round trip between an issuer and a holder, to check that two
+/// agents work together
+pub struct IssueAndVerifyAutomation<'a> {
+    /// Name of the schema that is registered for the credential
+    pub schema_name: &'a str,
+
+    /// Attributes of the credential. Every attribute is requested in the proof
+    pub attributes: Vec<CredentialAttribute>,
+
+    /// Maximum amount of time a step may wait for the agents
+    pub timeout: Duration,
+}
+
+/// Duration of a single step of the automation
+#[derive(Debug, Serialize)]
+pub struct StepTiming {
+    /// Name of the step
+    pub step: &'static str,
+
+    /// How long the step took in milliseconds
+    pub milliseconds: u128,
+}
+
+/// Result of the automation
+#[derive(Debug, Serialize)]
+pub struct IssueAndVerifyReport {
+    /// Connection id at the issuer
+    pub issuer_connection_id: String,
+
+    /// Connection id at the holder
+    pub holder_connection_id: String,
+
+    /// Credential definition the credential is issued with
+    pub credential_definition_id: String,
+
+    /// Values of the attributes the holder revealed in the proof, keyed by their name
+    pub revealed_attributes: BTreeMap<String, String>,
+
+    /// Duration of every step, in the order they were executed
+    pub steps: Vec<StepTiming>,
+}
+
+impl IssueAndVerifyAutomation<'_> {
+    /// Main executor function
+    /// 1. Connect the holder to the issuer
+    /// 2. Register the schema and the credential definition at the issuer
+    /// 3. Issue the credential and store it at the holder
+    /// 4. Request a proof of every attribute and present it from the holder
+    /// 5. Verify the proof at the issuer
+    ///
+    /// The agents may respond to the protocols on their own. A step only acts for an agent when
+    /// its record is still waiting for it
+    ///
+    /// # Errors
+    ///
+    /// - When a request to an agent fails
+    /// - When a step did not finish within the timeout
+    /// - When an exchange was abandoned or the proof could not be verified
+    pub async fn execute(
+        &self,
+        issuer: &(impl IssueAndVerifyAgent + ?Sized),
+        holder: &(impl IssueAndVerifyAgent + ?Sized),
+    ) -> Result<IssueAndVerifyReport> {
+        let mut steps = vec![];
+
+        let started = Instant::now();
+        let (issuer_connection_id, holder_connection_id) = self.connect(issuer, holder).await?;
+        steps.push(timing("connect", started));
+
+        let started = Instant::now();
+        let credential_definition_id = self.register(issuer).await?;
+        steps.push(timing("register", started));
+
+        let started = Instant::now();
+        self.issue(
+            issuer,
+            holder,
+            &issuer_connection_id,
+            &credential_definition_id,
+        )
+        .await?;
+        steps.push(timing("issue", started));
+
+        let started = Instant::now();
+        let presentation_exchange_id = self.present(issuer, holder, &issuer_connection_id).await?;
+        steps.push(timing("present", started));
+
+        let started = Instant::now();
+        let revealed_attributes = self.verify(issuer, &presentation_exchange_id).await?;
+        steps.push(timing("verify", started));
+
+        Ok(IssueAndVerifyReport {
+            issuer_connection_id,
+            holder_connection_id,
+            credential_definition_id,
+            revealed_attributes,
+            steps,
+        })
+    }
+
+    /// Connect the holder to the issuer and return the connection ids of both
+    async fn connect(
+        &self,
+        issuer: &(impl IssueAndVerifyAgent + ?Sized),
+        holder: &(impl IssueAndVerifyAgent + ?Sized),
+    ) -> Result<(String, String)> {
+        info!({ "message": "Connecting the holder to the issuer..." });
+        let invitation = ConnectionModule::create_invitation(
+            issuer,
+            ConnectionCreateInvitationOptions {
+                auto_accept: true,
+                ..ConnectionCreateInvitationOptions::default()
+            },
+        )
+        .await?;
+        let received: ConnectionReceiveInvitationOptions =
+            serde_json::from_value(invitation.invitation)?;
+        let connection = ConnectionModule::receive_invitation(holder, received).await?;
+
+        let (issuer_id, holder_id) = (invitation.id, connection.id);
+        poll(self.timeout, "connect", || async {
+            let issuer_connection = ConnectionModule::get_by_id(issuer, issuer_id.clone()).await?;
+            let holder_connection = ConnectionModule::get_by_id(holder, holder_id.clone()).await?;
+            Ok(
+                (issuer_connection.state.is_ready() && holder_connection.state.is_ready())
+                    .then_some(()),
+            )
+        })
+        .await?;
+        Ok((issuer_id, holder_id))
+    }
+
+    /// Register the schema and the credential definition at the issuer
+    async fn register(&self, issuer: &(impl IssueAndVerifyAgent + ?Sized)) -> Result<String> {
+        info!({ "message": "Registering the schema and credential definition..." });
+        let version = {
+            let mut rng = rand::thread_rng();
+            format!("{}.{}", rng.next_u32(), rng.next_u32())
+        };
+        let automation = CreateCredentialDefinition {
+            name: self.schema_name,
+            version: &version,
+            attributes: self.attributes.iter().map(|a| a.name.as_str()).collect(),
+        };
+        let credential_definition = automation.execute(issuer).await?;
+        Ok(credential_definition.credential_definition_id)
+    }
+
+    /// Offer the credential and move the exchange along on both sides until the holder stored it
+    async fn issue(
+        &self,
+        issuer: &(impl IssueAndVerifyAgent + ?Sized),
+        holder: &(impl IssueAndVerifyAgent + ?Sized),
+        connection_id: &str,
+        credential_definition_id: &str,
+    ) -> Result<()> {
+        info!({ "message": "Issuing the credential..." });
+        let offer = issuer
+            .send_offer(CredentialOfferOptions {
+                connection_id: connection_id.to_owned(),
+                cred_def_id: credential_definition_id.to_owned(),
+                attributes: self.attributes.clone(),
+            })
+            .await?;
+
+        let thread_id = offer.thread_id;
+        poll(self.timeout, "issue", || async {
+            // Finished exchanges may be removed by the agents, so a missing record is not an error
+            let issuer_exchange = CredentialModule::get_all(
+                issuer,
+                CredentialGetAllOptions {
+                    thread_id: Some(thread_id.clone()),
+                    role: Some(String::from("issuer")),
+                    ..CredentialGetAllOptions::default()
+                },
+            )
+            .await?
+            .into_iter()
+            .next();
+            if let Some(exchange) = issuer_exchange {
+                match exchange.state {
+                    CredentialExchangeState::RequestReceived => {
+                        issuer.issue(exchange.credential_exchange_id).await?;
+                    }
+                    CredentialExchangeState::Abandoned => {
+                        return Err(Error::ExchangeAbandoned(exchange.credential_exchange_id).into())
+                    }
+                    _ => {}
+                }
+            }
+
+            let holder_exchange = CredentialModule::get_all(
+                holder,
+                CredentialGetAllOptions {
+                    thread_id: Some(thread_id.clone()),
+                    role: Some(String::from("holder")),
+                    ..CredentialGetAllOptions::default()
+                },
+            )
+            .await?
+            .into_iter()
+            .next();
+            let Some(exchange) = holder_exchange else {
+                return Ok(None);
+            };
+            match exchange.state {
+                CredentialExchangeState::OfferReceived => {
+                    holder.accept_offer(exchange.credential_exchange_id).await?;
+                    Ok(None)
+                }
+                CredentialExchangeState::CredentialReceived => {
+                    holder.store(exchange.credential_exchange_id).await?;
+                    Ok(Some(()))
+                }
+                CredentialExchangeState::CredentialAcked | CredentialExchangeState::Done => {
+                    Ok(Some(()))
+                }
+                CredentialExchangeState::Abandoned => {
+                    Err(Error::ExchangeAbandoned(exchange.credential_exchange_id).into())
+                }
+                _ => Ok(None),
+            }
+        })
+        .await
+    }
+
+    /// Request a proof of every attribute and present it from the holder. Returns the id of the
+    /// exchange of the issuer once it received the presentation
+    async fn present(
+        &self,
+        issuer: &(impl IssueAndVerifyAgent + ?Sized),
+        holder: &(impl IssueAndVerifyAgent + ?Sized),
+        connection_id: &str,
+    ) -> Result<String> {
+        info!({ "message": "Requesting the proof..." });
+        let request = issuer
+            .send_request(ProofRequestOptions {
+                connection_id: connection_id.to_owned(),
+                name: format!("{} proof", self.schema_name),
+                attributes: self.attributes.iter().map(|a| a.name.clone()).collect(),
+                predicates: vec![],
+            })
+            .await?;
+
+        let id = request.presentation_exchange_id;
+        let thread_id = request.thread_id;
+        poll(self.timeout, "present", || async {
+            let exchange = ProofModule::get_by_id(issuer, id.clone()).await?;
+            match exchange.state {
+                PresentationExchangeState::PresentationReceived
+                | PresentationExchangeState::Verified => return Ok(Some(())),
+                PresentationExchangeState::Abandoned => {
+                    return Err(Error::ExchangeAbandoned(id.clone()).into())
+                }
+                _ => {}
+            }
+
+            let prover_exchange = ProofModule::get_all(
+                holder,
+                ProofGetAllOptions {
+                    thread_id: Some(thread_id.clone()),
+                    role: Some(String::from("prover")),
+                    ..ProofGetAllOptions::default()
+                },
+            )
+            .await?
+            .into_iter()
+            .next();
+            if let Some(exchange) = prover_exchange {
+                if exchange.state == PresentationExchangeState::RequestReceived {
+                    holder.present(exchange.presentation_exchange_id).await?;
+                }
+            }
+            Ok(None)
+        })
+        .await?;
+        Ok(id)
+    }
+
+    /// Verify the received presentation and return the revealed attributes
+    async fn verify(
+        &self,
+        issuer: &(impl IssueAndVerifyAgent + ?Sized),
+        id: &str,
+    ) -> Result<BTreeMap<String, String>> {
+        info!({ "message": "Verifying the proof..." });
+        let exchange = poll(self.timeout, "verify", || async {
+            let exchange = ProofModule::get_by_id(issuer, id.to_owned()).await?;
+            match exchange.state {
+                PresentationExchangeState::PresentationReceived => {
+                    issuer.verify(id.to_owned()).await?;
+                    Ok(None)
+                }
+                PresentationExchangeState::Verified => Ok(Some(exchange)),
+                _ => Ok(None),
+            }
+        })
+        .await?;
+
+        if exchange.verified.as_deref() != Some("true") {
+            return Err(Error::ProofNotVerified(id.to_owned()).into());
+        }
+        Ok(exchange.revealed_attributes())
+    }
+}
+
+/// Duration of the step that started at the instant
+fn timing(step: &'static str, started: Instant) -> StepTiming {
+    let milliseconds = started.elapsed().as_millis();
+    info!({ "message": format!("Finished {step} in {milliseconds} ms") });
+    StepTiming { step, milliseconds }
+}
@@ -2,3 +2,35 @@
 pub mod create_credential_definition;
 /// Credential offer automation
 pub mod credential_offer;
+/// Find an existing credential definition automation
+pub mod find_credential_definition;
+/// Issue and verify automation between two agents
+pub mod issue_and_verify;
+/// Proof request automation
+pub mod proof_request;
+
+use crate::error::{Error, Result};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Time between two checks of the records of the agents
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Check until the check returns a value, or fail when it takes longer than the timeout
+pub(crate) async fn poll<T, F, Fut>(timeout: Duration, step: &str, mut check: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<T>>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(value) = check().await? {
+            return Ok(value);
+        }
+        if Instant::now() >= deadline {
+            return Err(Error::StepTimedOut(step.to_owned()).into());
+        }
+        trace!({ "message": format!("Step {step} is not finished, checking again...") });
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{
+    automations::poll,
+    error::{Error, Result},
+};
+use serde::Serialize;
+use siera_agent::modules::{
+    connection::ConnectionModule,
+    proof::{PresentationExchangeState, ProofModule, ProofRequestOptions},
+};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Proof request automation which requests a proof from a connection and verifies it
+pub struct ProofRequestAutomation {
+    /// Connection id to which the proof request will be send to
+    pub connection_id: String,
+    /// Name of the proof request
+    pub name: String,
+    /// Attributes that have to be revealed
+    pub attributes: Vec<String>,
+    /// Predicates of the form (name, operator, value)
+    pub predicates: Vec<(String, String, i32)>,
+    /// Maximum amount of time to wait for the presentation
+    pub timeout: Duration,
+}
+
+/// Outcome of the proof request
+#[derive(Debug, Serialize)]
+pub struct ProofRequestResult {
+    /// Id of the presentation exchange
+    pub presentation_exchange_id: String,
+    /// Whether the presentation is valid
+    pub verified: bool,
+    /// Values of the attributes the holder revealed, keyed by their name
+    pub revealed_attributes: BTreeMap<String, String>,
+}
+
+impl ProofRequestAutomation {
+    /// Main executor function
+    /// 1. Check the connection
+    /// 2. Send the proof request
+    /// 3. Wait for the presentation
+    /// 4. Verify the presentation, unless the agent already did
+    ///
+    /// # Errors
+    ///
+    /// - When the connection is not active
+    /// - When the proof request could not be send
+    /// - When no presentation was received within the timeout
+    /// - When the holder abandoned the exchange
+    pub async fn execute(
+        &self,
+        agent: &(impl ConnectionModule + ProofModule + Send + Sync + ?Sized),
+    ) -> Result<ProofRequestResult> {
+        info!({ "message": "Starting automation" });
+        let connection = ConnectionModule::get_by_id(agent, self.connection_id.clone()).await?;
+        if !connection.state.is_ready() {
+            return Err(Error::ConnectionNotReady.into());
+        }
+
+        info!({ "message": "Requesting the proof..." });
+        let request = agent
+            .send_request(ProofRequestOptions {
+                connection_id: self.connection_id.clone(),
+                name: self.name.clone(),
+                attributes: self.attributes.clone(),
+                predicates: self.predicates.clone(),
+            })
+            .await?;
+
+        info!({ "message": "Waiting for the presentation..." });
+        let id = request.presentation_exchange_id;
+        let exchange = poll(self.timeout, "presentation", || async {
+            let exchange = ProofModule::get_by_id(agent, id.clone()).await?;
+            match exchange.state {
+                PresentationExchangeState::PresentationReceived => {
+                    info!({ "message": "Verifying the presentation..." });
+                    agent.verify(id.clone()).await?;
+                    Ok(None)
+                }
+                PresentationExchangeState::Verified => Ok(Some(exchange)),
+                PresentationExchangeState::Abandoned => {
+                    Err(Error::ExchangeAbandoned(id.clone()).into())
+                }
+                _ => Ok(None),
+            }
+        })
+        .await?;
+
+        Ok(ProofRequestResult {
+            verified: exchange.verified.as_deref() == Some("true"),
+            revealed_attributes: exchange.revealed_attributes(),
+            presentation_exchange_id: id,
+        })
+    }
+}
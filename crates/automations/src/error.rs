@@ -5,6 +5,15 @@ use std::fmt::{Display, Formatter};
 pub enum Error {
     /// The connection did not get ready in time to receive a credential
     ConnectionNotReady,
+
+    /// A step of the automation did not finish within the timeout
+    StepTimedOut(String),
+
+    /// The other party abandoned the exchange
+    ExchangeAbandoned(String),
+
+    /// The presentation was received but is not valid
+    ProofNotVerified(String),
 }
 
 impl std::error::Error for Error {}
@@ -16,6 +25,17 @@ impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::ConnectionNotReady => write!(f, "Connection is not in state active"),
+            Self::StepTimedOut(step) => write!(
+                f,
+                "Step '{step}' did not finish in time. Please try again with a higher --timeout."
+            ),
+            Self::ExchangeAbandoned(id) => {
+                write!(f, "Exchange {id} was abandoned by one of the agents.")
+            }
+            Self::ProofNotVerified(id) => write!(
+                f,
+                "The presentation of exchange {id} could not be verified."
+            ),
         }
     }
 }
@@ -9,4 +9,4 @@ extern crate siera_logger;
 pub mod automations;
 
 /// Error module that includes the user-level errors and the result type
-mod error;
+pub mod error;
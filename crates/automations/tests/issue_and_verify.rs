@@ -0,0 +1,59 @@
+//! Tests of the issue and verify automation between two mock agent servers
+
+use siera_agent::agent::HttpOptions;
+use siera_agent::modules::credential::CredentialAttribute;
+use siera_automations::automations::issue_and_verify::IssueAndVerifyAutomation;
+use siera_cloudagent_python::agent::{CloudAgentPython, CloudAgentPythonVersion};
+use siera_mock_agent::server::{MockAgentServer, MockAgentServerOptions};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Start a mock agent on a free port and return a client for it
+fn serve(label: &str) -> CloudAgentPython {
+    let server = MockAgentServer::bind(MockAgentServerOptions {
+        address: "127.0.0.1:0".parse().unwrap(),
+        label: label.to_owned(),
+        api_key: None,
+        endpoint: None,
+    })
+    .unwrap();
+    let endpoint = server.endpoint().to_owned();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    CloudAgentPython::new(
+        endpoint,
+        CloudAgentPythonVersion::ZeroSevenThree,
+        None,
+        None,
+        HttpOptions::default(),
+    )
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn issues_and_verifies_a_credential() {
+    let issuer = serve("Issuer");
+    let holder = serve("Holder");
+
+    let automation = IssueAndVerifyAutomation {
+        schema_name: "passport",
+        attributes: vec![
+            CredentialAttribute::new("name", "Alice"),
+            CredentialAttribute::new("age", "28"),
+        ],
+        timeout: Duration::from_secs(5),
+    };
+    let report = automation.execute(&issuer, &holder).await.unwrap();
+
+    assert_eq!(
+        report.revealed_attributes,
+        BTreeMap::from([
+            (String::from("age"), String::from("28")),
+            (String::from("name"), String::from("Alice")),
+        ])
+    );
+    let steps: Vec<_> = report.steps.iter().map(|s| s.step).collect();
+    assert_eq!(steps, ["connect", "register", "issue", "present", "verify"]);
+    assert!(!report.credential_definition_id.is_empty());
+}
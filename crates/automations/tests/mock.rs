@@ -0,0 +1,315 @@
+//! Tests of the automations against the in-memory mock agent of `siera_agent`
+
+use siera_agent::agent::Capability;
+use siera_agent::error::{Error as AgentError, HttpContext};
+use siera_agent::mock::MockAgent;
+use siera_agent::modules::connection::ConnectionState;
+use siera_agent::modules::credential::{CredentialAttribute, CredentialExchangeState};
+use siera_agent::modules::proof::PresentationExchangeState;
+use siera_agent::modules::schema::{SchemaCreateOptions, SchemaModule};
+use siera_agent::modules::webhook::WebhookModule;
+use siera_automations::automations::create_credential_definition::CreateCredentialDefinition;
+use siera_automations::automations::credential_offer::CredentialOfferAutomation;
+use siera_automations::automations::proof_request::ProofRequestAutomation;
+use siera_automations::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Automation that offers a credential with a name and age to the connection
+fn credential_offer(connection_id: String) -> CredentialOfferAutomation {
+    CredentialOfferAutomation {
+        connection_id,
+        attributes: vec![
+            CredentialAttribute::new("name", "Alice"),
+            CredentialAttribute::new("age", "25"),
+        ],
+        schema_name: String::from("person"),
+        schema_version: None,
+        new_definition: false,
+    }
+}
+
+#[tokio::test]
+async fn offers_a_credential_to_an_active_connection() {
+    let agent = MockAgent::new();
+    let connection = agent.add_connection(ConnectionState::Active);
+
+    credential_offer(connection.id.clone())
+        .execute(&agent)
+        .await
+        .unwrap();
+
+    let methods: Vec<_> = agent.calls().iter().map(|c| (c.module, c.method)).collect();
+    assert_eq!(
+        methods,
+        [
+            (Capability::Connection, "get_by_id"),
+            (Capability::Schema, "get_all"),
+            (Capability::Schema, "create"),
+            (Capability::CredentialDefinition, "create"),
+            (Capability::Credential, "send_offer"),
+        ]
+    );
+
+    let exchanges = agent.credential_exchanges();
+    assert_eq!(exchanges.len(), 1);
+    assert_eq!(exchanges[0].connection_id, connection.id);
+    assert_eq!(exchanges[0].state, CredentialExchangeState::OfferSent);
+
+    let id = &exchanges[0].credential_exchange_id;
+    assert_eq!(
+        agent.advance_credential_exchange(id).unwrap(),
+        CredentialExchangeState::RequestReceived
+    );
+    assert_eq!(
+        agent.advance_credential_exchange(id).unwrap(),
+        CredentialExchangeState::CredentialIssued
+    );
+}
+
+#[tokio::test]
+async fn reuses_a_matching_credential_definition() {
+    let agent = MockAgent::new();
+    let connection = agent.add_connection(ConnectionState::Active);
+
+    let first = CreateCredentialDefinition {
+        name: "person",
+        attributes: vec!["age", "name"],
+        version: "1.0",
+    }
+    .execute(&agent)
+    .await
+    .unwrap();
+    CreateCredentialDefinition {
+        name: "person",
+        attributes: vec!["name"],
+        version: "2.0",
+    }
+    .execute(&agent)
+    .await
+    .unwrap();
+
+    credential_offer(connection.id.clone())
+        .execute(&agent)
+        .await
+        .unwrap();
+    assert_eq!(agent.calls_to(Capability::Schema, "create").len(), 2);
+    let exchanges = agent.credential_exchanges();
+    assert_eq!(
+        exchanges[0].credential_definition_id,
+        first.credential_definition_id
+    );
+
+    let mut automation = credential_offer(connection.id);
+    automation.new_definition = true;
+    automation.execute(&agent).await.unwrap();
+    assert_eq!(agent.calls_to(Capability::Schema, "create").len(), 3);
+}
+
+#[tokio::test]
+async fn reuses_a_matching_schema_without_a_credential_definition() {
+    let agent = MockAgent::new();
+    let connection = agent.add_connection(ConnectionState::Active);
+
+    let schema = SchemaModule::create(
+        &agent,
+        SchemaCreateOptions {
+            name: String::from("person"),
+            version: String::from("1.0"),
+            attributes: vec![String::from("name"), String::from("age")],
+        },
+    )
+    .await
+    .unwrap();
+
+    let mut automation = credential_offer(connection.id);
+    automation.execute(&agent).await.unwrap();
+    let created = agent.calls_to(Capability::CredentialDefinition, "create");
+    assert_eq!(agent.calls_to(Capability::Schema, "create").len(), 1);
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].arguments["schema_id"], schema.id);
+
+    automation.schema_version = Some(String::from("1.0"));
+    automation.new_definition = true;
+    automation.execute(&agent).await.unwrap();
+    assert_eq!(agent.calls_to(Capability::Schema, "create").len(), 1);
+    assert_eq!(
+        agent
+            .calls_to(Capability::CredentialDefinition, "create")
+            .len(),
+        2
+    );
+}
+
+/// Automation that requests a proof of the name and an age of at least 18 from the connection
+fn proof_request(connection_id: String) -> ProofRequestAutomation {
+    ProofRequestAutomation {
+        connection_id,
+        name: String::from("adult"),
+        attributes: vec![String::from("name")],
+        predicates: vec![(String::from("age"), String::from(">="), 18)],
+        timeout: Duration::from_secs(5),
+    }
+}
+
+/// Wait until the proof request is sent and return the id of its exchange
+async fn sent_proof_request(agent: &MockAgent) -> String {
+    loop {
+        if let Some(exchange) = agent.proof_exchanges().first() {
+            return exchange.presentation_exchange_id.clone();
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn verifies_a_received_presentation() {
+    let agent = MockAgent::new();
+    let connection = agent.add_connection(ConnectionState::Active);
+
+    let automation = proof_request(connection.id);
+    let (result, ()) = tokio::join!(automation.execute(&agent), async {
+        let id = sent_proof_request(&agent).await;
+        agent
+            .update_proof_exchange(&id, |exchange| {
+                exchange.state = PresentationExchangeState::PresentationReceived;
+                exchange.presentation = serde_json::json!({
+                    "requested_proof": { "revealed_attrs": { "name": { "raw": "Alice" } } }
+                });
+            })
+            .unwrap();
+    });
+    let result = result.unwrap();
+
+    assert!(result.verified);
+    assert_eq!(result.revealed_attributes["name"], "Alice");
+    assert_eq!(agent.calls_to(Capability::Proof, "verify").len(), 1);
+}
+
+#[tokio::test]
+async fn stops_when_the_proof_request_is_abandoned() {
+    let agent = MockAgent::new();
+    let connection = agent.add_connection(ConnectionState::Active);
+
+    let automation = proof_request(connection.id);
+    let (result, ()) = tokio::join!(automation.execute(&agent), async {
+        let id = sent_proof_request(&agent).await;
+        agent
+            .update_proof_exchange(&id, |exchange| {
+                exchange.state = PresentationExchangeState::Abandoned;
+            })
+            .unwrap();
+    });
+
+    let error = result.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::ExchangeAbandoned(_))
+    ));
+    assert!(agent.calls_to(Capability::Proof, "verify").is_empty());
+}
+
+#[tokio::test]
+async fn refuses_a_connection_that_is_not_ready() {
+    let agent = MockAgent::new();
+    let connection = agent.add_connection(ConnectionState::Invitation);
+
+    let error = credential_offer(connection.id.clone())
+        .execute(&agent)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::ConnectionNotReady)
+    ));
+    assert!(agent
+        .calls_to(Capability::Credential, "send_offer")
+        .is_empty());
+
+    assert_eq!(
+        agent.advance_connection(&connection.id).unwrap(),
+        ConnectionState::Request
+    );
+    assert_eq!(
+        agent.advance_connection(&connection.id).unwrap(),
+        ConnectionState::Response
+    );
+    assert!(credential_offer(connection.id.clone())
+        .execute(&agent)
+        .await
+        .is_err());
+
+    assert_eq!(
+        agent.advance_connection(&connection.id).unwrap(),
+        ConnectionState::Active
+    );
+    credential_offer(connection.id)
+        .execute(&agent)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn stops_when_the_schema_could_not_be_registered() {
+    let agent = MockAgent::new();
+    agent.fail_next(
+        Capability::Schema,
+        "create",
+        AgentError::InternalServerError(
+            HttpContext::new("POST", "mock://agent/schemas").with_response(500, "Ledger offline"),
+        ),
+    );
+
+    let error = CreateCredentialDefinition {
+        name: "person",
+        attributes: vec!["name"],
+        version: "1.0",
+    }
+    .execute(&agent)
+    .await
+    .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<AgentError>(),
+        Some(AgentError::InternalServerError(_))
+    ));
+    assert!(agent
+        .calls_to(Capability::CredentialDefinition, "create")
+        .is_empty());
+
+    let response = CreateCredentialDefinition {
+        name: "person",
+        attributes: vec!["name"],
+        version: "1.0",
+    }
+    .execute(&agent)
+    .await
+    .unwrap();
+    assert!(response
+        .credential_definition_id
+        .ends_with(":3:CL:1:default"));
+}
+
+#[tokio::test]
+async fn delivers_the_events_of_the_records() {
+    /// Number of events that were delivered
+    static EVENTS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Count the event, as `listen` takes a function pointer
+    fn on_event(event: &serde_json::Value) {
+        assert!(event.get("topic").is_some());
+        EVENTS.fetch_add(1, Ordering::SeqCst);
+    }
+
+    let agent = MockAgent::new();
+    let connection = agent.add_connection(ConnectionState::Active);
+    credential_offer(connection.id)
+        .execute(&agent)
+        .await
+        .unwrap();
+
+    agent.listen(|event| on_event(&event)).await.unwrap();
+    assert_eq!(EVENTS.load(Ordering::SeqCst), 2);
+
+    agent.listen(|event| on_event(&event)).await.unwrap();
+    assert_eq!(EVENTS.load(Ordering::SeqCst), 2);
+}
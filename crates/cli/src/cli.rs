@@ -1,16 +1,21 @@
 use std::path::PathBuf;
 
+use clap::builder::{PossibleValuesParser, TypedValueParser};
 use clap::{Parser, Subcommand};
+use siera_agent::agent::Capability;
+use siera_logger::OutputFormat;
 
 use crate::help_strings::HelpStrings;
 
-use crate::modules::automation::AutomationOptions;
+use crate::modules::automation::{AutomationOptions, AutomationSubcommands};
 use crate::modules::multitenancy::MultitenancyOptions;
 use crate::modules::{
-    basic_message::BasicMessageOptions, configuration::ConfigurationOptions,
+    api::ApiOptions, basic_message::BasicMessageOptions, configuration::ConfigurationOptions,
     connection::ConnectionOptions, credential::CredentialOptions,
-    credential_definition::CredentialDefinitionOptions, feature::FeaturesOptions, oob::OobOptions,
-    proof::ProofOptions, schema::SchemaOptions, wallet::WalletOptions, webhook::WebhookOptions,
+    credential_definition::CredentialDefinitionOptions, dashboard::DashboardOptions,
+    feature::FeaturesOptions, mock_agent::MockAgentOptions, oob::OobOptions, proof::ProofOptions,
+    run::RunOptions, schema::SchemaOptions, shell::ShellOptions, wallet::WalletOptions,
+    webhook::WebhookOptions,
 };
 
 /// Main command with options, flags and subcommands
@@ -39,9 +44,17 @@ pub struct Cli {
     pub copy: bool,
 
     /// Whether specific output should be copied to the clipboard
-    #[clap(long = "json", short = 'j', help = HelpStrings::OutputJson)]
+    #[clap(long = "json", short = 'j', help = HelpStrings::OutputJson, conflicts_with = "output")]
     pub output_json: bool,
 
+    /// Format in which the result of the command is printed
+    #[clap(long, help = HelpStrings::Output, value_parser = PossibleValuesParser::new(OutputFormat::VALUES).map(|s| OutputFormat::from(s.as_str())))]
+    pub output: Option<OutputFormat>,
+
+    /// Fields of the result that are printed
+    #[clap(long, visible_alias = "query", help = HelpStrings::Fields, value_delimiter = ',')]
+    pub fields: Vec<String>,
+
     /// Whether the output should be quiet
     #[clap(long, short, help = HelpStrings::Quiet, conflicts_with = "verbose")]
     pub quiet: bool,
@@ -54,6 +67,30 @@ pub struct Cli {
     #[clap(long, short, default_value = "default", help = HelpStrings::Environment)]
     pub environment: String,
 
+    /// Maximum amount of seconds it may take to connect to the agent
+    #[clap(long, help = HelpStrings::ConnectTimeout)]
+    pub connect_timeout: Option<u64>,
+
+    /// Maximum amount of seconds a single request to the agent may take
+    #[clap(long, help = HelpStrings::RequestTimeout)]
+    pub request_timeout: Option<u64>,
+
+    /// How often an idempotent request is retried
+    #[clap(long, help = HelpStrings::Retries)]
+    pub retries: Option<u32>,
+
+    /// Proxy that every request is sent through
+    #[clap(long, help = HelpStrings::Proxy)]
+    pub proxy: Option<String>,
+
+    /// User agent that is sent with every request
+    #[clap(long, help = HelpStrings::UserAgent)]
+    pub user_agent: Option<String>,
+
+    /// Whether requests are printed as `curl` commands instead of sent
+    #[clap(long, help = HelpStrings::DryRun)]
+    pub dry_run: bool,
+
     /// Whether more verbose output should be printed
     #[clap(long, short='v', help = HelpStrings::Verbose, action = clap::ArgAction::Count, conflicts_with = "quiet")]
     pub verbose: u8,
@@ -104,10 +141,74 @@ pub enum Commands {
 
     /// Wallet subcommands
     Wallet(WalletOptions),
+
+    /// Interactive shell
+    Shell(ShellOptions),
+
+    /// Terminal dashboard
+    Dashboard(DashboardOptions),
+
+    /// Run a flow file
+    Run(RunOptions),
+
+    /// Raw request to the agent
+    Api(ApiOptions),
+
+    /// Local stand-in for an agent
+    MockAgent(MockAgentOptions),
+}
+
+impl Commands {
+    /// The agent modules that are required to execute the command
+    pub const fn capabilities(&self) -> &'static [Capability] {
+        match self {
+            Self::Automate(AutomationOptions {
+                commands: AutomationSubcommands::ProofRequest { .. },
+            }) => &[Capability::Connection, Capability::Proof],
+            Self::Automate(_) => &[
+                Capability::Connection,
+                Capability::Credential,
+                Capability::CredentialDefinition,
+                Capability::Schema,
+            ],
+            Self::Connection(_) | Self::Dashboard(_) => &[Capability::Connection],
+            Self::Webhook(_) => &[Capability::Webhook],
+            // The attachments of an invitation are credential offers and proof requests, and the
+            // connections of an invitation are looked up
+            Self::Oob(_) => &[
+                Capability::Oob,
+                Capability::Connection,
+                Capability::Credential,
+                Capability::Proof,
+            ],
+            Self::Feature(_) => &[Capability::Feature],
+            Self::Schema(_) => &[Capability::Schema],
+            Self::CredentialDefinition(_) => &[Capability::CredentialDefinition],
+            Self::Message(_) => &[Capability::BasicMessage, Capability::Connection],
+            Self::Credential(_) => &[Capability::Credential, Capability::Connection],
+            Self::Configuration(_) | Self::Shell(_) | Self::Run(_) | Self::MockAgent(_) => &[],
+            Self::Proof(_) => &[Capability::Proof, Capability::Connection],
+            Self::Multitenancy(_) => &[Capability::Multitenancy],
+            Self::Wallet(_) => &[Capability::Wallet],
+            Self::Api(_) => &[Capability::Api],
+        }
+    }
+}
+
+impl Commands {
+    /// Whether the command can print its requests instead of sending them. Interactive commands
+    /// keep sending requests while they run and the webhook listener connects over a websocket,
+    /// so those can not
+    pub const fn supports_dry_run(&self) -> bool {
+        !matches!(
+            self,
+            Self::Shell(_) | Self::Dashboard(_) | Self::Webhook(_) | Self::MockAgent(_)
+        )
+    }
 }
 
-impl From<Commands> for String {
-    fn from(c: Commands) -> Self {
+impl From<&Commands> for String {
+    fn from(c: &Commands) -> Self {
         let s = match c {
             Commands::Automate(_) => "Automate",
             Commands::Connection(_) => "Connection",
@@ -122,6 +223,11 @@ impl From<Commands> for String {
             Commands::Proof(_) => "Proof",
             Commands::Multitenancy(_) => "Multitenancy",
             Commands::Wallet(_) => "Wallet",
+            Commands::Shell(_) => "Shell",
+            Commands::Dashboard(_) => "Dashboard",
+            Commands::Run(_) => "Run",
+            Commands::Api(_) => "Api",
+            Commands::MockAgent(_) => "MockAgent",
         };
 
         Self::from(s)
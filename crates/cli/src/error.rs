@@ -47,14 +47,71 @@ pub enum Error {
     /// The configuration is empty
     EmptyConfiguration,
 
-    /// The agent flag was invalid and should be: aca-py or afj
-    InvalidAgent(String),
+    /// The agent flag was invalid and should be one of the registered agents
+    InvalidAgent(String, Vec<String>),
 
-    /// The subcommand is not registered for the specified agent
-    SubcommandNotRegisteredForAgent(String, &'static str),
+    /// The subcommand requires a module that the specified agent does not support
+    SubcommandNotRegisteredForAgent(String, String, String),
 
     /// The compare value supplied cannot be parsed into a number
     PredicateValueNonNumber(String, String),
+
+    /// The command can not be used inside the shell
+    UnavailableInShell(String),
+
+    /// The subcommand can not print its requests instead of sending them
+    DryRunUnsupported(String),
+
+    /// The flow file could not be parsed or is inconsistent
+    InvalidFlow(String),
+
+    /// A flow references an output that does not exist
+    UnknownFlowReference(String),
+
+    /// An assertion of a flow step did not hold
+    FlowAssertionFailed(String, String),
+
+    /// A flow step did not finish within its timeout
+    FlowStepTimedOut(String, u64),
+
+    /// The rows of a bulk file could not be read
+    InvalidBulkFile(String),
+
+    /// Some rows of a bulk operation failed
+    BulkOfferFailed(usize, String),
+
+    /// No connection has the id, alias or label
+    UnknownConnection(String),
+
+    /// More than one connection has the alias or label
+    AmbiguousConnection(String, Vec<String>),
+
+    /// No connection was supplied and none could be picked
+    NoConnectionSupplied,
+
+    /// An attribute is not of the form `name=value`
+    InvalidAttribute(String),
+
+    /// The attributes file is not a JSON object or an array of attributes
+    InvalidAttributesFile(String, String),
+
+    /// An attribute is supplied more than once
+    DuplicateAttribute(String),
+
+    /// A query parameter is not of the form `key=value`
+    InvalidQueryParameter(String),
+
+    /// The file of an attachment of an invitation is not a valid proof request or credential offer
+    InvalidAttachmentFile(String, String),
+
+    /// An invitation without a handshake has nothing to offer or request
+    AttachmentRequired,
+
+    /// The body of a request is not valid JSON
+    InvalidApiBody(String),
+
+    /// A presentation was verified, but it was not valid
+    ProofNotVerified(String),
 }
 
 impl std::error::Error for Error {}
@@ -79,8 +136,27 @@ impl Display for Error {
             Self::InactiveConnection => write!(f, "The connection was not activated within the specified time. Please try again with a higher --timeout."),
             Self::EmptyConfiguration => write!(f, "Unable to delete from an empty configuration"),
             Self::PredicateValueNonNumber(name, val) => write!(f, "Predicate value {val}, for name {name}, is not of type number."),
-            Self::InvalidAgent(agent) => write!(f, "Invalid agent '{agent}' supplied. Choose one of the following: 'aca-py' or 'afj'. (aca-py is default)"),
-            Self::SubcommandNotRegisteredForAgent(subcommand, agent) => write!(f, "Subcommand '{subcommand}' is not registered for {agent}.")
+            Self::InvalidAgent(agent, agents) => write!(f, "Invalid agent '{agent}' supplied. Choose one of the following: '{}'. (aca-py is default)", agents.join("', '")),
+            Self::SubcommandNotRegisteredForAgent(subcommand, agent, capability) => write!(f, "Subcommand '{subcommand}' is not registered for {agent}. The agent does not support the {capability} module."),
+            Self::UnavailableInShell(subcommand) => write!(f, "Subcommand '{subcommand}' can not be used inside the shell."),
+            Self::DryRunUnsupported(subcommand) => write!(f, "Subcommand '{subcommand}' does not support --dry-run."),
+            Self::InvalidFlow(reason) => write!(f, "Invalid flow: {reason}"),
+            Self::UnknownFlowReference(reference) => write!(f, "The flow references '{reference}', which does not exist. Only outputs of earlier steps with an id can be referenced."),
+            Self::FlowAssertionFailed(step, assertion) => write!(f, "Assertion of step '{step}' failed: {assertion}"),
+            Self::FlowStepTimedOut(step, timeout) => write!(f, "Step '{step}' did not finish within {timeout} seconds. Please try again with a higher timeout."),
+            Self::InvalidBulkFile(reason) => write!(f, "Invalid bulk file: {reason}"),
+            Self::BulkOfferFailed(failed, report) => write!(f, "The offers of {failed} row(s) failed. See {report} for the reasons and run the command again with --resume to retry them."),
+            Self::UnknownConnection(reference) => write!(f, "No connection has the id, alias or label '{reference}'."),
+            Self::AmbiguousConnection(reference, candidates) => write!(f, "Multiple connections have the alias or label '{reference}': {}. Use the connection id instead.", candidates.join(", ")),
+            Self::NoConnectionSupplied => write!(f, "No connection supplied. Supply the id, alias or label of the connection via `--connection-id`."),
+            Self::InvalidAttribute(attribute) => write!(f, "Invalid attribute '{attribute}'. Supply attributes as name=value."),
+            Self::InvalidAttributesFile(path, reason) => write!(f, "Invalid attributes file {path}: {reason}. It should contain a JSON object with the names and values or an array of objects with a name, a value and optionally a mime-type."),
+            Self::DuplicateAttribute(name) => write!(f, "The attribute '{name}' is supplied more than once."),
+            Self::InvalidQueryParameter(parameter) => write!(f, "Invalid query parameter '{parameter}'. Supply query parameters as key=value."),
+            Self::InvalidApiBody(reason) => write!(f, "The body is not valid JSON: {reason}"),
+            Self::InvalidAttachmentFile(path, reason) => write!(f, "Invalid attachment file {path}: {reason}. A proof request contains a name, attributes and predicates like \"age,>=,18\". A credential offer contains a cred_def_id and attributes."),
+            Self::ProofNotVerified(id) => write!(f, "The presentation of exchange {id} could not be verified."),
+            Self::AttachmentRequired => write!(f, "An invitation without a handshake needs a proof request or credential offer. Supply one via `--attach-proof-request` or `--attach-credential-offer`."),
         }
     }
 }
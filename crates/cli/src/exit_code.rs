@@ -0,0 +1,184 @@
+use crate::error::Error;
+use siera_agent::error::{Error as AgentError, ErrorCategory};
+use siera_automations::error::Error as AutomationError;
+use siera_logger::envelope::EnvelopeError;
+
+/// Exit codes of the cli. Scripts can rely on these, so existing values must never change
+///
+/// | Code | Meaning                                                              |
+/// | ---- | -------------------------------------------------------------------- |
+/// | 0    | Success                                                              |
+/// | 1    | Any error that does not fit one of the categories below              |
+/// | 2    | Invalid usage, e.g. a flag or a subcommand the agent does not support |
+/// | 3    | Invalid or missing configuration, environment, TLS or http options   |
+/// | 4    | The agent could not be reached or is unavailable                     |
+/// | 5    | The agent rejected the api key or token                              |
+/// | 6    | The requested resource does not exist on the agent                   |
+/// | 7    | The input or the request was rejected (4xx)                          |
+/// | 8    | The agent failed while handling the request (5xx)                    |
+/// | 9    | The agent or the other party did not respond in time                 |
+/// | 10   | A proof was received but could not be verified                       |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// The command succeeded
+    Success = 0,
+
+    /// Any error that does not fit one of the other categories
+    General = 1,
+
+    /// Invalid usage of the cli. Also used by clap when the arguments can not be parsed
+    Usage = 2,
+
+    /// Invalid or missing configuration
+    Configuration = 3,
+
+    /// The agent could not be reached or is unavailable
+    Connectivity = 4,
+
+    /// The agent did not accept the credentials
+    Authorization = 5,
+
+    /// The requested resource does not exist
+    NotFound = 6,
+
+    /// The input or the request was rejected
+    Validation = 7,
+
+    /// The agent responded with a 5xx status code
+    Server = 8,
+
+    /// The agent or the other party did not respond in time
+    Timeout = 9,
+
+    /// A presentation was not verified
+    VerificationFailed = 10,
+}
+
+impl ExitCode {
+    /// Exit code for an error that was returned from a command
+    pub fn from_error(error: &(dyn std::error::Error + 'static)) -> Self {
+        if let Some(e) = error.downcast_ref::<Error>() {
+            Self::from(e)
+        } else if let Some(e) = error.downcast_ref::<AgentError>() {
+            Self::from(e.category())
+        } else if let Some(e) = error.downcast_ref::<AutomationError>() {
+            Self::from(e)
+        } else {
+            Self::General
+        }
+    }
+
+    /// Report an error that was returned from a command and return its exit code
+    ///
+    /// In JSON mode the error is emitted as the envelope of the command. Agent errors carry the
+    /// request and response, which are reported separately
+    pub fn report(error: &(dyn std::error::Error + 'static)) -> Self {
+        let exit_code = Self::from_error(error);
+        let should_output_json = siera_logger::STATE
+            .read()
+            .is_ok_and(|state| state.should_output_json);
+        if should_output_json {
+            let agent_error = error.downcast_ref::<AgentError>();
+            siera_logger::envelope::finish(Some(EnvelopeError {
+                message: agent_error.map_or_else(|| error.to_string(), AgentError::message),
+                category: exit_code.category().to_owned(),
+                exit_code: exit_code.into(),
+                request: agent_error
+                    .and_then(AgentError::http_context)
+                    .and_then(|context| serde_json::to_value(context).ok()),
+            }));
+        } else {
+            error!({"error": error.to_string()});
+        }
+        exit_code
+    }
+
+    /// Name of the error category, as it is reported in the JSON output
+    pub const fn category(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::General => "general",
+            Self::Usage => "usage",
+            Self::Configuration => "configuration",
+            Self::Connectivity => "connectivity",
+            Self::Authorization => "authorization",
+            Self::NotFound => "not_found",
+            Self::Validation => "validation",
+            Self::Server => "server",
+            Self::Timeout => "timeout",
+            Self::VerificationFailed => "verification_failed",
+        }
+    }
+}
+
+impl From<ExitCode> for i32 {
+    fn from(code: ExitCode) -> Self {
+        code as Self
+    }
+}
+
+impl From<&Error> for ExitCode {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::CannotReadConfigurationFile
+            | Error::InvalidConfigurationPath
+            | Error::InvalidConfigurationStructure
+            | Error::InvalidEnvironment(_)
+            | Error::NoAgentURLSupplied
+            | Error::NoEnvironmentSupplied
+            | Error::HomeNotFound
+            | Error::OsUnknown
+            | Error::EmptyConfiguration
+            | Error::InvalidAgent(..) => Self::Configuration,
+            Error::SubcommandNotRegisteredForAgent(..)
+            | Error::UnavailableInShell(_)
+            | Error::DryRunUnsupported(_)
+            | Error::NoConnectionSupplied
+            | Error::AttachmentRequired => Self::Usage,
+            Error::InvalidAgentInvitation
+            | Error::UnequalAmountKeyValue
+            | Error::RequiredAttributes
+            | Error::PredicateValueNonNumber(..)
+            | Error::InvalidFlow(_)
+            | Error::UnknownFlowReference(_)
+            | Error::InvalidBulkFile(_)
+            | Error::AmbiguousConnection(..)
+            | Error::InvalidAttribute(_)
+            | Error::InvalidAttributesFile(..)
+            | Error::DuplicateAttribute(_)
+            | Error::InvalidQueryParameter(_)
+            | Error::InvalidApiBody(_)
+            | Error::InvalidAttachmentFile(..) => Self::Validation,
+            Error::ProofNotVerified(_) => Self::VerificationFailed,
+            Error::UnknownConnection(_) => Self::NotFound,
+            Error::InactiveConnection | Error::FlowStepTimedOut(..) => Self::Timeout,
+            Error::FlowAssertionFailed(..) | Error::BulkOfferFailed(..) => Self::General,
+        }
+    }
+}
+
+impl From<ErrorCategory> for ExitCode {
+    fn from(category: ErrorCategory) -> Self {
+        match category {
+            ErrorCategory::Authorization => Self::Authorization,
+            ErrorCategory::NotFound => Self::NotFound,
+            ErrorCategory::InvalidRequest => Self::Validation,
+            ErrorCategory::Server => Self::Server,
+            ErrorCategory::Network => Self::Connectivity,
+            ErrorCategory::Timeout => Self::Timeout,
+            ErrorCategory::Configuration => Self::Configuration,
+            ErrorCategory::Unsupported => Self::Usage,
+            ErrorCategory::Response | ErrorCategory::Other => Self::General,
+        }
+    }
+}
+
+impl From<&AutomationError> for ExitCode {
+    fn from(error: &AutomationError) -> Self {
+        match error {
+            AutomationError::ExchangeAbandoned(_) => Self::Validation,
+            AutomationError::ConnectionNotReady | AutomationError::StepTimedOut(_) => Self::Timeout,
+            AutomationError::ProofNotVerified(_) => Self::VerificationFailed,
+        }
+    }
+}
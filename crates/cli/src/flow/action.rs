@@ -0,0 +1,371 @@
+use crate::error::{Error, Result};
+use crate::modules::connection::invite_url_to_struct;
+use crate::modules::proof::parse_predicates;
+use crate::utils::resolve::resolve_connection_id;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use siera_agent::agent::{Agent, Capability};
+use siera_agent::modules::basic_message::SendBasicMessageOptions;
+use siera_agent::modules::connection::{
+    Connection, ConnectionCreateInvitationOptions, ConnectionGetAllOptions, ConnectionModule,
+    ConnectionState,
+};
+use siera_agent::modules::credential::{CredentialAttribute, CredentialOfferOptions};
+use siera_agent::modules::credential_definition::{
+    CredentialDefinitionCreateOptions, CredentialDefinitionModule,
+};
+use siera_agent::modules::proof::ProofRequestOptions;
+use siera_agent::modules::schema::{SchemaCreateOptions, SchemaModule};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// What a step does. The name is the `action` of the step and the fields are its `with`
+#[derive(Deserialize, Debug)]
+#[serde(tag = "action", content = "with", deny_unknown_fields)]
+pub enum Action {
+    /// Create a connection invitation
+    #[serde(rename = "connection.invite")]
+    ConnectionInvite(ConnectionInvite),
+
+    /// Receive a connection invitation
+    #[serde(rename = "connection.receive")]
+    ConnectionReceive(ConnectionReceive),
+
+    /// Fetch a single connection
+    #[serde(rename = "connection.get")]
+    ConnectionGet(ConnectionGet),
+
+    /// Fetch the connections, optionally filtered
+    #[serde(rename = "connection.list")]
+    ConnectionList(ConnectionList),
+
+    /// Register a schema
+    #[serde(rename = "schema.create")]
+    SchemaCreate(SchemaCreate),
+
+    /// Register a credential definition
+    #[serde(rename = "credential_definition.create")]
+    CredentialDefinitionCreate(CredentialDefinitionCreate),
+
+    /// Offer a credential to a connection
+    #[serde(rename = "credential.offer")]
+    CredentialOffer(CredentialOffer),
+
+    /// Request a proof from a connection
+    #[serde(rename = "proof.request")]
+    ProofRequest(ProofRequest),
+
+    /// Send a basic message to a connection
+    #[serde(rename = "message.send")]
+    MessageSend(MessageSend),
+
+    /// Wait for an amount of seconds
+    #[serde(rename = "sleep")]
+    Sleep(Sleep),
+
+    /// Do nothing. Used for steps that only have assertions
+    #[serde(rename = "assert")]
+    Assert {},
+}
+
+/// Options of `connection.invite`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionInvite {
+    /// Alias of the connection
+    alias: Option<String>,
+
+    /// Whether the connection is accepted automatically
+    #[serde(default = "default_true")]
+    auto_accept: bool,
+
+    /// Whether the invitation can be used multiple times
+    #[serde(default)]
+    multi_use: bool,
+}
+
+/// Options of `connection.receive`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionReceive {
+    /// The invitation url
+    url: String,
+}
+
+/// Options of `connection.get`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionGet {
+    /// Id of the connection
+    id: String,
+}
+
+/// Options of `connection.list`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionList {
+    /// Alias to filter on
+    alias: Option<String>,
+
+    /// State to filter on
+    state: Option<ConnectionState>,
+}
+
+/// Options of `schema.create`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaCreate {
+    /// Name of the schema
+    name: String,
+
+    /// Version of the schema
+    #[serde(default = "default_version")]
+    version: String,
+
+    /// Attributes of the schema
+    attributes: Vec<String>,
+}
+
+/// Options of `credential_definition.create`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CredentialDefinitionCreate {
+    /// Schema the credential definition is based on
+    schema_id: String,
+
+    /// Tag of the credential definition
+    tag: Option<String>,
+}
+
+/// Options of `credential.offer`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CredentialOffer {
+    /// Id, alias or label of the connection the credential is offered to
+    connection_id: String,
+
+    /// Credential definition of the credential
+    cred_def_id: String,
+
+    /// Names and values of the attributes
+    attributes: BTreeMap<String, Value>,
+}
+
+/// Options of `proof.request`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ProofRequest {
+    /// Id, alias or label of the connection the proof is requested from
+    connection_id: String,
+
+    /// Name of the proof request
+    #[serde(default = "default_proof_name")]
+    name: String,
+
+    /// Requested attributes
+    #[serde(default)]
+    attributes: Vec<String>,
+
+    /// Requested predicates, e.g. `age,>=,18`
+    #[serde(default)]
+    predicates: Vec<String>,
+}
+
+/// Options of `message.send`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MessageSend {
+    /// Id, alias or label of the connection the message is sent to
+    connection_id: String,
+
+    /// The message
+    message: String,
+}
+
+/// Options of `sleep`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Sleep {
+    /// Amount of seconds to wait
+    seconds: u64,
+}
+
+/// Serde default for flags that are enabled unless disabled explicitly
+const fn default_true() -> bool {
+    true
+}
+
+/// Serde default for the version of a schema
+fn default_version() -> String {
+    String::from("1.0")
+}
+
+/// Serde default for the name of a proof request
+fn default_proof_name() -> String {
+    String::from("proof-request")
+}
+
+impl Action {
+    /// Parse the action of a step from its name and its resolved options
+    pub fn parse(name: &str, with: Value) -> Result<Self> {
+        let mut step = Map::new();
+        step.insert(String::from("action"), Value::String(name.to_owned()));
+        // A step without `with` uses the defaults of every option
+        let with = if with.is_null() {
+            Value::Object(Map::new())
+        } else {
+            with
+        };
+        step.insert(String::from("with"), with);
+        serde_json::from_value(Value::Object(step))
+            .map_err(|e| Error::InvalidFlow(format!("action '{name}': {e}")).into())
+    }
+
+    /// The module the agent needs for the action
+    pub const fn capability(&self) -> Option<Capability> {
+        match self {
+            Self::ConnectionInvite(_)
+            | Self::ConnectionReceive(_)
+            | Self::ConnectionGet(_)
+            | Self::ConnectionList(_) => Some(Capability::Connection),
+            Self::SchemaCreate(_) => Some(Capability::Schema),
+            Self::CredentialDefinitionCreate(_) => Some(Capability::CredentialDefinition),
+            Self::CredentialOffer(_) => Some(Capability::Credential),
+            Self::ProofRequest(_) => Some(Capability::Proof),
+            Self::MessageSend(_) => Some(Capability::BasicMessage),
+            Self::Sleep(_) | Self::Assert {} => None,
+        }
+    }
+
+    /// Execute the action and return its output, which later steps can reference
+    #[allow(clippy::too_many_lines)]
+    pub async fn execute(self, agent: &dyn Agent) -> Result<Value> {
+        let output = match self {
+            Self::ConnectionInvite(options) => {
+                let invitation = ConnectionModule::create_invitation(
+                    agent,
+                    ConnectionCreateInvitationOptions {
+                        alias: options.alias,
+                        auto_accept: options.auto_accept,
+                        multi_use: options.multi_use,
+                        ..ConnectionCreateInvitationOptions::default()
+                    },
+                )
+                .await?;
+                json!({
+                    "connection_id": invitation.id,
+                    "invitation_url": invitation.invitation_url,
+                    "invitation": invitation.invitation,
+                })
+            }
+            Self::ConnectionReceive(options) => {
+                let invitation = invite_url_to_struct(options.url)?;
+                connection_output(&ConnectionModule::receive_invitation(agent, invitation).await?)?
+            }
+            Self::ConnectionGet(options) => {
+                connection_output(&ConnectionModule::get_by_id(agent, options.id).await?)?
+            }
+            Self::ConnectionList(options) => {
+                let connections = ConnectionModule::get_all(
+                    agent,
+                    ConnectionGetAllOptions {
+                        alias: options.alias,
+                        state: options.state,
+                        ..ConnectionGetAllOptions::default()
+                    },
+                )
+                .await?;
+                json!({ "connections": connections })
+            }
+            Self::SchemaCreate(options) => serde_json::to_value(
+                SchemaModule::create(
+                    agent,
+                    SchemaCreateOptions {
+                        name: options.name,
+                        version: options.version,
+                        attributes: options.attributes,
+                    },
+                )
+                .await?,
+            )?,
+            Self::CredentialDefinitionCreate(options) => {
+                let default = CredentialDefinitionCreateOptions::default();
+                serde_json::to_value(
+                    CredentialDefinitionModule::create(
+                        agent,
+                        CredentialDefinitionCreateOptions {
+                            schema_id: options.schema_id,
+                            tag: options.tag.unwrap_or(default.tag),
+                            ..default
+                        },
+                    )
+                    .await?,
+                )?
+            }
+            Self::CredentialOffer(options) => {
+                let attributes = options
+                    .attributes
+                    .iter()
+                    .map(|(name, value)| {
+                        CredentialAttribute::new(name, super::template::to_text(value))
+                    })
+                    .collect();
+                let connection_id =
+                    resolve_connection_id(agent, Some(&options.connection_id)).await?;
+                serde_json::to_value(
+                    agent
+                        .send_offer(CredentialOfferOptions {
+                            connection_id,
+                            cred_def_id: options.cred_def_id,
+                            attributes,
+                        })
+                        .await?,
+                )?
+            }
+            Self::ProofRequest(options) => {
+                let predicates = parse_predicates(&options.predicates)?;
+                let connection_id =
+                    resolve_connection_id(agent, Some(&options.connection_id)).await?;
+                serde_json::to_value(
+                    agent
+                        .send_request(ProofRequestOptions {
+                            connection_id,
+                            name: options.name,
+                            attributes: options.attributes,
+                            predicates,
+                        })
+                        .await?,
+                )?
+            }
+            Self::MessageSend(options) => {
+                let connection_id =
+                    resolve_connection_id(agent, Some(&options.connection_id)).await?;
+                agent
+                    .send_message(SendBasicMessageOptions {
+                        connection_id,
+                        message: options.message,
+                    })
+                    .await?;
+                Value::Null
+            }
+            Self::Sleep(options) => {
+                tokio::time::sleep(Duration::from_secs(options.seconds)).await;
+                Value::Null
+            }
+            Self::Assert {} => Value::Null,
+        };
+        Ok(output)
+    }
+}
+
+/// Output of a connection, with the id also available as `connection_id`
+fn connection_output(connection: &Connection) -> Result<Value> {
+    let mut output = serde_json::to_value(connection)?;
+    if let Value::Object(object) = &mut output {
+        object.insert(
+            String::from("connection_id"),
+            Value::String(connection.id.clone()),
+        );
+    }
+    Ok(output)
+}
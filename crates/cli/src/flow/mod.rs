@@ -0,0 +1,294 @@
+use crate::error::{Error, Result};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use siera_agent::agent::Agent;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// The actions a step can execute
+mod action;
+
+/// Resolving references to the outputs of earlier steps
+mod template;
+
+/// Unit tests of parsing, resolving and validating flows and of their conditions
+#[cfg(test)]
+mod tests;
+
+use action::Action;
+
+/// A flow as it is written in the YAML file. Its steps are executed in order against one or more
+/// environments. Steps can reference the outputs of earlier steps with `${steps.<id>.<field>}`,
+/// retry until a condition holds and assert on their output
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Flow {
+    /// Name of the flow, only used for logging
+    pub name: Option<String>,
+
+    /// Environment of the steps that do not set one. The environment of the cli is used when
+    /// this is not set either
+    pub environment: Option<String>,
+
+    /// Maximum amount of seconds a step may take, unless the step sets its own timeout
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+
+    /// The steps, executed in order
+    pub steps: Vec<Step>,
+}
+
+/// A single step of a flow
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    /// Id with which later steps can reference the output of this step
+    pub id: Option<String>,
+
+    /// Environment of the configuration file the step is executed against
+    pub environment: Option<String>,
+
+    /// Name of the action, e.g. `connection.invite`
+    pub action: String,
+
+    /// Options of the action. Strings may contain references
+    #[serde(default)]
+    pub with: Value,
+
+    /// Maximum amount of seconds the step may take, including retries
+    pub timeout: Option<u64>,
+
+    /// Conditions on the output. The action is retried until all of them hold
+    #[serde(default)]
+    pub until: Vec<Condition>,
+
+    /// Amount of seconds between retries of the action
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+
+    /// Conditions that must hold after the action. The flow fails when one of them does not
+    #[serde(default)]
+    pub assert: Vec<Condition>,
+}
+
+/// A condition on the output of a step or on a referenced value
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Condition {
+    /// Dotted path into the output of the step, e.g. `state`
+    pub path: Option<String>,
+
+    /// Value to check instead of the output of the step. May contain references
+    pub value: Option<Value>,
+
+    /// The value must be equal to this
+    pub equals: Option<Value>,
+
+    /// The value must be equal to one of these
+    pub one_of: Option<Vec<Value>>,
+
+    /// The value must not be null, an empty string or an empty array or object
+    #[serde(default)]
+    pub not_empty: bool,
+}
+
+/// Serde default for the timeout of a step
+const fn default_timeout() -> u64 {
+    60
+}
+
+/// Serde default for the interval between retries
+const fn default_interval() -> u64 {
+    1
+}
+
+impl Flow {
+    /// Read and validate a flow file
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let flow: Self =
+            serde_yaml::from_str(&content).map_err(|e| Error::InvalidFlow(e.to_string()))?;
+        flow.validate()?;
+        Ok(flow)
+    }
+
+    /// Check that step ids are unique and that steps only reference earlier steps
+    fn validate(&self) -> Result<()> {
+        let mut known: Vec<&str> = vec![];
+        for (index, step) in self.steps.iter().enumerate() {
+            let mut values = vec![&step.with];
+            values.extend(step.until.iter().chain(&step.assert).flat_map(|c| &c.value));
+            for reference in values.into_iter().flat_map(template::references) {
+                if let Some(path) = reference.strip_prefix("steps.") {
+                    let id = path.split('.').next().unwrap_or_default();
+                    if !known.contains(&id) {
+                        return Err(Error::UnknownFlowReference(reference).into());
+                    }
+                }
+            }
+            if let Some(id) = &step.id {
+                if known.contains(&id.as_str()) {
+                    return Err(Error::InvalidFlow(format!("duplicate step id '{id}'")).into());
+                }
+                known.push(id);
+            }
+            if step.until.is_empty() && step.interval != default_interval() {
+                return Err(Error::InvalidFlow(format!(
+                    "step {} sets an interval without `until`",
+                    step.label(index)
+                ))
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Execute the steps in order and return the outputs of the steps with an id
+    ///
+    /// `build_agent` builds the agent for an environment, or for the environment of the cli when
+    /// none is supplied. Every agent is built once
+    pub async fn execute(
+        &self,
+        build_agent: impl Fn(Option<&str>) -> Result<Box<dyn Agent>>,
+    ) -> Result<Map<String, Value>> {
+        let mut agents: HashMap<Option<String>, Box<dyn Agent>> = HashMap::new();
+        let mut steps = Map::new();
+
+        if let Some(name) = &self.name {
+            info!({ "message": format!("Running flow {name}") });
+        }
+
+        for (index, step) in self.steps.iter().enumerate() {
+            let label = step.label(index);
+            let context = json!({ "steps": &steps });
+            let environment = step
+                .environment
+                .clone()
+                .or_else(|| self.environment.clone());
+            if !agents.contains_key(&environment) {
+                let agent = build_agent(environment.as_deref())?;
+                agents.insert(environment.clone(), agent);
+            }
+            let agent = agents
+                .get(&environment)
+                .ok_or_else(|| Error::InvalidFlow(format!("no agent for step {label}")))?;
+
+            info!({ "message": format!("Running step {label} ({})", step.action) });
+            let timeout = step.timeout.unwrap_or(self.timeout);
+            let output = tokio::time::timeout(
+                Duration::from_secs(timeout),
+                step.execute(agent.as_ref(), &context),
+            )
+            .await
+            .map_err(|_| Error::FlowStepTimedOut(label.clone(), timeout))??;
+
+            for condition in &step.assert {
+                if !condition.holds(&output, &context)? {
+                    return Err(Error::FlowAssertionFailed(label, condition.describe()).into());
+                }
+            }
+            debug!({ "step": label, "output": output });
+
+            if let Some(id) = &step.id {
+                steps.insert(id.clone(), output);
+            }
+        }
+
+        Ok(steps)
+    }
+}
+
+impl Step {
+    /// Name of the step in messages. The id, or the position when the step has no id
+    fn label(&self, index: usize) -> String {
+        self.id.clone().unwrap_or_else(|| format!("#{}", index + 1))
+    }
+
+    /// Execute the action, and retry it until the `until` conditions hold
+    async fn execute(&self, agent: &dyn Agent, context: &Value) -> Result<Value> {
+        loop {
+            let with = template::resolve(&self.with, context)?;
+            let action = Action::parse(&self.action, with)?;
+            if let Some(capability) = action.capability() {
+                if !agent.supports(capability) {
+                    return Err(Error::SubcommandNotRegisteredForAgent(
+                        self.action.clone(),
+                        agent.to_string(),
+                        capability.to_string(),
+                    )
+                    .into());
+                }
+            }
+            let output = action.execute(agent).await?;
+
+            let mut done = true;
+            for condition in &self.until {
+                done = done && condition.holds(&output, context)?;
+            }
+            if done {
+                return Ok(output);
+            }
+            trace!({ "message": "Condition does not hold yet, trying again", "output": output });
+            tokio::time::sleep(Duration::from_secs(self.interval)).await;
+        }
+    }
+}
+
+impl Condition {
+    /// The value that is checked
+    fn actual(&self, output: &Value, context: &Value) -> Result<Value> {
+        match (&self.path, &self.value) {
+            (Some(path), None) => Ok(template::lookup(output, path)
+                .cloned()
+                .unwrap_or(Value::Null)),
+            (None, Some(value)) => template::resolve(value, context),
+            _ => Err(Error::InvalidFlow(String::from(
+                "a condition needs either a `path` or a `value`",
+            ))
+            .into()),
+        }
+    }
+
+    /// Whether the condition holds
+    fn holds(&self, output: &Value, context: &Value) -> Result<bool> {
+        let actual = self.actual(output, context)?;
+        // Values are compared by their text, so `1` equals `"1"`
+        let equal = |expected: &Value| template::to_text(&actual) == template::to_text(expected);
+
+        let not_empty = match &actual {
+            Value::Null => false,
+            Value::String(s) => !s.is_empty(),
+            Value::Array(items) => !items.is_empty(),
+            Value::Object(object) => !object.is_empty(),
+            _ => true,
+        };
+
+        Ok(self.equals.as_ref().is_none_or(equal)
+            && self
+                .one_of
+                .as_ref()
+                .is_none_or(|values| values.iter().any(equal))
+            && (!self.not_empty || not_empty))
+    }
+
+    /// Human readable description of the condition
+    fn describe(&self) -> String {
+        let subject = match (&self.path, &self.value) {
+            (Some(path), _) => path.clone(),
+            (None, Some(value)) => template::to_text(value),
+            (None, None) => String::from("value"),
+        };
+        let mut checks = vec![];
+        if let Some(expected) = &self.equals {
+            checks.push(format!("equals {expected}"));
+        }
+        if let Some(values) = &self.one_of {
+            checks.push(format!("is one of {}", Value::Array(values.clone())));
+        }
+        if self.not_empty {
+            checks.push(String::from("is not empty"));
+        }
+        format!("{subject} {}", checks.join(" and "))
+    }
+}
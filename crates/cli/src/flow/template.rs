@@ -0,0 +1,127 @@
+use crate::error::{Error, Result};
+use serde_json::Value;
+
+/// Start of a reference, e.g. `${steps.invite.connection_id}`
+const OPEN: &str = "${";
+
+/// End of a reference
+const CLOSE: &str = "}";
+
+/// Replace every reference in the strings of `value` with the value it points to in `context`
+///
+/// A string that is a single reference is replaced by the value itself, so objects, arrays and
+/// numbers keep their type. References that are part of a longer string are replaced by their
+/// text. `${env.NAME}` is replaced by the environment variable `NAME`
+pub fn resolve(value: &Value, context: &Value) -> Result<Value> {
+    match value {
+        Value::String(s) => resolve_str(s, context),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| resolve(item, context))
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Array),
+        Value::Object(object) => object
+            .iter()
+            .map(|(k, v)| resolve(v, context).map(|v| (k.clone(), v)))
+            .collect::<Result<serde_json::Map<_, _>>>()
+            .map(Value::Object),
+        v => Ok(v.clone()),
+    }
+}
+
+/// Every reference in the strings of `value`, without the `${` and `}`
+pub fn references(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => split(s)
+            .into_iter()
+            .filter_map(|part| match part {
+                Part::Reference(r) => Some(r.to_owned()),
+                Part::Text(_) => None,
+            })
+            .collect(),
+        Value::Array(items) => items.iter().flat_map(references).collect(),
+        Value::Object(object) => object.values().flat_map(references).collect(),
+        _ => vec![],
+    }
+}
+
+/// Value at a dotted path, e.g. `connections.0.id`. Numeric segments index arrays
+pub fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |value, segment| match value {
+            Value::Object(object) => object.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
+
+/// Text of a value as it is inserted into a string
+pub fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        v => v.to_string(),
+    }
+}
+
+/// A piece of a string that may contain references
+enum Part<'a> {
+    /// Literal text
+    Text(&'a str),
+
+    /// A reference without the `${` and `}`
+    Reference(&'a str),
+}
+
+/// Split a string into text and references. An unterminated `${` is kept as text
+fn split(s: &str) -> Vec<Part<'_>> {
+    let mut parts = vec![];
+    let mut rest = s;
+    while let Some(start) = rest.find(OPEN) {
+        let Some(end) = rest[start..].find(CLOSE) else {
+            break;
+        };
+        if start > 0 {
+            parts.push(Part::Text(&rest[..start]));
+        }
+        parts.push(Part::Reference(
+            rest[start + OPEN.len()..start + end].trim(),
+        ));
+        rest = &rest[start + end + CLOSE.len()..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    parts
+}
+
+/// Resolve the references of a single string
+fn resolve_str(s: &str, context: &Value) -> Result<Value> {
+    let parts = split(s);
+    if let [Part::Reference(reference)] = parts.as_slice() {
+        return resolve_reference(reference, context);
+    }
+    parts
+        .iter()
+        .map(|part| match part {
+            Part::Text(text) => Ok((*text).to_owned()),
+            Part::Reference(reference) => {
+                resolve_reference(reference, context).map(|v| to_text(&v))
+            }
+        })
+        .collect::<Result<String>>()
+        .map(Value::String)
+}
+
+/// Value of a single reference
+fn resolve_reference(reference: &str, context: &Value) -> Result<Value> {
+    if let Some(name) = reference.strip_prefix("env.") {
+        return std::env::var(name)
+            .map(Value::String)
+            .map_err(|_| Error::UnknownFlowReference(reference.to_owned()).into());
+    }
+    lookup(context, reference)
+        .cloned()
+        .ok_or_else(|| Error::UnknownFlowReference(reference.to_owned()).into())
+}
@@ -0,0 +1,290 @@
+use super::action::Action;
+use super::template::{lookup, references, resolve};
+use super::{Condition, Flow};
+use crate::error::Error;
+use serde_json::{json, Value};
+use siera_agent::agent::Capability;
+
+/// Parse a flow from YAML without validating it
+fn flow(yaml: &str) -> Flow {
+    serde_yaml::from_str(yaml).expect("flow should parse")
+}
+
+/// Parse a condition from JSON
+fn condition(value: Value) -> Condition {
+    serde_json::from_value(value).expect("condition should parse")
+}
+
+/// The cli error of a result, when it failed with one
+fn cli_error<T>(result: crate::error::Result<T>) -> Option<Error> {
+    result
+        .err()
+        .and_then(|e| e.downcast::<Error>().ok())
+        .map(|e| *e)
+}
+
+#[test]
+fn splits_every_reference_out_of_a_string() {
+    let value = json!({
+        "url": "${steps.invite.url}",
+        "message": ["Hello ${ env.NAME }, this is ${steps.me.label}"],
+        "count": 1,
+    });
+
+    let mut found = references(&value);
+    found.sort();
+
+    assert_eq!(found, ["env.NAME", "steps.invite.url", "steps.me.label"]);
+}
+
+#[test]
+fn keeps_an_unterminated_reference_as_text() {
+    let context = json!({});
+
+    assert!(references(&json!("${steps.invite")).is_empty());
+    assert_eq!(
+        resolve(&json!("cost: ${steps"), &context).unwrap(),
+        json!("cost: ${steps")
+    );
+}
+
+#[test]
+fn resolves_a_single_reference_to_the_value_itself() {
+    let context = json!({ "steps": { "offer": { "count": 2, "attributes": { "age": "18" } } } });
+
+    assert_eq!(
+        resolve(&json!("${steps.offer.count}"), &context).unwrap(),
+        json!(2)
+    );
+    assert_eq!(
+        resolve(&json!({ "with": ["${steps.offer.attributes}"] }), &context).unwrap(),
+        json!({ "with": [{ "age": "18" }] })
+    );
+}
+
+#[test]
+fn resolves_references_inside_text_to_their_text() {
+    let context = json!({ "steps": { "offer": { "count": 2, "state": null } } });
+
+    assert_eq!(
+        resolve(
+            &json!("${steps.offer.count} offers, state '${steps.offer.state}'"),
+            &context
+        )
+        .unwrap(),
+        json!("2 offers, state ''")
+    );
+}
+
+#[test]
+fn resolves_environment_variables() {
+    std::env::set_var("SIERA_FLOW_TEST_LABEL", "Alice");
+
+    assert_eq!(
+        resolve(&json!("Hi ${env.SIERA_FLOW_TEST_LABEL}"), &json!({})).unwrap(),
+        json!("Hi Alice")
+    );
+}
+
+#[test]
+fn rejects_unknown_references() {
+    let context = json!({ "steps": { "invite": {} } });
+
+    assert!(matches!(
+        cli_error(resolve(&json!("${steps.invite.url}"), &context)),
+        Some(Error::UnknownFlowReference(reference)) if reference == "steps.invite.url"
+    ));
+    assert!(matches!(
+        cli_error(resolve(&json!("${env.SIERA_FLOW_TEST_UNSET}"), &context)),
+        Some(Error::UnknownFlowReference(_))
+    ));
+}
+
+#[test]
+fn looks_up_dotted_paths_with_array_indices() {
+    let value = json!({ "connections": [{ "id": "a" }, { "id": "b" }] });
+
+    assert_eq!(lookup(&value, "connections.1.id"), Some(&json!("b")));
+    assert_eq!(lookup(&value, ""), Some(&value));
+    assert_eq!(lookup(&value, "connections.2.id"), None);
+    assert_eq!(lookup(&value, "connections.first"), None);
+    assert_eq!(lookup(&value, "connections.0.id.length"), None);
+}
+
+#[test]
+fn accepts_references_to_earlier_steps() {
+    let flow = flow(
+        r"
+steps:
+  - id: invite
+    action: connection.invite
+  - action: connection.receive
+    with:
+      url: ${steps.invite.invitation_url}
+    until:
+      - value: ${steps.invite.connection_id}
+        not_empty: true
+    interval: 2
+",
+    );
+
+    assert!(flow.validate().is_ok());
+}
+
+#[test]
+fn rejects_references_to_later_or_unknown_steps() {
+    let flow = flow(
+        r"
+steps:
+  - action: connection.receive
+    with:
+      url: ${steps.invite.invitation_url}
+  - id: invite
+    action: connection.invite
+",
+    );
+
+    assert!(matches!(
+        cli_error(flow.validate()),
+        Some(Error::UnknownFlowReference(reference)) if reference == "steps.invite.invitation_url"
+    ));
+}
+
+#[test]
+fn rejects_duplicate_step_ids() {
+    let flow = flow(
+        r"
+steps:
+  - id: invite
+    action: connection.invite
+  - id: invite
+    action: connection.invite
+",
+    );
+
+    assert!(matches!(
+        cli_error(flow.validate()),
+        Some(Error::InvalidFlow(reason)) if reason.contains("duplicate step id 'invite'")
+    ));
+}
+
+#[test]
+fn rejects_an_interval_without_until() {
+    let flow = flow(
+        r"
+steps:
+  - action: sleep
+    with:
+      seconds: 1
+    interval: 5
+",
+    );
+
+    assert!(matches!(
+        cli_error(flow.validate()),
+        Some(Error::InvalidFlow(reason)) if reason.contains("#1")
+    ));
+}
+
+#[test]
+fn parses_an_action_with_the_defaults_of_its_options() {
+    let action = Action::parse("connection.invite", Value::Null).unwrap();
+
+    assert!(matches!(action, Action::ConnectionInvite(_)));
+    assert_eq!(action.capability(), Some(Capability::Connection));
+    assert_eq!(
+        Action::parse("assert", Value::Null).unwrap().capability(),
+        None
+    );
+}
+
+#[test]
+fn rejects_unknown_actions_and_options() {
+    assert!(matches!(
+        cli_error(Action::parse("connection.delete", Value::Null)),
+        Some(Error::InvalidFlow(reason)) if reason.starts_with("action 'connection.delete'")
+    ));
+    assert!(matches!(
+        cli_error(Action::parse("sleep", json!({ "seconds": 1, "minutes": 1 }))),
+        Some(Error::InvalidFlow(reason)) if reason.contains("minutes")
+    ));
+}
+
+#[test]
+fn compares_values_by_their_text() {
+    let output = json!({ "count": 1, "state": "active" });
+
+    assert!(condition(json!({ "path": "count", "equals": "1" }))
+        .holds(&output, &json!({}))
+        .unwrap());
+    assert!(!condition(json!({ "path": "state", "equals": "request" }))
+        .holds(&output, &json!({}))
+        .unwrap());
+}
+
+#[test]
+fn holds_when_the_value_is_one_of_the_values() {
+    let output = json!({ "state": "response" });
+    let holds = |one_of: Value| {
+        condition(json!({ "path": "state", "one_of": one_of }))
+            .holds(&output, &json!({}))
+            .unwrap()
+    };
+
+    assert!(holds(json!(["response", "active"])));
+    assert!(!holds(json!(["active"])));
+}
+
+#[test]
+fn requires_a_value_to_be_present_when_not_empty() {
+    let output = json!({ "id": "a", "empty": "", "list": [], "object": {}, "zero": 0 });
+    let holds = |path: &str| {
+        condition(json!({ "path": path, "not_empty": true }))
+            .holds(&output, &json!({}))
+            .unwrap()
+    };
+
+    assert!(holds("id"));
+    assert!(holds("zero"));
+    assert!(!holds("empty"));
+    assert!(!holds("list"));
+    assert!(!holds("object"));
+    assert!(!holds("missing"));
+}
+
+#[test]
+fn checks_every_part_of_a_condition() {
+    let condition = condition(json!({
+        "value": "${steps.offer.state}",
+        "equals": "done",
+        "one_of": ["done", "abandoned"],
+        "not_empty": true,
+    }));
+    let context = |state: &str| json!({ "steps": { "offer": { "state": state } } });
+
+    assert!(condition.holds(&json!(null), &context("done")).unwrap());
+    assert!(!condition
+        .holds(&json!(null), &context("abandoned"))
+        .unwrap());
+}
+
+#[test]
+fn holds_without_checks() {
+    assert!(condition(json!({ "path": "missing" }))
+        .holds(&json!({}), &json!({}))
+        .unwrap());
+}
+
+#[test]
+fn needs_either_a_path_or_a_value() {
+    let output = json!({});
+
+    assert!(matches!(
+        cli_error(condition(json!({ "equals": 1 })).holds(&output, &output)),
+        Some(Error::InvalidFlow(_))
+    ));
+    assert!(matches!(
+        cli_error(condition(json!({ "path": "a", "value": 1 })).holds(&output, &output)),
+        Some(Error::InvalidFlow(_))
+    ));
+}
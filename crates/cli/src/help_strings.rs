@@ -11,10 +11,18 @@ pub enum HelpStrings {
     ApiKey,
     Copy,
     OutputJson,
+    Output,
+    Fields,
     Quiet,
     Verbose,
     Config,
     Environment,
+    ConnectTimeout,
+    RequestTimeout,
+    Retries,
+    Proxy,
+    UserAgent,
+    DryRun,
 
     // Configuration
     Configuration,
@@ -28,6 +36,34 @@ pub enum HelpStrings {
     // Webhook
     Webhook,
 
+    // Shell
+    Shell,
+    ShellWebhooks,
+
+    // Dashboard
+    Dashboard,
+    DashboardInterval,
+
+    // Run
+    Run,
+    RunFlow,
+
+    // Mock agent
+    MockAgent,
+    MockAgentServe,
+    MockAgentServePort,
+    MockAgentServeHost,
+    MockAgentServeLabel,
+    MockAgentServeApiKey,
+    MockAgentServeEndpoint,
+
+    // Api
+    Api,
+    ApiMethod,
+    ApiPath,
+    ApiQuery,
+    ApiBody,
+
     // OOB
     OobConnection,
     OobHandshakeProtocol,
@@ -41,6 +77,13 @@ pub enum HelpStrings {
     OobInviteMultiUse,
     OobInviteQr,
     OobInviteAutoAccept,
+    OobInviteAttachProofRequest,
+    OobInviteAttachCredentialOffer,
+    OobInviteNoHandshake,
+    OobReceiveAlias,
+    OobReceiveUseExistingConnection,
+    OobConnections,
+    OobConnectionsId,
 
     // Connections
     Connections,
@@ -60,8 +103,14 @@ pub enum HelpStrings {
     ConnectionsListState,
     ConnectionsListTheirDid,
     ConnectionsListTheirRole,
+    ConnectionsListInvitationMsgId,
     ConnectionsReceive,
     ConnectionsReceiveUrl,
+    ConnectionsEstablish,
+    ConnectionsEstablishFrom,
+    ConnectionsEstablishTo,
+    ConnectionsEstablishOob,
+    ConnectionsEstablishTimeout,
 
     // Credential Definitions
     CredentialDefinition,
@@ -80,6 +129,13 @@ pub enum HelpStrings {
     CredentialsOfferConnectionId,
     CredentialsOfferKey,
     CredentialsOfferValue,
+    CredentialsOfferAttribute,
+    CredentialsOfferAttributesFile,
+    CredentialsBulk,
+    CredentialsBulkFile,
+    CredentialsBulkConcurrency,
+    CredentialsBulkReport,
+    CredentialsBulkResume,
     CredentialsPropose,
     CredentialsProposeId,
 
@@ -107,6 +163,14 @@ pub enum HelpStrings {
     ProofRequestPredicate,
     ProofRequestAttribute,
     ProofRequestConnectionId,
+    ProofList,
+    ProofListId,
+    ProofListConnectionId,
+    ProofListRole,
+    ProofListState,
+    ProofListThreadId,
+    ProofVerify,
+    ProofVerifyId,
 
     // Multitenancy
     Multitenancy,
@@ -122,12 +186,30 @@ pub enum HelpStrings {
     AutomationCredentialOfferNoQr,
     AutomationCredentialOfferSelf,
     AutomationCredentialOfferTimeout,
+    AutomationCredentialOfferAttribute,
+    AutomationCredentialOfferAttributesFile,
+    AutomationCredentialOfferSchemaName,
+    AutomationCredentialOfferSchemaVersion,
+    AutomationCredentialOfferNewDefinition,
 
     // Create credential definition
     AutomationCreateCredentialDefinitionName,
     AutomationCreateCredentialDefinitionAttributes,
     AutomationCreateCredentialDefinitionVersion,
 
+    // Request a proof
+    AutomationProofRequest,
+    AutomationProofRequestConnectionId,
+    AutomationProofRequestTimeout,
+
+    // Issue and verify a credential
+    AutomationIssueAndVerify,
+    AutomationIssueAndVerifyIssuer,
+    AutomationIssueAndVerifyHolder,
+    AutomationIssueAndVerifyName,
+    AutomationIssueAndVerifyAttribute,
+    AutomationIssueAndVerifyTimeout,
+
     // Wallet
     Wallet,
     WalletCreate,
@@ -169,13 +251,41 @@ impl HelpStrings {
             Self::Agent => "Type of Aries agent (aca-py or afj) [default: aca-py]",
             Self::ApiKey => "This API key will be passed to the agent",
             Self::Copy => "Copy output to your clipboard",
-            Self::OutputJson => "Output format will be JSON",
+            Self::OutputJson => "Output format will be JSON. Same as `--output json`",
+            Self::Output => "Format in which the result is printed [default: plain]",
+            Self::Fields => "Comma separated fields of the result to print, e.g. `id,state`. Nested fields are selected with a dot, e.g. `invitation.label`",
             Self::Quiet => "Suppresses most output",
             Self::Verbose => "Print debug logs",
             Self::Config => "Supply a path to your configuration file to use that instead of the default",
             Self::Environment => "Specify your current environment",
+            Self::ConnectTimeout => "Maximum amount of seconds it may take to connect to the agent [default: 10]",
+            Self::RequestTimeout => "Maximum amount of seconds a single request to the agent may take [default: 60]",
+            Self::Retries => "How often a GET request is retried when the agent is unreachable or responds with a 502 or 503. Uses an exponential backoff [default: 3]",
+            Self::Proxy => "Proxy url that every request to the agent is sent through",
+            Self::UserAgent => "User agent that is sent with every request [default: siera/<version>]",
+            Self::DryRun => "Print the request as a `curl` command, with the api key and token redacted, instead of sending it. Steps that need the response of the agent are not simulated. Not available for the shell, the dashboard and webhooks",
 
             Self::Webhook => "Listen to webhook",
+            Self::Shell => "Start an interactive shell that runs every command against the same agent. Supports history and tab completion of subcommands and connection ids",
+            Self::ShellWebhooks => "Print incoming webhook events between the prompts",
+            Self::Dashboard => "Show a live overview of the connections, credential and proof exchanges and webhook events of the agent. Messages, credential offers and proof requests can be sent to the selected connection",
+            Self::DashboardInterval => "Amount of seconds between fetching the connections",
+            Self::Run => "Run the steps of a flow file against one or more environments",
+            Self::RunFlow => "Path to the YAML flow file",
+
+            Self::MockAgent => "Local stand-in for an aries-cloudagent-python agent, for trying out siera and testing without a ledger",
+            Self::MockAgentServe => "Serve an in-memory imitation of the admin api of an aries-cloudagent-python agent. Two instances can connect to each other, issue credentials and request proofs. Nothing is persisted",
+            Self::MockAgentServePort => "Port the admin api listens on. Use 0 to pick a free port",
+            Self::MockAgentServeHost => "Address the admin api listens on",
+            Self::MockAgentServeLabel => "Label of the agent, as it is shown to the agents that connect to it",
+            Self::MockAgentServeApiKey => "Api key that every request to the admin api must supply",
+            Self::MockAgentServeEndpoint => "Url on which other agents reach this agent. Defaults to the address it listens on",
+
+            Self::Api => "Send a request to any endpoint of the admin api of the agent, with the url, authentication and TLS settings of the environment",
+            Self::ApiMethod => "Http method of the request",
+            Self::ApiPath => "Path of the endpoint, e.g. /connections",
+            Self::ApiQuery => "A query parameter as key=value. Can be supplied multiple times",
+            Self::ApiBody => "File with the JSON body of the request, or - to read it from stdin",
             Self::Configuration => "Add agents to your configuration or view your current configuration. To quickly get started run the following command: siera configuration add --default",
             Self::ConfigurationAdd => "Add a new, or overwrite an existing, agent your configuration file",
             Self::ConfigurationRemove => "PERMANENTLY remove an agent from your configuration",
@@ -186,7 +296,7 @@ impl HelpStrings {
             Self::ConfigurationView => "Print your current configuration file",
             Self::ConfigurationInitializeToken => "Authentication token for a multi tenancy agent",
             Self::Oob | Self::OobConnection => "Retrieve oob connections or create oob invitations",
-            Self::OobHandshakeProtocol => "The handshake protocol to use. Defaults to did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/didexchange/1.0",
+            Self::OobHandshakeProtocol => "The handshake protocol to use. Defaults to didexchange/1.0",
             Self::OobReceive | Self::OobReceiveInvitation => "Receive an oob invitation via url",
             Self::OobCreateInvitation => "Create an oob invitation",
             Self::OobInvite => "Create an Oob connection invite",
@@ -197,6 +307,23 @@ impl HelpStrings {
                 "Automatically accept the new oob connection once they accept this invitation"
             }
             Self::OobInviteMultiUse => "This oob invitation can be used more than once",
+            Self::OobInviteAttachProofRequest => {
+                "JSON file with a name, attributes and predicates of a proof request to attach"
+            }
+            Self::OobInviteAttachCredentialOffer => {
+                "JSON file with a cred_def_id and attributes of a credential offer to attach"
+            }
+            Self::OobReceiveAlias => "A custom alias for the connection of the invitation",
+            Self::OobReceiveUseExistingConnection => {
+                "Reuse an existing connection with the inviter instead of creating a new one"
+            }
+            Self::OobConnections => {
+                "List the connections that were created with an invitation, e.g. a multi use one"
+            }
+            Self::OobConnectionsId => "Message ID of the invitation, as printed when it is created",
+            Self::OobInviteNoHandshake => {
+                "Do not establish a connection. Requires an attached proof request or credential offer"
+            }
             Self::Connections => "Retrieve connections or create invitations",
             Self::ConnectionsId => "ID of connection to retrieve",
             Self::ConnectionsInvite => "Create a new connection invitation",
@@ -220,8 +347,16 @@ impl HelpStrings {
             Self::ConnectionsListState => "Filter connections on the `state` property",
             Self::ConnectionsListTheirDid => "Filter connections on the `their_did` property",
             Self::ConnectionsListTheirRole => "Filter connections on the `their_role` property",
+            Self::ConnectionsListInvitationMsgId => {
+                "Filter connections on the message id of the invitation they were created with"
+            }
             Self::ConnectionsReceive => "Receive an invitation via url",
             Self::OobReceiveUrl | Self::ConnectionsReceiveUrl => "The url that contains the invitation, surrounded by quotes",
+            Self::ConnectionsEstablish => "Connect two environments to each other. The first creates the invitation, the second receives it, and both connection ids are printed once the connection is active on both sides",
+            Self::ConnectionsEstablishFrom => "Environment that creates the invitation",
+            Self::ConnectionsEstablishTo => "Environment that receives the invitation",
+            Self::ConnectionsEstablishOob => "Use an out of band invitation instead of a connections/1.0 invitation",
+            Self::ConnectionsEstablishTimeout => "Maximum amount of seconds to wait until the connection is active on both sides",
 
             Self::CredentialDefinition => "Retrieve or create credential definitions",
             Self::CredentialDefinitionId => "ID of a credential definition to retrieve",
@@ -235,20 +370,27 @@ impl HelpStrings {
             Self::Credentials => "Issue Credential V1",
             Self::CredentialsOffer => "Offer a new credential to an existing connection",
             Self::CredentialsOfferConnectionId => {
-                "Existing connection ID to offer the credential to"
+                "Id, alias or label of the connection to offer the credential to. Prompts for a connection when omitted on a terminal"
             }
             Self::CredentialsOfferCredentialDefinitionId => {
                 "A credential definition to base the credential on"
             }
             Self::CredentialsOfferKey => "An attribute key name",
             Self::CredentialsOfferValue => "An attribute value",
+            Self::CredentialsOfferAttribute => "An attribute as name=value. Can be supplied multiple times",
+            Self::CredentialsOfferAttributesFile => "JSON file with the attributes. Either an object with the names and values, or an array of objects with a name, a value and optionally a mime-type",
+            Self::CredentialsBulk => "Offer credentials to many connections from a CSV or JSONL file",
+            Self::CredentialsBulkFile => "CSV file with a header or JSONL file with an object per line. The `connection_id` or `alias` column selects the connection, an optional `cred_def_id` column overrides --cred-def-id and every other column is an attribute",
+            Self::CredentialsBulkConcurrency => "Maximum amount of offers that are sent at the same time",
+            Self::CredentialsBulkReport => "JSONL file to which the result of every row is written. Defaults to the file with the extension `.report.jsonl`",
+            Self::CredentialsBulkResume => "Skip the rows that already succeeded according to the report and append to it",
             Self::CredentialsPropose => "Not implemented yet: propose a credential that should be offered to you",
             Self::CredentialsProposeId => "Not implemented yet: connection ID to send proposal to",
 
             Self::Features => "List all available features",
 
             Self::Message => "Send a secure message to an existing connection",
-            Self::MessageId => "Connection ID to send the message to",
+            Self::MessageId => "Id, alias or label of the connection to send the message to. Prompts for a connection when omitted on a terminal",
             Self::MessageMessage => "Contents of the message",
 
             Self::Schema => "Retrieve or create schemas",
@@ -264,17 +406,39 @@ impl HelpStrings {
             Self::ProofRequestName => "Name of the proof request",
             Self::ProofRequestAttribute => "Attribute required in the proof request. e.g. -a=name -a=lastname",
             Self::ProofRequestPredicate => "Predicates required in the proof request (format = name,operator,value). e.g. -p=\"age,>=,18\"",
-            Self::ProofRequestConnectionId => "Connection id to send the proof request to",
+            Self::ProofRequestConnectionId => "Id, alias or label of the connection to send the proof request to. Prompts for a connection when omitted on a terminal",
+            Self::ProofList => "List all your presentation exchanges",
+            Self::ProofListId => "Get a presentation exchange by id",
+            Self::ProofListConnectionId => "Filter presentation exchanges on the id, alias or label of their connection",
+            Self::ProofListRole => "Filter presentation exchanges on your role, `verifier` or `prover`",
+            Self::ProofListState => "Filter presentation exchanges on the `state` property",
+            Self::ProofListThreadId => "Filter presentation exchanges on the `thread_id` property",
+            Self::ProofVerify => "Verify a received presentation. Exits with code 10 when it is not valid",
+            Self::ProofVerifyId => "Presentation exchange id of the presentation to verify",
 
             Self::Automation => "Run a set of actions against the agent",
             Self::AutomationCredentialOffer => "Offer a premade credential to an agent",
-            Self::AutomationCredentialOfferConnectionId => "Connection id of the receiving party",
+            Self::AutomationCredentialOfferConnectionId => "Id, alias or label of the connection of the receiving party",
             Self::AutomationCredentialOfferNoQr => "Do not show a QR code",
             Self::AutomationCredentialOfferSelf => "Offer a credential to self",
             Self::AutomationCredentialOfferTimeout=> "Timeout in seconds",
+            Self::AutomationCredentialOfferAttribute => "Attribute of the credential as name=value. Can be used multiple times. A mock credential is offered when no attributes are supplied",
+            Self::AutomationCredentialOfferAttributesFile => "JSON file with the attributes of the credential, either an object of names and values or a list of objects with a name, value and mime-type",
+            Self::AutomationCredentialOfferSchemaName => "Name of the schema of the credential",
+            Self::AutomationCredentialOfferSchemaVersion => "Version of the schema of the credential. A random version is used when a new schema is registered without one",
+            Self::AutomationCredentialOfferNewDefinition => "Register a new credential definition, even when a matching one already exists. An existing schema with the supplied version is reused",
             Self::AutomationCreateCredentialDefinitionName => "Name of the schema the credential definition will be based on",
             Self::AutomationCreateCredentialDefinitionAttributes => "Attributes of the schema the credential definition will be based on",
             Self::AutomationCreateCredentialDefinitionVersion => "Version of the schema the credential definition will be based on",
+            Self::AutomationProofRequest => "Request a proof from a new or existing connection, verify the presentation and show the revealed attributes",
+            Self::AutomationProofRequestConnectionId => "Id, alias or label of the connection to request the proof from. An invitation is created when it is omitted",
+            Self::AutomationProofRequestTimeout => "Maximum amount of seconds to wait for the connection and for the presentation",
+            Self::AutomationIssueAndVerify => "Connect two environments, issue a credential from one to the other and verify a proof of it. Prints how long every step took",
+            Self::AutomationIssueAndVerifyIssuer => "Environment that issues the credential and verifies the proof",
+            Self::AutomationIssueAndVerifyHolder => "Environment that receives the credential and presents the proof",
+            Self::AutomationIssueAndVerifyName => "Name of the schema the credential is issued with",
+            Self::AutomationIssueAndVerifyAttribute => "Attribute of the credential as name=value. Can be used multiple times",
+            Self::AutomationIssueAndVerifyTimeout => "Maximum amount of seconds every step may take",
             Self::Multitenancy => "Manage multiple agents",
             Self::MultitenancyCreate => "Create a new sub agent",
             Self::MultitenancyRemove => "Remove a sub agent",
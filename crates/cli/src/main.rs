@@ -6,6 +6,7 @@
 #[macro_use]
 extern crate siera_logger;
 
+use exit_code::ExitCode;
 use register::register;
 
 /// Module for the whole cli
@@ -14,6 +15,12 @@ mod cli;
 /// Generic error module
 mod error;
 
+/// Exit codes of the cli for every error category
+mod exit_code;
+
+/// Declarative flows of steps that are executed against one or more agents
+mod flow;
+
 /// Module for the help strings printed by the cli
 mod help_strings;
 
@@ -30,10 +37,7 @@ mod utils;
 #[tokio::main]
 async fn main() {
     match register().await {
-        Ok(_) => (),
-        Err(e) => {
-            error!({"error": e.to_string()});
-            std::process::exit(1);
-        }
+        Ok(_) => siera_logger::envelope::finish(None),
+        Err(e) => std::process::exit(ExitCode::report(e.as_ref()).into()),
     }
 }
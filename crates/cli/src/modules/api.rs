@@ -0,0 +1,75 @@
+use crate::error::{Error, Result};
+use crate::help_strings::HelpStrings;
+use crate::utils::loader::{Loader, LoaderVariant};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::Args;
+use serde_json::Value;
+use siera_agent::modules::api::{ApiModule, ApiRequestOptions};
+use siera_logger::output;
+use std::io::Read;
+
+/// Http methods that can be used for a request
+const METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// Api options and flags
+#[derive(Args)]
+#[clap(about = HelpStrings::Api)]
+pub struct ApiOptions {
+    /// Http method of the request
+    #[clap(help = HelpStrings::ApiMethod, ignore_case = true, value_parser = PossibleValuesParser::new(METHODS).map(|s| s.to_uppercase()))]
+    method: String,
+
+    /// Path of the endpoint, relative to the agent url
+    #[clap(help = HelpStrings::ApiPath)]
+    path: String,
+
+    /// Query parameters of the form `key=value`
+    #[clap(long, short, value_parser = parse_query_parameter, help = HelpStrings::ApiQuery)]
+    query: Vec<(String, String)>,
+
+    /// File with the JSON body, or `-` to read it from stdin
+    #[clap(long, short, help = HelpStrings::ApiBody)]
+    body: Option<String>,
+}
+
+/// Subcommand Api parser
+pub async fn parse_api_args(
+    options: &ApiOptions,
+    agent: &(impl ApiModule + Send + Sync + ?Sized),
+) -> Result<()> {
+    let body = options.body.as_deref().map(read_body).transpose()?;
+
+    let loader = Loader::start(&LoaderVariant::default());
+    let response = agent
+        .request(ApiRequestOptions {
+            method: options.method.clone(),
+            path: options.path.clone(),
+            query: options.query.clone(),
+            body,
+        })
+        .await;
+    loader.stop();
+
+    output("response", response?)?;
+    Ok(())
+}
+
+/// Parse a query parameter of the form `key=value`
+fn parse_query_parameter(parameter: &str) -> std::result::Result<(String, String), Error> {
+    parameter
+        .split_once('=')
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .ok_or_else(|| Error::InvalidQueryParameter(parameter.to_owned()))
+}
+
+/// Read the JSON body from a file, or from stdin for `-`
+fn read_body(source: &str) -> Result<Value> {
+    let content = if source == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        std::fs::read_to_string(source)?
+    };
+    serde_json::from_str(&content).map_err(|e| Error::InvalidApiBody(e.to_string()).into())
+}
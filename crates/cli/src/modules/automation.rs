@@ -1,19 +1,28 @@
 use crate::error::{Error, Result};
 use crate::help_strings::HelpStrings;
 use crate::modules::connection::invite_url_to_struct;
+use crate::modules::proof::parse_predicates;
+use crate::utils::attributes::{check_unique, parse_attribute, read_attributes_file};
 use crate::utils::loader::{Loader, LoaderVariant};
 use crate::utils::qr;
+use crate::utils::resolve::resolve_connection_id;
 use clap::{Args, Subcommand};
 use colored::Colorize;
+use siera_agent::agent::{Agent, Capability};
 use siera_agent::modules::connection::{ConnectionCreateInvitationOptions, ConnectionModule};
-use siera_agent::modules::credential::CredentialModule;
+use siera_agent::modules::credential::{CredentialAttribute, CredentialModule};
 use siera_agent::modules::credential_definition::CredentialDefinitionModule;
+use siera_agent::modules::proof::ProofModule;
 use siera_agent::modules::schema::SchemaModule;
 use siera_automations::automations::{
     create_credential_definition::CreateCredentialDefinition,
-    credential_offer::CredentialOfferAutomation,
+    credential_offer::CredentialOfferAutomation, issue_and_verify::IssueAndVerifyAutomation,
+    proof_request::ProofRequestAutomation,
 };
-use std::collections::HashMap;
+use siera_automations::error::Error as AutomationError;
+use siera_logger::output;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Automation options and flags
 #[derive(Args)]
@@ -45,6 +54,26 @@ pub enum AutomationSubcommands {
         /// Whether no qr code should be printed out
         #[clap(long, short, help = HelpStrings::AutomationCredentialOfferNoQr )]
         no_qr: bool,
+
+        /// Attributes of the form `name=value`
+        #[clap(long, short = 'a', value_parser = parse_attribute, help = HelpStrings::AutomationCredentialOfferAttribute)]
+        attribute: Vec<CredentialAttribute>,
+
+        /// JSON file with the attributes, optionally with their MIME types
+        #[clap(long, short = 'f', help = HelpStrings::AutomationCredentialOfferAttributesFile)]
+        attributes_file: Option<PathBuf>,
+
+        /// Name of the schema of the credential
+        #[clap(long, default_value = "full-credential-offer-automation", help = HelpStrings::AutomationCredentialOfferSchemaName)]
+        schema_name: String,
+
+        /// Version of the schema of the credential
+        #[clap(long, help = HelpStrings::AutomationCredentialOfferSchemaVersion)]
+        schema_version: Option<String>,
+
+        /// Whether a new credential definition should always be registered
+        #[clap(long, help = HelpStrings::AutomationCredentialOfferNewDefinition)]
+        new_definition: bool,
     },
 
     /// Create a credential definition subcommand
@@ -62,6 +91,62 @@ pub enum AutomationSubcommands {
         #[clap(long = "version", short = 'v', default_value="1.0", help = HelpStrings::AutomationCreateCredentialDefinitionVersion)]
         version: String,
     },
+
+    /// Proof request subcommand which requests a proof and verifies it
+    #[clap(about = HelpStrings::AutomationProofRequest)]
+    ProofRequest {
+        /// Connection id to send the proof request to
+        #[clap(long, short, help = HelpStrings::AutomationProofRequestConnectionId)]
+        connection_id: Option<String>,
+
+        /// The name of the proof request
+        #[clap(long, short, default_value = "proof-request", help = HelpStrings::ProofRequestName)]
+        name: String,
+
+        /// Attributes that have to be revealed
+        #[clap(long, short, help = HelpStrings::ProofRequestAttribute)]
+        attribute: Vec<String>,
+
+        /// Predicates that have to be satisfied, e.g. age,>=,18
+        #[clap(long, short, help = HelpStrings::ProofRequestPredicate)]
+        predicate: Vec<String>,
+
+        /// Maximum amount of time it should wait for the connection and for the presentation
+        #[clap(long, short, default_value = "60", help = HelpStrings::AutomationProofRequestTimeout)]
+        timeout: u32,
+
+        /// Whether no qr code should be printed out
+        #[clap(long = "no-qr", short = 'q', help = HelpStrings::AutomationCredentialOfferNoQr)]
+        no_qr: bool,
+    },
+
+    /// Issue a credential from one environment to another and verify it
+    #[clap(about = HelpStrings::AutomationIssueAndVerify)]
+    IssueAndVerify(IssueAndVerifyOptions),
+}
+
+/// Options and flags to issue and verify a credential between two environments
+#[derive(Args, Debug)]
+pub struct IssueAndVerifyOptions {
+    /// Environment that issues the credential and verifies the proof
+    #[clap(long, short, help = HelpStrings::AutomationIssueAndVerifyIssuer)]
+    pub issuer: String,
+
+    /// Environment that receives the credential and presents the proof
+    #[clap(long = "holder", short = 'H', help = HelpStrings::AutomationIssueAndVerifyHolder)]
+    pub holder: String,
+
+    /// Name of the schema the credential is issued with
+    #[clap(long, short, default_value = "siera-issue-and-verify", help = HelpStrings::AutomationIssueAndVerifyName)]
+    pub name: String,
+
+    /// Attributes of the credential
+    #[clap(long = "attribute", short, default_values = ["name=Alice", "age=28"], value_parser = parse_attribute, help = HelpStrings::AutomationIssueAndVerifyAttribute)]
+    pub attributes: Vec<CredentialAttribute>,
+
+    /// Maximum amount of seconds every step may take
+    #[clap(long, short, default_value = "30", help = HelpStrings::AutomationIssueAndVerifyTimeout)]
+    pub timeout: u64,
 }
 
 /// Subcommand Automation parser
@@ -69,12 +154,14 @@ pub enum AutomationSubcommands {
 /// not a single one
 pub async fn parse_automation_args(
     options: &AutomationOptions,
-    agent: impl ConnectionModule
-        + CredentialModule
-        + SchemaModule
-        + CredentialDefinitionModule
-        + Send
-        + Sync,
+    agent: &(impl ConnectionModule
+          + CredentialModule
+          + SchemaModule
+          + CredentialDefinitionModule
+          + ProofModule
+          + Send
+          + Sync
+          + ?Sized),
 ) -> Result<()> {
     let loader = Loader::start(&LoaderVariant::default());
 
@@ -84,73 +171,32 @@ pub async fn parse_automation_args(
             timeout,
             sent_to_self,
             no_qr,
-        } => match connection_id {
-            Some(c) => credential_offer(c.clone(), agent).await?,
-            None => {
-                let connection = agent
-                    .create_invitation(ConnectionCreateInvitationOptions {
-                        auto_accept: true,
-                        alias: Some(String::from("automation")),
-                        ..Default::default()
-                    })
-                    .await?;
-                if *sent_to_self {
-                    let invitation_object = invite_url_to_struct(connection.invitation_url)?;
-                    agent.receive_invitation(invitation_object).await?;
-                } else {
-                    if !no_qr {
-                        info!({ "message": "Scan the QR code to accept the invitation"});
-                        qr::print_qr_code(&connection.invitation_url)?;
-                    }
-                    println!();
-                    println!();
-                    info!({ "message": "Credential offer" });
-                    println!();
-
-                    log!({
-                        "message":
-                            format!(
-                                "{} invitation with connection id {}.",
-                                "Created".green(),
-                                connection.id.bold()
-                            )
-                    });
-                    println!();
-                    log!({"message": "Use this URL", "invitation_url": connection.invitation_url });
-                    println!();
-                    println!();
-                    info!({
-                        "message":
-                            format!(
-                                "{} for the invitation to be accepted. Timeout is {timeout} seconds...",
-                                "Waiting".cyan(),
-                            )
-                    });
-                    copy!("{}", connection.invitation_url);
-                }
-                debug!({ "message": format!("Looping {timeout} times") });
-                for i in 1..=*timeout {
-                    let connection =
-                        ConnectionModule::get_by_id(&agent, connection.id.clone()).await?;
-                    if connection.state != "active" && connection.state != "response" {
-                        trace!({ "message":
-                            "Connection state is not active, waiting 1 second then trying again..."
-                        });
-                        std::thread::sleep(std::time::Duration::from_millis(1000));
-                    } else {
-                        info!({ "message": format!("Invitation {}!", "accepted".green()) });
-                        credential_offer(connection.id, agent).await?;
-                        break;
-                    }
-                    if i == *timeout {
-                        return Err(Error::InactiveConnection.into());
-                    }
+            attribute,
+            attributes_file,
+            schema_name,
+            schema_version,
+            new_definition,
+        } => {
+            let attributes = offer_attributes(attribute, attributes_file.as_deref())?;
+            let connection_id = match connection_id {
+                Some(c) => resolve_connection_id(agent, Some(c)).await?,
+                None => {
+                    wait_for_invitation(agent, "Credential offer", *timeout, *sent_to_self, *no_qr)
+                        .await?
                 }
-                info!({ "message": "Successfully executed automation"});
-                info!({ "message": "It might take a few seconds for the credential to arrive"});
-                loader.stop();
-            }
-        },
+            };
+            let automation = CredentialOfferAutomation {
+                connection_id,
+                attributes,
+                schema_name: schema_name.clone(),
+                schema_version: schema_version.clone(),
+                new_definition: *new_definition,
+            };
+            automation.execute(agent).await?;
+            info!({ "message": "Successfully executed automation"});
+            info!({ "message": "It might take a few seconds for the credential to arrive"});
+            loader.stop();
+        }
         AutomationSubcommands::CreateCredentialDefinition {
             name,
             version,
@@ -161,41 +207,189 @@ pub async fn parse_automation_args(
                 version,
                 attributes: attributes.iter().map(std::string::String::as_str).collect(),
             };
-            automation.execute(&agent).await?;
+            automation.execute(agent).await?;
+        }
+        AutomationSubcommands::ProofRequest {
+            connection_id,
+            name,
+            attribute,
+            predicate,
+            timeout,
+            no_qr,
+        } => {
+            let predicates = parse_predicates(predicate)?;
+            let connection_id = match connection_id {
+                Some(c) => resolve_connection_id(agent, Some(c)).await?,
+                None => {
+                    wait_for_invitation(agent, "Proof request", *timeout, false, *no_qr).await?
+                }
+            };
+            let automation = ProofRequestAutomation {
+                connection_id,
+                name: name.clone(),
+                attributes: attribute.clone(),
+                predicates,
+                timeout: Duration::from_secs(u64::from(*timeout)),
+            };
+            let result = automation.execute(agent).await;
+            loader.stop();
+            let result = result?;
+
+            if result.verified {
+                log!({ "message": format!("Proof {}", "verified".green()) });
+            } else {
+                log!({ "message": format!("Proof {}", "not verified".red()) });
+            }
+            output("proof", &result)?;
+            if !result.verified {
+                return Err(
+                    AutomationError::ProofNotVerified(result.presentation_exchange_id).into(),
+                );
+            }
+        }
+        AutomationSubcommands::IssueAndVerify(_) => {
+            return Err(
+                Error::UnavailableInShell(String::from("Automation issue and verify")).into(),
+            )
         }
     };
     Ok(())
 }
 
-/// Building and offering the credential
-async fn credential_offer(
-    connection_id: String,
-    agent: impl ConnectionModule
-        + CredentialModule
-        + SchemaModule
-        + CredentialDefinitionModule
-        + Send
-        + Sync,
+/// Issue a credential between two environments and verify it. Both agents are built from the
+/// configuration
+pub async fn parse_issue_and_verify_args(
+    options: &IssueAndVerifyOptions,
+    build_agent: impl Fn(Option<&str>) -> Result<Box<dyn Agent>>,
 ) -> Result<()> {
-    // Mock credential
-    let mut attributes: HashMap<String, String> = HashMap::new();
-    attributes.insert(String::from("Name"), String::from("Joyce Brown"));
-    attributes.insert(String::from("Date Of Birth"), String::from("19890321"));
-    attributes.insert(String::from("Street"), String::from("Main Road 207"));
-    attributes.insert(String::from("City"), String::from("New York"));
-    attributes.insert(String::from("Bank"), String::from("qBank New York"));
-    attributes.insert(
-        String::from("Card Number"),
-        String::from("4537-6696-0666-0146"),
-    );
-    attributes.insert(String::from("Security Code"), String::from("063"));
-    attributes.insert(String::from("Valid Until"), String::from("20251212"));
-    debug!({ "message": "Mock credential", "attributes": attributes });
-
-    let automation = CredentialOfferAutomation {
-        connection_id,
-        attributes,
+    let issuer = build_agent(Some(&options.issuer))?;
+    let holder = build_agent(Some(&options.holder))?;
+    for agent in [&issuer, &holder] {
+        let capabilities = [
+            Capability::Connection,
+            Capability::Credential,
+            Capability::CredentialDefinition,
+            Capability::Proof,
+            Capability::Schema,
+        ];
+        if let Some(capability) = capabilities.iter().find(|c| !agent.supports(**c)) {
+            return Err(Error::SubcommandNotRegisteredForAgent(
+                String::from("Automation issue and verify"),
+                agent.to_string(),
+                capability.to_string(),
+            )
+            .into());
+        }
+    }
+
+    let loader = Loader::start(&LoaderVariant::default());
+    let automation = IssueAndVerifyAutomation {
+        schema_name: &options.name,
+        attributes: options.attributes.clone(),
+        timeout: Duration::from_secs(options.timeout),
+    };
+    let report = automation.execute(issuer.as_ref(), holder.as_ref()).await;
+    loader.stop();
+    let report = report?;
+
+    log!({ "message": format!("Credential {} and verified", "issued".green()) });
+    output("report", &report)?;
+    Ok(())
+}
+
+/// Create an invitation and wait until it is accepted. Returns the id of the connection
+async fn wait_for_invitation(
+    agent: &(impl ConnectionModule + Send + Sync + ?Sized),
+    title: &str,
+    timeout: u32,
+    sent_to_self: bool,
+    no_qr: bool,
+) -> Result<String> {
+    let connection = agent
+        .create_invitation(ConnectionCreateInvitationOptions {
+            auto_accept: true,
+            alias: Some(String::from("automation")),
+            ..Default::default()
+        })
+        .await?;
+    if sent_to_self {
+        let invitation_object = invite_url_to_struct(connection.invitation_url)?;
+        agent.receive_invitation(invitation_object).await?;
+    } else {
+        if !no_qr {
+            info!({ "message": "Scan the QR code to accept the invitation"});
+            qr::print_qr_code(&connection.invitation_url)?;
+        }
+        println!();
+        println!();
+        info!({ "message": title });
+        println!();
+
+        log!({
+            "message":
+                format!(
+                    "{} invitation with connection id {}.",
+                    "Created".green(),
+                    connection.id.bold()
+                )
+        });
+        println!();
+        log!({"message": "Use this URL", "invitation_url": connection.invitation_url });
+        println!();
+        println!();
+        info!({
+            "message":
+                format!(
+                    "{} for the invitation to be accepted. Timeout is {timeout} seconds...",
+                    "Waiting".cyan(),
+                )
+        });
+        copy!("{}", connection.invitation_url);
+    }
+    debug!({ "message": format!("Looping {timeout} times") });
+    for _ in 1..=timeout {
+        let connection = ConnectionModule::get_by_id(agent, connection.id.clone()).await?;
+        if connection.state.is_ready() {
+            info!({ "message": format!("Invitation {}!", "accepted".green()) });
+            return Ok(connection.id);
+        }
+        trace!({ "message":
+            "Connection state is not active, waiting 1 second then trying again..."
+        });
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+    }
+    Err(Error::InactiveConnection.into())
+}
+
+/// Attributes of the offer from the file and the flags, or the mock credential when none are
+/// supplied
+fn offer_attributes(
+    attribute: &[CredentialAttribute],
+    attributes_file: Option<&Path>,
+) -> Result<Vec<CredentialAttribute>> {
+    let mut attributes = match attributes_file {
+        Some(path) => read_attributes_file(path)?,
+        None => vec![],
     };
+    attributes.extend(attribute.iter().cloned());
+    check_unique(&attributes)?;
+    if attributes.is_empty() {
+        attributes = mock_attributes();
+        debug!({ "message": "Mock credential", "attributes": attributes });
+    }
+    Ok(attributes)
+}
 
-    automation.execute(agent).await
+/// Mock credential that is offered when no attributes are supplied
+fn mock_attributes() -> Vec<CredentialAttribute> {
+    vec![
+        CredentialAttribute::new("Name", "Joyce Brown"),
+        CredentialAttribute::new("Date Of Birth", "19890321"),
+        CredentialAttribute::new("Street", "Main Road 207"),
+        CredentialAttribute::new("City", "New York"),
+        CredentialAttribute::new("Bank", "qBank New York"),
+        CredentialAttribute::new("Card Number", "4537-6696-0666-0146"),
+        CredentialAttribute::new("Security Code", "063"),
+        CredentialAttribute::new("Valid Until", "20251212"),
+    ]
 }
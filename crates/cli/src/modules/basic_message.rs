@@ -1,16 +1,18 @@
 use crate::error::Result;
 use crate::help_strings::HelpStrings;
 use crate::utils::loader::{Loader, LoaderVariant};
+use crate::utils::resolve::resolve_connection_id;
 use clap::Args;
 use siera_agent::modules::basic_message::{BasicMessageModule, SendBasicMessageOptions};
+use siera_agent::modules::connection::ConnectionModule;
 
 /// Basic Message options and flags
 #[derive(Args)]
 #[clap(about = HelpStrings::Message)]
 pub struct BasicMessageOptions {
-    /// The connection id to which to send the connectoon to
+    /// The connection id, alias or label to which to send the message to
     #[clap(short = 'i', long, help=HelpStrings::MessageId)]
-    connection_id: String,
+    connection_id: Option<String>,
 
     /// The message that should be send to the connection id
     #[clap(short, long, help=HelpStrings::MessageMessage)]
@@ -20,15 +22,20 @@ pub struct BasicMessageOptions {
 /// Subcommand Basic Message parser
 pub async fn parse_basic_message_args(
     options: &BasicMessageOptions,
-    agent: impl BasicMessageModule + Send + Sync,
+    agent: &(impl BasicMessageModule + ConnectionModule + Send + Sync + ?Sized),
 ) -> Result<()> {
+    let connection_id = resolve_connection_id(agent, options.connection_id.as_deref()).await?;
     let loader = Loader::start(&LoaderVariant::default());
     let send_options = SendBasicMessageOptions {
-        connection_id: options.connection_id.clone(),
+        connection_id,
         message: options.message.clone(),
     };
-    agent.send_message(send_options).await.map(|_| {
-        loader.stop();
-        info!({ "message": "Successfully sent message"});
-    })
+    agent
+        .send_message(send_options)
+        .await
+        .map(|_| {
+            loader.stop();
+            info!({ "message": "Successfully sent message"});
+        })
+        .map_err(Into::into)
 }
@@ -106,6 +106,7 @@ pub fn parse_configuration_args(options: &ConfigurationOptions) -> Result<()> {
                 auth_token: token.clone(),
                 // TODO: this can only be aca-py or afj
                 agent: agent.clone(),
+                ..Environment::default()
             };
             log!({
                 "message":
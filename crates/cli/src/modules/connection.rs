@@ -1,15 +1,23 @@
 use crate::error::{Error, Result};
 use crate::help_strings::HelpStrings;
+use crate::modules::oob;
 use crate::utils::loader::{Loader, LoaderVariant};
 use crate::utils::qr::print_qr_code;
+use crate::utils::string_enum::string_enum_parser;
 use base64::{engine::general_purpose, Engine as _};
 use clap::{Args, Subcommand};
+use serde::Serialize;
+use siera_agent::agent::{Agent, Capability};
 use siera_agent::modules::connection::{
     ConnectionCreateInvitationOptions, ConnectionGetAllOptions, ConnectionModule,
-    ConnectionReceiveInvitationOptions,
+    ConnectionProtocol, ConnectionReceiveInvitationOptions, ConnectionRole, ConnectionState,
 };
-use siera_logger::{copy, pretty_stringify_obj};
+use siera_agent::modules::oob::{
+    OobConnectionCreateInvitationOptions, OobModule, OobReceiveOptions,
+};
+use siera_logger::{copy, output, pretty_stringify_obj};
 use std::str;
+use std::time::{Duration, Instant};
 
 /// Connection options and flags
 #[derive(Args)]
@@ -68,8 +76,8 @@ pub enum ConnectionSubcommands {
         alias: Option<String>,
 
         /// Filter on connection protocol
-        #[clap(long, short, help = HelpStrings::ConnectionsListConnectionProtocol, conflicts_with = "id")]
-        connection_protocol: Option<String>,
+        #[clap(long, short, help = HelpStrings::ConnectionsListConnectionProtocol, conflicts_with = "id", value_parser = string_enum_parser::<ConnectionProtocol>())]
+        connection_protocol: Option<ConnectionProtocol>,
 
         /// Filter on invitation key
         #[clap(long, short = 'k', help = HelpStrings::ConnectionsListInvitationKey, conflicts_with = "id")]
@@ -80,23 +88,69 @@ pub enum ConnectionSubcommands {
         my_did: Option<String>,
 
         /// Filter on the state of the connection
-        #[clap(long, short, help = HelpStrings::ConnectionsListState, conflicts_with = "id")]
-        state: Option<String>,
+        #[clap(long, short, help = HelpStrings::ConnectionsListState, conflicts_with = "id", value_parser = string_enum_parser::<ConnectionState>())]
+        state: Option<ConnectionState>,
 
         /// Filter on their did
         #[clap(long, short = 'd', help = HelpStrings::ConnectionsListTheirDid, conflicts_with = "id")]
         their_did: Option<String>,
 
         /// Filter on their role
-        #[clap(long, short = 'r', help = HelpStrings::ConnectionsListTheirRole, conflicts_with = "id")]
-        their_role: Option<String>,
+        #[clap(long, short = 'r', help = HelpStrings::ConnectionsListTheirRole, conflicts_with = "id", value_parser = string_enum_parser::<ConnectionRole>())]
+        their_role: Option<ConnectionRole>,
+
+        /// Filter on the message id of the invitation
+        #[clap(long, help = HelpStrings::ConnectionsListInvitationMsgId, conflicts_with = "id")]
+        invitation_msg_id: Option<String>,
     },
+
+    /// Connect two environments to each other
+    #[clap(about = HelpStrings::ConnectionsEstablish)]
+    Establish(ConnectionEstablishOptions),
+}
+
+/// Options and flags to connect two environments to each other
+#[derive(Args, Debug)]
+pub struct ConnectionEstablishOptions {
+    /// Environment that creates the invitation
+    #[clap(long, short, help = HelpStrings::ConnectionsEstablishFrom)]
+    pub from: String,
+
+    /// Environment that receives the invitation
+    #[clap(long, short, help = HelpStrings::ConnectionsEstablishTo)]
+    pub to: String,
+
+    /// Whether an out of band invitation should be used
+    #[clap(long, short, help = HelpStrings::ConnectionsEstablishOob)]
+    pub oob: bool,
+
+    /// Handshake protocol of the out of band invitation
+    #[clap(long, short = 'p', help = HelpStrings::OobHandshakeProtocol, default_value = "didexchange/1.0", requires = "oob", value_parser = string_enum_parser::<ConnectionProtocol>())]
+    pub handshake_protocol: ConnectionProtocol,
+
+    /// A custom alias for the connection of the inviter
+    #[clap(long, short = 'l', help = HelpStrings::ConnectionsInviteAlias)]
+    pub alias: Option<String>,
+
+    /// Maximum amount of seconds to wait until both sides are active
+    #[clap(long, default_value = "30", help = HelpStrings::ConnectionsEstablishTimeout)]
+    pub timeout: u64,
+}
+
+/// One side of an established connection
+#[derive(Debug, Serialize)]
+struct EstablishedConnection {
+    /// Environment of the agent
+    environment: String,
+
+    /// Id of the connection at the agent
+    connection_id: String,
 }
 
 /// Subcommand connection parser
 pub async fn parse_connection_args(
     options: &ConnectionOptions,
-    agent: impl ConnectionModule + Send + Sync,
+    agent: &(impl ConnectionModule + Send + Sync + ?Sized),
 ) -> Result<()> {
     let loader = Loader::start(&LoaderVariant::default());
 
@@ -115,18 +169,22 @@ pub async fn parse_connection_args(
                 qr: *qr,
                 toolbox: *toolbox,
             };
-            agent.create_invitation(options).await.map(|response| {
-                loader.stop();
-                info!({ "message": "Created invititation" });
-                log!({ "connection_id": response.id });
-                if *qr {
-                    info!({"message": "Scan this QR code to accept the invitation"});
-                    print_qr_code(&response.invitation_url).unwrap();
-                }
-                info!({ "message": "Another agent can use this URL to accept your invitation"});
-                log!({ "invitation_url": &response.invitation_url});
-                copy!("{}", response.invitation_url);
-            })
+            agent
+                .create_invitation(options)
+                .await
+                .map(|response| {
+                    loader.stop();
+                    info!({ "message": "Created invititation" });
+                    log!({ "connection_id": response.id });
+                    if *qr {
+                        info!({"message": "Scan this QR code to accept the invitation"});
+                        print_qr_code(&response.invitation_url).unwrap();
+                    }
+                    info!({ "message": "Another agent can use this URL to accept your invitation"});
+                    log!({ "invitation_url": &response.invitation_url});
+                    copy!("{}", response.invitation_url);
+                })
+                .map_err(Into::into)
         }
         ConnectionSubcommands::Receive { url } => {
             let invitation = invite_url_to_struct(url)?;
@@ -138,6 +196,7 @@ pub async fn parse_connection_args(
                     info!({"message": "Fetched connection id"});
                     log!({ "connection_id": connection.id });
                 })
+                .map_err(Into::into)
         }
         ConnectionSubcommands::List {
             id,
@@ -148,33 +207,162 @@ pub async fn parse_connection_args(
             my_did,
             state,
             their_did,
+            invitation_msg_id,
         } => match id {
-            Some(i) => agent.get_by_id(i.clone()).await.map(|connection| {
+            Some(i) => {
+                let connection = agent.get_by_id(i.clone()).await?;
                 loader.stop();
                 copy!("{}", pretty_stringify_obj(&connection));
-                log!({ "connection": connection });
-            }),
+                output("connection", &connection).map_err(Into::into)
+            }
             None => {
                 let options = ConnectionGetAllOptions {
                     alias: alias.as_deref().map(std::string::ToString::to_string),
                     their_did: their_did.as_deref().map(std::string::ToString::to_string),
-                    state: state.as_deref().map(std::string::ToString::to_string),
+                    state: state.clone(),
                     my_did: my_did.as_deref().map(std::string::ToString::to_string),
                     invitation_key: invitation_key
                         .as_deref()
                         .map(std::string::ToString::to_string),
-                    connection_protocol: connection_protocol
-                        .as_deref()
-                        .map(std::string::ToString::to_string),
-                    their_role: their_role.as_deref().map(std::string::ToString::to_string),
+                    connection_protocol: connection_protocol.clone(),
+                    their_role: their_role.clone(),
+                    invitation_msg_id: invitation_msg_id.clone(),
                 };
-                agent.get_all(options).await.map(|connections| {
-                    loader.stop();
-                    copy!("{}", pretty_stringify_obj(&connections));
-                    log!({ "connections": connections });
-                })
+                let connections = agent.get_all(options).await?;
+                loader.stop();
+                copy!("{}", pretty_stringify_obj(&connections));
+                output("connections", &connections).map_err(Into::into)
             }
         },
+        ConnectionSubcommands::Establish(_) => {
+            Err(Error::UnavailableInShell(String::from("Connection establish")).into())
+        }
+    }
+}
+
+/// Connect two environments of the configuration to each other
+///
+/// The first environment creates the invitation, the second receives it. Both agents need to
+/// accept the connection automatically, as nothing is accepted on their behalf
+pub async fn parse_connection_establish_args(
+    options: &ConnectionEstablishOptions,
+    build_agent: impl Fn(Option<&str>) -> Result<Box<dyn Agent>>,
+) -> Result<()> {
+    let from = build_agent(Some(&options.from))?;
+    let to = build_agent(Some(&options.to))?;
+    let capability = if options.oob {
+        Capability::Oob
+    } else {
+        Capability::Connection
+    };
+    for agent in [&from, &to] {
+        for capability in [capability, Capability::Connection] {
+            if !agent.supports(capability) {
+                return Err(Error::SubcommandNotRegisteredForAgent(
+                    String::from("Connection establish"),
+                    agent.to_string(),
+                    capability.to_string(),
+                )
+                .into());
+            }
+        }
+    }
+
+    let loader = Loader::start(&LoaderVariant::default());
+    let (from_id, to_id) = if options.oob {
+        let invitation = OobModule::create_invitation(
+            from.as_ref(),
+            OobConnectionCreateInvitationOptions {
+                auto_accept: true,
+                handshake_protocol: Some(options.handshake_protocol.clone()),
+                alias: options.alias.clone(),
+                ..OobConnectionCreateInvitationOptions::default()
+            },
+        )
+        .await?;
+        let invitation = oob::invite_url_to_struct(&invitation.invitation_url)?;
+        let connection =
+            OobModule::receive_invitation(to.as_ref(), invitation, OobReceiveOptions::default())
+                .await?;
+        // The from only has a connection once the invitation is received, which is looked up
+        // while waiting
+        (None, connection.connection_id)
+    } else {
+        let invitation = ConnectionModule::create_invitation(
+            from.as_ref(),
+            ConnectionCreateInvitationOptions {
+                auto_accept: true,
+                alias: options.alias.clone(),
+                ..ConnectionCreateInvitationOptions::default()
+            },
+        )
+        .await?;
+        let invitation_id = invitation.id;
+        let invitation = invite_url_to_struct(&invitation.invitation_url)?;
+        let connection = ConnectionModule::receive_invitation(to.as_ref(), invitation).await?;
+        (Some(invitation_id), connection.id)
+    };
+    info!({ "message": format!("{} received the invitation of {}", options.to, options.from) });
+
+    let from_id =
+        wait_until_active(from.as_ref(), to.as_ref(), from_id, &to_id, options.timeout).await?;
+    loader.stop();
+
+    info!({ "message": format!("Connected {} to {}", options.from, options.to) });
+    output(
+        "connections",
+        [
+            EstablishedConnection {
+                environment: options.from.clone(),
+                connection_id: from_id,
+            },
+            EstablishedConnection {
+                environment: options.to.clone(),
+                connection_id: to_id,
+            },
+        ],
+    )?;
+    Ok(())
+}
+
+/// Wait until the connection is active on both sides and return the connection id of the
+/// from
+///
+/// Without a connection id of the from, its connection is the one with the DID the to
+/// connected to
+async fn wait_until_active(
+    from: &(impl ConnectionModule + Send + Sync + ?Sized),
+    to: &(impl ConnectionModule + Send + Sync + ?Sized),
+    from_id: Option<String>,
+    to_id: &str,
+    timeout: u64,
+) -> Result<String> {
+    let deadline = Instant::now() + Duration::from_secs(timeout);
+    loop {
+        let to_connection = to.get_by_id(to_id.to_owned()).await?;
+        let from_connection = match (&from_id, &to_connection.their_did) {
+            (Some(id), _) => Some(from.get_by_id(id.clone()).await?),
+            (None, Some(their_did)) => from
+                .get_all(ConnectionGetAllOptions {
+                    my_did: Some(their_did.clone()),
+                    ..ConnectionGetAllOptions::default()
+                })
+                .await?
+                .into_iter()
+                .next(),
+            (None, None) => None,
+        };
+        if let Some(from_connection) = from_connection {
+            if from_connection.state.is_ready() && to_connection.state.is_ready() {
+                return Ok(from_connection.id);
+            }
+        }
+
+        if Instant::now() >= deadline {
+            return Err(Error::InactiveConnection.into());
+        }
+        trace!({ "message": "The connection is not active on both sides, waiting 1 second then trying again..." });
+        tokio::time::sleep(Duration::from_secs(1)).await;
     }
 }
 
@@ -1,8 +1,31 @@
 use crate::error::{Error, Result};
 use crate::help_strings::HelpStrings;
+use crate::utils::attributes::{check_unique, parse_attribute, read_attributes_file};
+use crate::utils::bulk::{read_rows, Report, ReportEntry, Row};
 use crate::utils::loader::{Loader, LoaderVariant};
+use crate::utils::resolve::{find_connection, is_uuid, resolve_connection_id};
 use clap::{Args, Subcommand};
-use siera_agent::modules::credential::{CredentialModule, CredentialOfferOptions};
+use futures_util::{stream, StreamExt};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde_json::json;
+use siera_agent::agent::Agent;
+use siera_agent::error::Error as AgentError;
+use siera_agent::modules::connection::{Connection, ConnectionGetAllOptions, ConnectionModule};
+use siera_agent::modules::credential::{
+    CredentialAttribute, CredentialModule, CredentialOfferOptions,
+};
+use siera_logger::output;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Column of a bulk file with the connection id
+const CONNECTION_ID_COLUMN: &str = "connection_id";
+
+/// Column of a bulk file with the alias of the connection, used when there is no connection id
+const ALIAS_COLUMN: &str = "alias";
+
+/// Column of a bulk file with the credential definition id, which overrides `--cred-def-id`
+const CRED_DEF_ID_COLUMN: &str = "cred_def_id";
 
 /// Credential options and flags
 #[derive(Args)]
@@ -19,9 +42,9 @@ pub enum CredentialSubcommands {
     /// Send a credential offer to the connection id
     #[clap(about = HelpStrings::CredentialsOffer)]
     Offer {
-        /// The connection id to which to send the credential to
+        /// The connection id, alias or label to which to send the credential to
         #[clap(long, short = 'i', help  = HelpStrings::CredentialsOfferConnectionId)]
-        connection_id: String,
+        connection_id: Option<String>,
 
         /// The credential definition used as a blueprint for the credential
         #[clap(long, short, help = HelpStrings::CredentialsOfferCredentialDefinitionId)]
@@ -36,38 +59,314 @@ pub enum CredentialSubcommands {
         /// these are matched with a key based on the index
         #[clap(long, short, help = HelpStrings::CredentialsOfferValue)]
         value: Vec<String>,
+
+        /// Attributes of the form `name=value`
+        #[clap(long, short = 'a', value_parser = parse_attribute, help = HelpStrings::CredentialsOfferAttribute)]
+        attribute: Vec<CredentialAttribute>,
+
+        /// JSON file with the attributes, optionally with their MIME types
+        #[clap(long, short = 'f', help = HelpStrings::CredentialsOfferAttributesFile)]
+        attributes_file: Option<PathBuf>,
+    },
+
+    /// Send credential offers for every row of a CSV or JSONL file
+    #[clap(about = HelpStrings::CredentialsBulk)]
+    Bulk {
+        /// CSV or JSONL file with a row per credential
+        #[clap(help = HelpStrings::CredentialsBulkFile)]
+        file: PathBuf,
+
+        /// The credential definition used for rows without a `cred_def_id` column
+        #[clap(long, short, help = HelpStrings::CredentialsOfferCredentialDefinitionId)]
+        cred_def_id: Option<String>,
+
+        /// Maximum amount of offers that are sent at the same time
+        #[clap(long, short = 'n', default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..), help = HelpStrings::CredentialsBulkConcurrency)]
+        concurrency: u16,
+
+        /// JSONL file to which the result of every row is written
+        #[clap(long, short, help = HelpStrings::CredentialsBulkReport)]
+        report: Option<PathBuf>,
+
+        /// Skip the rows that already succeeded according to the report
+        #[clap(long, help = HelpStrings::CredentialsBulkResume)]
+        resume: bool,
     },
 }
 
 /// Subcommand credentials parser
 pub async fn parse_credentials_args(
     commands: &CredentialSubcommands,
-    agent: impl CredentialModule + Send + Sync,
+    agent: &(impl Agent + ?Sized),
 ) -> Result<()> {
-    let loader = Loader::start(&LoaderVariant::default());
     match commands {
         CredentialSubcommands::Offer {
             connection_id,
             cred_def_id,
             key,
             value,
+            attribute,
+            attributes_file,
         } => {
             if key.len() != value.len() {
                 return Err(Error::UnequalAmountKeyValue.into());
             }
 
+            let mut attributes = match attributes_file {
+                Some(path) => read_attributes_file(path)?,
+                None => vec![],
+            };
+            attributes.extend(
+                key.iter()
+                    .zip(value)
+                    .map(|(k, v)| CredentialAttribute::new(k, v)),
+            );
+            attributes.extend(attribute.iter().cloned());
+            check_unique(&attributes)?;
+            let connection_id = resolve_connection_id(agent, connection_id.as_deref()).await?;
+
+            let loader = Loader::start(&LoaderVariant::default());
             let options = CredentialOfferOptions {
-                connection_id: connection_id.to_string(),
+                connection_id,
                 cred_def_id: cred_def_id.to_string(),
-                keys: key.iter().map(std::string::ToString::to_string).collect(),
-                values: value.iter().map(std::string::ToString::to_string).collect(),
+                attributes,
             };
-            agent.send_offer(options).await.map(|credential| {
-                loader.stop();
-                debug!({ "credential": credential });
-                info!({ "message": "Successefully offered a credential" });
-                log!({ "credential_exchange_id": credential.credential_exchange_id });
-            })
+            agent
+                .send_offer(options)
+                .await
+                .map(|credential| {
+                    loader.stop();
+                    debug!({ "credential": credential });
+                    info!({ "message": "Successefully offered a credential" });
+                    log!({ "credential_exchange_id": credential.credential_exchange_id });
+                })
+                .map_err(Into::into)
         }
+        CredentialSubcommands::Bulk {
+            file,
+            cred_def_id,
+            concurrency,
+            report,
+            resume,
+        } => {
+            let report = report
+                .clone()
+                .unwrap_or_else(|| file.with_extension("report.jsonl"));
+            offer_bulk(
+                agent,
+                file,
+                cred_def_id.as_deref(),
+                usize::from(*concurrency),
+                &report,
+                *resume,
+            )
+            .await
+        }
+    }
+}
+
+/// Send an offer for every row of the file, at most `concurrency` at the same time
+///
+/// The result of every row is appended to the report as soon as it is known. When resuming, the
+/// rows that already succeeded according to the report are skipped
+async fn offer_bulk(
+    agent: &(impl Agent + ?Sized),
+    file: &Path,
+    cred_def_id: Option<&str>,
+    concurrency: usize,
+    report_path: &Path,
+    resume: bool,
+) -> Result<()> {
+    let rows = read_rows(file)?;
+    let succeeded = if resume {
+        Report::succeeded_rows(report_path)?
+    } else {
+        BTreeSet::new()
+    };
+    let total = rows.len();
+    let pending: Vec<Row> = rows
+        .into_iter()
+        .filter(|row| !succeeded.contains(&row.number))
+        .collect();
+    let skipped = total - pending.len();
+
+    // Aliases are resolved against a single listing of the connections
+    let connections = if pending
+        .iter()
+        .any(|row| !column(row, CONNECTION_ID_COLUMN).is_some_and(is_uuid))
+    {
+        ConnectionModule::get_all(agent, ConnectionGetAllOptions::default()).await?
+    } else {
+        vec![]
+    };
+
+    // Nothing is sent, so there is nothing to report and an existing report is left untouched
+    if agent.is_dry_run() {
+        return print_bulk(agent, pending, cred_def_id, &connections).await;
+    }
+
+    let mut report = Report::open(report_path, resume)?;
+    let progress = progress_bar(pending.len());
+    let mut offers = stream::iter(pending)
+        .map(|row| offer_row(agent, row, cred_def_id, &connections))
+        .buffer_unordered(concurrency);
+
+    let (mut offered, mut failed) = (0, 0);
+    while let Some(entry) = offers.next().await {
+        progress.inc(1);
+        if let Some(error) = &entry.error {
+            failed += 1;
+            progress.suspend(|| warn!({ "message": format!("Row {} failed: {error}", entry.row) }));
+        } else {
+            offered += 1;
+        }
+        report.write(&entry)?;
+    }
+    progress.finish_and_clear();
+
+    output(
+        "bulk",
+        json!({
+            "offered": offered,
+            "failed": failed,
+            "skipped": skipped,
+            "report": report_path,
+        }),
+    )?;
+
+    if failed > 0 {
+        return Err(Error::BulkOfferFailed(failed, report_path.display().to_string()).into());
+    }
+    Ok(())
+}
+
+/// Print the offer of every row of a dry run, one after the other. Rows that could not be turned
+/// into an offer are reported and fail the command, so a file can be checked before it is sent
+async fn print_bulk(
+    agent: &(impl CredentialModule + Send + Sync + ?Sized),
+    rows: Vec<Row>,
+    cred_def_id: Option<&str>,
+    connections: &[Connection],
+) -> Result<()> {
+    let mut invalid = 0;
+    for row in rows {
+        let number = row.number;
+        match offer_options(row, cred_def_id, connections) {
+            Ok(options) => match agent.send_offer(options).await {
+                Ok(_) | Err(AgentError::DryRun(_)) => (),
+                Err(e) => return Err(e.into()),
+            },
+            Err(e) => {
+                invalid += 1;
+                warn!({ "message": format!("Row {number} is invalid: {e}") });
+            }
+        }
+    }
+
+    if invalid > 0 {
+        return Err(Error::InvalidBulkFile(format!("{invalid} row(s) are invalid")).into());
+    }
+    Ok(())
+}
+
+/// Send the offer of a single row and return its entry for the report
+async fn offer_row(
+    agent: &(impl CredentialModule + Send + Sync + ?Sized),
+    row: Row,
+    cred_def_id: Option<&str>,
+    connections: &[Connection],
+) -> ReportEntry {
+    let mut entry = ReportEntry {
+        row: row.number,
+        connection_id: None,
+        credential_exchange_id: None,
+        error: None,
+    };
+    match offer_options(row, cred_def_id, connections) {
+        Ok(options) => {
+            entry.connection_id = Some(options.connection_id.clone());
+            match agent.send_offer(options).await {
+                Ok(credential) => {
+                    entry.credential_exchange_id = Some(credential.credential_exchange_id);
+                }
+                Err(e) => entry.error = Some(e.to_string()),
+            }
+        }
+        Err(e) => entry.error = Some(e.to_string()),
+    }
+    entry
+}
+
+/// Build the offer of a row. Every column, except the reserved ones, is an attribute
+fn offer_options(
+    mut row: Row,
+    cred_def_id: Option<&str>,
+    connections: &[Connection],
+) -> Result<CredentialOfferOptions> {
+    let connection_id = match (
+        take_column(&mut row, CONNECTION_ID_COLUMN),
+        take_column(&mut row, ALIAS_COLUMN),
+    ) {
+        (Some(reference), _) | (None, Some(reference)) => find_connection(&reference, connections)?,
+        (None, None) => {
+            return Err(Error::InvalidBulkFile(format!(
+                "row {} has no {CONNECTION_ID_COLUMN} or {ALIAS_COLUMN}",
+                row.number
+            ))
+            .into())
+        }
+    };
+    let cred_def_id = take_column(&mut row, CRED_DEF_ID_COLUMN)
+        .or_else(|| cred_def_id.map(ToOwned::to_owned))
+        .ok_or_else(|| {
+            Error::InvalidBulkFile(format!(
+                "row {} has no {CRED_DEF_ID_COLUMN} and no --cred-def-id is supplied",
+                row.number
+            ))
+        })?;
+    let attributes = row
+        .columns
+        .into_iter()
+        .map(|(name, value)| CredentialAttribute::new(name, value))
+        .collect();
+
+    Ok(CredentialOfferOptions {
+        connection_id,
+        cred_def_id,
+        attributes,
+    })
+}
+
+/// Non-empty value of a column
+fn column<'a>(row: &'a Row, name: &str) -> Option<&'a str> {
+    row.columns
+        .get(name)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+}
+
+/// Remove a column from the row and return its non-empty value
+fn take_column(row: &mut Row, name: &str) -> Option<String> {
+    row.columns
+        .remove(name)
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty())
+}
+
+/// Progress bar of the offers on stderr. It is hidden when stderr is not a terminal and when
+/// the output is JSON or the cli is quiet
+fn progress_bar(length: usize) -> ProgressBar {
+    let state = siera_logger::STATE.read().unwrap();
+    let hidden = state.should_output_json || state.level == siera_logger::LogLevel::Off;
+    drop(state);
+    if hidden {
+        return ProgressBar::hidden();
+    }
+    let progress = ProgressBar::new(length as u64);
+    if let Ok(style) =
+        ProgressStyle::with_template("{bar:40} {pos}/{len} offers sent ({eta} remaining)")
+    {
+        progress.set_style(style);
     }
+    progress
 }
@@ -7,7 +7,7 @@ use siera_agent::modules::credential_definition::{
     CredentialDefinitionCreateOptions, CredentialDefinitionModule,
 };
 
-use siera_logger::pretty_stringify_obj;
+use siera_logger::{output, pretty_stringify_obj};
 
 /// Credential Definition options and flags
 #[derive(Args)]
@@ -53,7 +53,7 @@ pub enum CredentialDefinitionSubcommands {
 /// Subcommand Credential Definition parser
 pub async fn parse_credential_definition_args(
     options: &CredentialDefinitionOptions,
-    agent: impl CredentialDefinitionModule + Send + Sync,
+    agent: &(impl CredentialDefinitionModule + Send + Sync + ?Sized),
 ) -> Result<()> {
     let loader = Loader::start(&LoaderVariant::default());
 
@@ -70,15 +70,20 @@ pub async fn parse_credential_definition_args(
                 tag: tag.to_string(),
                 revocation_registry_size: *revocation_registry_size,
             };
-            agent.create(options).await.map(|cred_def| {
-                loader.stop();
-                copy!("{}", cred_def.credential_definition_id);
-                info!({ "message": "Created credential definition" });
-                log!({ "credential_definition_id": cred_def.credential_definition_id });
-            })
+            agent
+                .create(options)
+                .await
+                .map(|cred_def| {
+                    loader.stop();
+                    copy!("{}", cred_def.credential_definition_id);
+                    info!({ "message": "Created credential definition" });
+                    log!({ "credential_definition_id": cred_def.credential_definition_id });
+                })
+                .map_err(Into::into)
         }
         CredentialDefinitionSubcommands::List { id } => match id {
-            Some(i) => agent.get_by_id(i.clone()).await.map(|cred_def| {
+            Some(i) => {
+                let cred_def = agent.get_by_id(i.clone()).await?;
                 loader.stop();
                 let loggable = json!({
                     "id": cred_def.id,
@@ -89,19 +94,19 @@ pub async fn parse_credential_definition_args(
                 });
                 debug!({ "credential_definition": cred_def });
                 copy!("{}", pretty_stringify_obj(&loggable));
-                log!({ "credential_definition": loggable });
-            }),
+                output("credential_definition", &loggable).map_err(Into::into)
+            }
 
-            None => agent.get_all().await.map(|cred_defs| {
+            None => {
+                let cred_defs = agent.get_all().await?;
                 loader.stop();
-                cred_defs
-                    .credential_definition_ids
-                    .iter()
-                    .for_each(|credential_definition_id| {
-                        info!({ "credential_definition_id": credential_definition_id });
-                    });
+                output(
+                    "credential_definition_id",
+                    &cred_defs.credential_definition_ids,
+                )?;
                 info!({ "message": "Successfully fetched credential definition IDs"});
-            }),
+                Ok(())
+            }
         },
     }
 }
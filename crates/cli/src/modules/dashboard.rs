@@ -0,0 +1,632 @@
+use crate::error::{Error, Result};
+use crate::help_strings::HelpStrings;
+use crate::utils::attributes::parse_attribute;
+use crate::utils::webhook::spawn_listener;
+use clap::Args;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Row, Table, TableState};
+use ratatui::{Frame, Terminal};
+use serde_json::Value;
+use siera_agent::agent::{Agent, Capability};
+use siera_agent::modules::basic_message::SendBasicMessageOptions;
+use siera_agent::modules::connection::{Connection, ConnectionGetAllOptions, ConnectionModule};
+use siera_agent::modules::credential::{
+    CredentialExchangeState, CredentialGetAllOptions, CredentialModule, CredentialOfferOptions,
+};
+use siera_agent::modules::proof::{
+    PresentationExchangeState, ProofGetAllOptions, ProofModule, ProofRequestOptions,
+};
+use siera_logger::LogLevel;
+use std::collections::VecDeque;
+use std::io::{stdout, Stdout};
+use std::panic::PanicHookInfo;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// Dashboard options and flags
+#[derive(Args)]
+#[clap(about = HelpStrings::Dashboard)]
+pub struct DashboardOptions {
+    /// Amount of seconds between fetching the connections
+    #[clap(long, short, default_value = "5", help = HelpStrings::DashboardInterval)]
+    interval: u64,
+}
+
+/// Maximum amount of webhook events that are kept for the event pane
+const MAX_EVENTS: usize = 200;
+
+/// How long to wait for a key press before the dashboard is redrawn
+const TICK: Duration = Duration::from_millis(250);
+
+/// Action on the selected connection that requires input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    /// Send a basic message
+    Message,
+
+    /// Offer a credential
+    Offer,
+
+    /// Request a proof
+    Proof,
+}
+
+impl Action {
+    /// Prompt that is shown while the input is entered
+    const fn prompt(self) -> &'static str {
+        match self {
+            Self::Message => "Message",
+            Self::Offer => "Offer (<credential definition id> <key>=<value> ...)",
+            Self::Proof => "Request proof of (<attribute> ...)",
+        }
+    }
+
+    /// The module the agent needs for the action
+    const fn capability(self) -> Capability {
+        match self {
+            Self::Message => Capability::BasicMessage,
+            Self::Offer => Capability::Credential,
+            Self::Proof => Capability::Proof,
+        }
+    }
+}
+
+/// A credential or proof exchange that is in flight
+struct Exchange {
+    /// `credential` or `proof`
+    kind: &'static str,
+
+    /// Id of the exchange
+    id: String,
+
+    /// Connection the exchange is with
+    connection_id: String,
+
+    /// Latest known state
+    state: String,
+}
+
+impl Exchange {
+    /// The exchange that a webhook event is about. Supports the topics of aca-py
+    fn from_event(event: &Value) -> Option<Self> {
+        let payload = event.get("payload")?;
+        let (kind, id_field) = match event.get("topic")?.as_str()? {
+            "issue_credential" => ("credential", "credential_exchange_id"),
+            "issue_credential_v2_0" => ("credential", "cred_ex_id"),
+            "present_proof" => ("proof", "presentation_exchange_id"),
+            "present_proof_v2_0" => ("proof", "pres_ex_id"),
+            _ => return None,
+        };
+        let field = |name: &str| {
+            payload
+                .get(name)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_owned()
+        };
+        Some(Self {
+            kind,
+            id: field(id_field),
+            connection_id: field("connection_id"),
+            state: field("state"),
+        })
+    }
+}
+
+/// State of the dashboard
+#[derive(Default)]
+struct Dashboard {
+    /// Connections of the agent
+    connections: Vec<Connection>,
+
+    /// The selected connection
+    selected: TableState,
+
+    /// Credential and proof exchanges, the most recently updated first
+    exchanges: Vec<Exchange>,
+
+    /// Lines of the event pane, the newest last
+    events: VecDeque<String>,
+
+    /// The action and the input that is being entered
+    input: Option<(Action, String)>,
+
+    /// Result of the last action
+    status: String,
+
+    /// Whether the connections should be fetched before the next draw
+    stale: bool,
+
+    /// Whether the dashboard should be closed
+    quit: bool,
+}
+
+impl Dashboard {
+    /// The selected connection
+    fn selected_connection(&self) -> Option<&Connection> {
+        self.selected
+            .selected()
+            .and_then(|i| self.connections.get(i))
+    }
+
+    /// Add a line to the event pane
+    fn push_event(&mut self, line: impl Into<String>) {
+        self.events.push_back(line.into());
+        while self.events.len() > MAX_EVENTS {
+            self.events.pop_front();
+        }
+    }
+
+    /// Add an exchange or update the state of a known one
+    fn upsert_exchange(&mut self, exchange: Exchange) {
+        self.exchanges.retain(|e| e.id != exchange.id);
+        self.exchanges.insert(0, exchange);
+    }
+
+    /// Move the selection by `offset` connections
+    fn move_selection(&mut self, offset: isize) {
+        if self.connections.is_empty() {
+            self.selected.select(None);
+            return;
+        }
+        let last = self.connections.len() - 1;
+        let current = self.selected.selected().unwrap_or_default();
+        let next = current.saturating_add_signed(offset).min(last);
+        self.selected.select(Some(next));
+    }
+
+    /// Process a webhook event
+    fn on_event(&mut self, event: &Value) {
+        let topic = event
+            .get("topic")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown");
+        let state = event
+            .pointer("/payload/state")
+            .and_then(Value::as_str)
+            .map(|state| format!(" -> {state}"))
+            .unwrap_or_default();
+        self.push_event(format!("{topic}{state}"));
+
+        if topic == "connections" {
+            self.stale = true;
+        }
+        if let Some(exchange) = Exchange::from_event(event) {
+            self.upsert_exchange(exchange);
+        }
+    }
+}
+
+/// Subcommand Dashboard parser
+///
+/// Shows the connections, the in-flight exchanges and the webhook events of the agent until `q`
+/// is pressed
+pub async fn parse_dashboard_args(options: &DashboardOptions, agent: Arc<dyn Agent>) -> Result<()> {
+    let mut dashboard = Dashboard {
+        stale: true,
+        ..Dashboard::default()
+    };
+
+    let (events, listener) = if agent.supports(Capability::Webhook) {
+        let (events, listener) = spawn_listener(&agent);
+        (Some(events), Some(listener))
+    } else {
+        dashboard.push_event(format!("Agent {agent} does not support webhooks"));
+        (None, None)
+    };
+
+    load_exchanges(&mut dashboard, agent.as_ref()).await;
+
+    // Logs would be drawn over the dashboard
+    let level = std::mem::replace(
+        &mut siera_logger::STATE.write().unwrap().level,
+        LogLevel::Off,
+    );
+
+    // A panic would otherwise leave the terminal in raw mode and on the alternate screen
+    let previous_hook: Arc<dyn Fn(&PanicHookInfo<'_>) + Sync + Send> =
+        Arc::from(std::panic::take_hook());
+    let hook = Arc::clone(&previous_hook);
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(stdout(), LeaveAlternateScreen);
+        hook(info);
+    }));
+
+    enable_raw_mode()?;
+    execute!(stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let result = run(
+        &mut terminal,
+        &mut dashboard,
+        agent.as_ref(),
+        Duration::from_secs(options.interval),
+        events,
+        listener,
+    )
+    .await;
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    std::panic::set_hook(Box::new(move |info| previous_hook(info)));
+    siera_logger::STATE.write().unwrap().level = level;
+
+    result
+}
+
+/// Draw the dashboard and handle input, events and refreshes until it is closed
+async fn run(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    dashboard: &mut Dashboard,
+    agent: &dyn Agent,
+    interval: Duration,
+    events: Option<Receiver<Value>>,
+    mut listener: Option<JoinHandle<siera_agent::error::Result<()>>>,
+) -> Result<()> {
+    let mut last_refresh = Instant::now();
+    while !dashboard.quit {
+        if dashboard.stale || last_refresh.elapsed() >= interval {
+            refresh(dashboard, agent).await;
+            last_refresh = Instant::now();
+        }
+
+        if let Some(events) = &events {
+            for event in events.try_iter() {
+                dashboard.on_event(&event);
+            }
+        }
+        if listener.as_ref().is_some_and(JoinHandle::is_finished) {
+            if let Some(Ok(Err(e))) = match listener.take() {
+                Some(finished) => Some(finished.await),
+                None => None,
+            } {
+                dashboard.push_event(format!("Stopped listening for webhooks: {}", e.message()));
+            }
+        }
+
+        terminal.draw(|frame| draw(frame, dashboard))?;
+
+        if tokio::task::block_in_place(|| event::poll(TICK))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    on_key(dashboard, agent, key).await;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fetch the connections of the agent
+async fn refresh(dashboard: &mut Dashboard, agent: &dyn Agent) {
+    dashboard.stale = false;
+    match ConnectionModule::get_all(agent, ConnectionGetAllOptions::default()).await {
+        Ok(connections) => {
+            let selected = dashboard.selected_connection().map(|c| c.id.clone());
+            dashboard.connections = connections;
+            let index = selected
+                .and_then(|id| dashboard.connections.iter().position(|c| c.id == id))
+                .or(if dashboard.connections.is_empty() {
+                    None
+                } else {
+                    Some(0)
+                });
+            dashboard.selected.select(index);
+        }
+        Err(e) => dashboard.status = format!("Could not fetch the connections: {}", e.message()),
+    }
+}
+
+/// Fetch the credential and proof exchanges that are still in flight, so exchanges that started
+/// before the dashboard are shown. Later updates arrive as webhook events
+async fn load_exchanges(dashboard: &mut Dashboard, agent: &dyn Agent) {
+    let mut exchanges = vec![];
+
+    if agent.supports(Capability::Credential) {
+        match CredentialModule::get_all(agent, CredentialGetAllOptions::default()).await {
+            Ok(credentials) => exchanges.extend(
+                credentials
+                    .into_iter()
+                    .filter(|c| {
+                        !matches!(
+                            c.state,
+                            CredentialExchangeState::CredentialAcked
+                                | CredentialExchangeState::CredentialRevoked
+                                | CredentialExchangeState::Done
+                                | CredentialExchangeState::Abandoned
+                        )
+                    })
+                    .map(|c| {
+                        let exchange = Exchange {
+                            kind: "credential",
+                            id: c.credential_exchange_id,
+                            connection_id: c.connection_id,
+                            state: c.state.to_string(),
+                        };
+                        (c.updated_at, exchange)
+                    }),
+            ),
+            Err(e) => dashboard.push_event(format!(
+                "Could not fetch the credential exchanges: {}",
+                e.message()
+            )),
+        }
+    }
+
+    if agent.supports(Capability::Proof) {
+        match ProofModule::get_all(agent, ProofGetAllOptions::default()).await {
+            Ok(proofs) => exchanges.extend(
+                proofs
+                    .into_iter()
+                    .filter(|p| {
+                        !matches!(
+                            p.state,
+                            PresentationExchangeState::Verified
+                                | PresentationExchangeState::PresentationAcked
+                                | PresentationExchangeState::Done
+                                | PresentationExchangeState::Abandoned
+                        )
+                    })
+                    .map(|p| {
+                        let exchange = Exchange {
+                            kind: "proof",
+                            id: p.presentation_exchange_id,
+                            connection_id: p.connection_id,
+                            state: p.state.to_string(),
+                        };
+                        (p.updated_at, exchange)
+                    }),
+            ),
+            Err(e) => dashboard.push_event(format!(
+                "Could not fetch the proof exchanges: {}",
+                e.message()
+            )),
+        }
+    }
+
+    // Every exchange is inserted at the top, so the most recently updated one ends up first
+    exchanges.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (_, exchange) in exchanges {
+        dashboard.upsert_exchange(exchange);
+    }
+}
+
+/// Handle a key press
+async fn on_key(dashboard: &mut Dashboard, agent: &dyn Agent, key: KeyEvent) {
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+        dashboard.quit = true;
+        return;
+    }
+
+    match (dashboard.input.take(), key.code) {
+        (Some(_), KeyCode::Esc) => dashboard.status = String::from("Cancelled"),
+        (Some((action, text)), KeyCode::Enter) => {
+            dashboard.status = match execute_action(dashboard, agent, action, &text).await {
+                Ok(status) => status,
+                Err(e) => e.to_string(),
+            };
+        }
+        (Some((action, mut text)), KeyCode::Backspace) => {
+            text.pop();
+            dashboard.input = Some((action, text));
+        }
+        (Some((action, mut text)), KeyCode::Char(c)) => {
+            text.push(c);
+            dashboard.input = Some((action, text));
+        }
+        (Some(input), _) => dashboard.input = Some(input),
+        (None, KeyCode::Char('q') | KeyCode::Esc) => dashboard.quit = true,
+        (None, KeyCode::Up | KeyCode::Char('k')) => dashboard.move_selection(-1),
+        (None, KeyCode::Down | KeyCode::Char('j')) => dashboard.move_selection(1),
+        (None, KeyCode::Char('r')) => dashboard.stale = true,
+        (None, KeyCode::Char(c @ ('m' | 'o' | 'p'))) => {
+            let action = match c {
+                'm' => Action::Message,
+                'o' => Action::Offer,
+                _ => Action::Proof,
+            };
+            if dashboard.selected_connection().is_none() {
+                dashboard.status = String::from("Select a connection first");
+            } else if !agent.supports(action.capability()) {
+                dashboard.status = format!(
+                    "Agent {agent} does not support the {} module",
+                    action.capability()
+                );
+            } else {
+                dashboard.input = Some((action, String::new()));
+            }
+        }
+        (None, _) => (),
+    }
+}
+
+/// Execute an action on the selected connection and describe the result
+async fn execute_action(
+    dashboard: &mut Dashboard,
+    agent: &dyn Agent,
+    action: Action,
+    text: &str,
+) -> Result<String> {
+    let Some(connection_id) = dashboard.selected_connection().map(|c| c.id.clone()) else {
+        return Ok(String::from("Select a connection first"));
+    };
+
+    match action {
+        Action::Message => {
+            agent
+                .send_message(SendBasicMessageOptions {
+                    connection_id,
+                    message: text.to_owned(),
+                })
+                .await?;
+            Ok(String::from("Sent message"))
+        }
+        Action::Offer => {
+            let mut words = shell_words::split(text)?.into_iter();
+            let Some(cred_def_id) = words.next() else {
+                return Ok(String::from("Supply a credential definition id"));
+            };
+            let attributes = words
+                .map(|pair| parse_attribute(&pair))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            let response = agent
+                .send_offer(CredentialOfferOptions {
+                    connection_id,
+                    cred_def_id,
+                    attributes,
+                })
+                .await?;
+            let status = format!("Offered credential {}", response.credential_exchange_id);
+            dashboard.upsert_exchange(Exchange {
+                kind: "credential",
+                id: response.credential_exchange_id,
+                connection_id: response.connection_id,
+                state: response.state.to_string(),
+            });
+            Ok(status)
+        }
+        Action::Proof => {
+            let attributes = shell_words::split(text)?;
+            if attributes.is_empty() {
+                return Err(Error::RequiredAttributes.into());
+            }
+            let response = agent
+                .send_request(ProofRequestOptions {
+                    connection_id,
+                    name: String::from("proof-request"),
+                    attributes,
+                    predicates: vec![],
+                })
+                .await?;
+            let status = format!("Requested proof {}", response.presentation_exchange_id);
+            dashboard.upsert_exchange(Exchange {
+                kind: "proof",
+                id: response.presentation_exchange_id,
+                connection_id: response.connection_id,
+                state: response.state.to_string(),
+            });
+            Ok(status)
+        }
+    }
+}
+
+/// Draw the dashboard
+fn draw(frame: &mut Frame<'_>, dashboard: &mut Dashboard) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(6),
+            Constraint::Length(10),
+            Constraint::Length(2),
+        ])
+        .split(frame.size());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[0]);
+
+    draw_connections(frame, dashboard, columns[0]);
+    draw_exchanges(frame, dashboard, columns[1]);
+    draw_events(frame, dashboard, rows[1]);
+    draw_footer(frame, dashboard, rows[2]);
+}
+
+/// Style of the header row of a table
+fn header_style() -> Style {
+    Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD)
+}
+
+/// Draw the connections with the selected one highlighted
+fn draw_connections(frame: &mut Frame<'_>, dashboard: &mut Dashboard, area: Rect) {
+    let rows = dashboard.connections.iter().map(|c| {
+        Row::new(vec![
+            c.alias
+                .clone()
+                .or_else(|| c.their_label.clone())
+                .unwrap_or_default(),
+            c.id.clone(),
+            c.state.to_string(),
+            c.role.to_string(),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(45),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+        ],
+    )
+    .header(Row::new(vec!["ALIAS", "ID", "STATE", "ROLE"]).style(header_style()))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Connections "),
+    )
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, area, &mut dashboard.selected);
+}
+
+/// Draw the credential and proof exchanges
+fn draw_exchanges(frame: &mut Frame<'_>, dashboard: &Dashboard, area: Rect) {
+    let rows = dashboard.exchanges.iter().map(|e| {
+        Row::new(vec![
+            e.kind.to_owned(),
+            e.id.clone(),
+            e.connection_id.clone(),
+            e.state.clone(),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+            Constraint::Percentage(25),
+        ],
+    )
+    .header(Row::new(vec!["TYPE", "ID", "CONNECTION", "STATE"]).style(header_style()))
+    .block(Block::default().borders(Borders::ALL).title(" Exchanges "));
+    frame.render_widget(table, area);
+}
+
+/// Draw the newest webhook events that fit
+fn draw_events(frame: &mut Frame<'_>, dashboard: &Dashboard, area: Rect) {
+    let height = usize::from(area.height.saturating_sub(2));
+    let skip = dashboard.events.len().saturating_sub(height);
+    let items: Vec<ListItem<'_>> = dashboard
+        .events
+        .iter()
+        .skip(skip)
+        .map(|line| ListItem::new(line.as_str()))
+        .collect();
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(" Events "));
+    frame.render_widget(list, area);
+}
+
+/// Draw the input that is being entered or the key bindings, and the result of the last action
+fn draw_footer(frame: &mut Frame<'_>, dashboard: &Dashboard, area: Rect) {
+    let first = match &dashboard.input {
+        Some((action, text)) => format!("{}: {text}_", action.prompt()),
+        None => String::from(
+            "↑/↓ select  m message  o offer credential  p request proof  r refresh  q quit",
+        ),
+    };
+    let footer = Paragraph::new(format!("{first}\n{}", dashboard.status));
+    frame.render_widget(footer, area);
+}
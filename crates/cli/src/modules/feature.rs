@@ -10,13 +10,19 @@ use siera_agent::modules::feature::FeatureModule;
 pub struct FeaturesOptions {}
 
 /// Subcommand Feature parser
-pub async fn parse_features_args(agent: impl FeatureModule + Send + Sync) -> Result<()> {
+pub async fn parse_features_args(
+    agent: &(impl FeatureModule + Send + Sync + ?Sized),
+) -> Result<()> {
     let loader = Loader::start(&LoaderVariant::default());
-    agent.discover_features().await.map(|features| {
-        loader.stop();
-        debug!({ "features": features });
-        log!({
-            "protocols": features.disclose.protocols
-        });
-    })
+    agent
+        .discover_features()
+        .await
+        .map(|features| {
+            loader.stop();
+            debug!({ "features": features });
+            log!({
+                "protocols": features.disclose.protocols
+            });
+        })
+        .map_err(Into::into)
 }
@@ -0,0 +1,64 @@
+use crate::error::Result;
+use crate::help_strings::HelpStrings;
+use clap::{Args, Subcommand};
+use siera_mock_agent::server::{MockAgentServer, MockAgentServerOptions};
+use std::net::{IpAddr, SocketAddr};
+
+/// Mock agent options and flags
+#[derive(Args)]
+pub struct MockAgentOptions {
+    /// All the subcommands of the mock agent cli
+    #[clap(subcommand)]
+    pub commands: MockAgentSubcommands,
+}
+
+/// Mock agent subcommands
+#[derive(Subcommand, Debug)]
+#[clap(about = HelpStrings::MockAgent)]
+pub enum MockAgentSubcommands {
+    /// Serve the admin api of the mock agent
+    #[clap(about = HelpStrings::MockAgentServe)]
+    Serve {
+        /// Port the admin api listens on
+        #[clap(long, short, default_value = "8020", help = HelpStrings::MockAgentServePort)]
+        port: u16,
+
+        /// Address the admin api listens on
+        #[clap(long, default_value = "127.0.0.1", help = HelpStrings::MockAgentServeHost)]
+        host: IpAddr,
+
+        /// Label of the agent
+        #[clap(long, short, default_value = "Mock agent", help = HelpStrings::MockAgentServeLabel)]
+        label: String,
+
+        /// Api key that is required for the admin api
+        #[clap(long, short = 'k', help = HelpStrings::MockAgentServeApiKey)]
+        api_key: Option<String>,
+
+        /// Url on which other agents reach the agent
+        #[clap(long, short, help = HelpStrings::MockAgentServeEndpoint)]
+        endpoint: Option<String>,
+    },
+}
+
+/// Subcommand Mock agent parser
+pub async fn parse_mock_agent_args(options: &MockAgentOptions) -> Result<()> {
+    match &options.commands {
+        MockAgentSubcommands::Serve {
+            port,
+            host,
+            label,
+            api_key,
+            endpoint,
+        } => {
+            let server = MockAgentServer::bind(MockAgentServerOptions {
+                address: SocketAddr::new(*host, *port),
+                label: label.clone(),
+                api_key: api_key.clone(),
+                endpoint: endpoint.clone(),
+            })?;
+            log!({ "message": format!("Serving the mock agent on {}. Stop it with ctrl-c", server.endpoint()) });
+            server.run().await
+        }
+    }
+}
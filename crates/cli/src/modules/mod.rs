@@ -1,3 +1,6 @@
+/// Module for raw requests to the agent
+pub mod api;
+
 /// Module for agent automations
 pub mod automation;
 
@@ -19,15 +22,27 @@ pub mod credential;
 /// Module for credential definitions
 pub mod credential_definition;
 
+/// Module for the terminal dashboard
+pub mod dashboard;
+
+/// Module for the local mock agent
+pub mod mock_agent;
+
 /// Module for features
 pub mod feature;
 
 /// Module for proofs
 pub mod proof;
 
+/// Module for running flows
+pub mod run;
+
 /// Module for schemas
 pub mod schema;
 
+/// Module for the interactive shell
+pub mod shell;
+
 /// Module for multitenancy
 pub mod multitenancy;
 
@@ -32,16 +32,20 @@ pub enum MultitenancySubcommands {
 /// Subcommand multitenancy parser
 pub async fn parse_multitenancy_args(
     options: &MultitenancyOptions,
-    agent: impl MultitenancyModule + Send + Sync,
+    agent: &(impl MultitenancyModule + Send + Sync + ?Sized),
 ) -> Result<()> {
     let loader = Loader::start(&LoaderVariant::default());
 
     match &options.commands {
-        MultitenancySubcommands::Create {} => agent.create().await.map(|response| {
-            loader.stop();
-            log!({ "response": response });
-            copy!("{}", response.wallet_id);
-        }),
+        MultitenancySubcommands::Create {} => agent
+            .create()
+            .await
+            .map(|response| {
+                loader.stop();
+                log!({ "response": response });
+                copy!("{}", response.wallet_id);
+            })
+            .map_err(Into::into),
         MultitenancySubcommands::Remove { wallet_id } => {
             agent.remove(wallet_id.clone()).await?;
             loader.stop();
@@ -1,13 +1,25 @@
 use crate::error::{Error, Result};
 use crate::help_strings::HelpStrings;
+use crate::modules::proof::parse_predicates;
+use crate::utils::attributes::{check_unique, AttributesFile};
 use crate::utils::loader::{Loader, LoaderVariant};
 use crate::utils::qr::print_qr_code;
+use crate::utils::string_enum::string_enum_parser;
 use base64::{engine::general_purpose, Engine as _};
 use clap::{Args, Subcommand};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use siera_agent::modules::connection::{
+    Connection, ConnectionGetAllOptions, ConnectionModule, ConnectionProtocol,
+};
+use siera_agent::modules::credential::{CredentialCreateOfferOptions, CredentialModule};
 use siera_agent::modules::oob::{
-    OobConnectionCreateInvitationOptions, OobConnectionReceiveInvitationOptions, OobModule,
+    OobAttachment, OobConnectionCreateInvitationOptions, OobConnectionReceiveInvitationOptions,
+    OobModule, OobReceiveOptions,
 };
-use siera_logger::copy;
+use siera_agent::modules::proof::{ProofCreateRequestOptions, ProofModule};
+use siera_logger::{copy, output, pretty_stringify_obj};
+use std::path::{Path, PathBuf};
 use std::str;
 
 /// Oob options and flags
@@ -38,12 +50,24 @@ pub enum OobSubcommands {
         multi_use: bool,
 
         /// A custom alias for that specific Oob
-        #[clap(long, short = 'p', help = HelpStrings::OobHandshakeProtocol, default_value="did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/didexchange/1.0")]
-        handshake_protocol: String,
+        #[clap(long, short = 'p', help = HelpStrings::OobHandshakeProtocol, conflicts_with = "no_handshake", value_parser = string_enum_parser::<ConnectionProtocol>())]
+        handshake_protocol: Option<ConnectionProtocol>,
 
         /// A custom alias for that specific Oob
         #[clap(long, short = 'l', help = HelpStrings::OobInviteAlias)]
         alias: Option<String>,
+
+        /// JSON file with a proof request that is attached to the invitation
+        #[clap(long, help = HelpStrings::OobInviteAttachProofRequest)]
+        attach_proof_request: Option<PathBuf>,
+
+        /// JSON file with a credential offer that is attached to the invitation
+        #[clap(long, help = HelpStrings::OobInviteAttachCredentialOffer)]
+        attach_credential_offer: Option<PathBuf>,
+
+        /// Whether the invitation should not establish a connection
+        #[clap(long, help = HelpStrings::OobInviteNoHandshake)]
+        no_handshake: bool,
     },
 
     /// Receive an invitation via url
@@ -52,13 +76,62 @@ pub enum OobSubcommands {
         /// Invitation url
         #[clap(long, short, help = HelpStrings::OobReceiveUrl)]
         url: String,
+
+        /// A custom alias for the connection
+        #[clap(long, short = 'l', help = HelpStrings::OobReceiveAlias)]
+        alias: Option<String>,
+
+        /// Whether an existing connection with the inviter should be reused
+        #[clap(long, help = HelpStrings::OobReceiveUseExistingConnection)]
+        use_existing_connection: bool,
+    },
+
+    /// List the connections of an invitation
+    #[clap(about = HelpStrings::OobConnections)]
+    Connections {
+        /// Message id of the invitation
+        #[clap(long, short, help = HelpStrings::OobConnectionsId)]
+        id: String,
     },
 }
 
+/// Proof request file that is attached to an invitation
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProofRequestFile {
+    /// Name of the proof request
+    #[serde(default = "default_proof_request_name")]
+    name: String,
+
+    /// Attributes that have to be revealed
+    #[serde(default)]
+    attributes: Vec<String>,
+
+    /// Predicates of the form `name,operator,value`
+    #[serde(default)]
+    predicates: Vec<String>,
+}
+
+/// Name of a proof request file without one
+fn default_proof_request_name() -> String {
+    String::from("proof-request")
+}
+
+/// Credential offer file that is attached to an invitation
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CredentialOfferFile {
+    /// Credential definition of the credential
+    cred_def_id: String,
+
+    /// Attributes of the credential, in any form of an attributes file
+    attributes: AttributesFile,
+}
+
 /// Subcommand Oob parser
 pub async fn parse_oob_args(
     options: &OobOptions,
-    agent: impl OobModule + Send + Sync,
+    agent: &(impl OobModule + ConnectionModule + CredentialModule + ProofModule + Send + Sync + ?Sized),
 ) -> Result<()> {
     let loader = Loader::start(&LoaderVariant::default());
 
@@ -69,18 +142,37 @@ pub async fn parse_oob_args(
             multi_use,
             handshake_protocol,
             alias,
+            attach_proof_request,
+            attach_credential_offer,
+            no_handshake,
         } => {
+            let attachments = create_attachments(
+                agent,
+                attach_proof_request.as_deref(),
+                attach_credential_offer.as_deref(),
+            )
+            .await?;
+            // Nothing is created when there are no attachment files
+            if *no_handshake && attachments.is_empty() {
+                return Err(Error::AttachmentRequired.into());
+            }
+
             let options = OobConnectionCreateInvitationOptions {
                 alias: alias.as_deref().map(std::borrow::ToOwned::to_owned),
-                handshake_protocol: handshake_protocol.clone(),
+                handshake_protocol: (!no_handshake)
+                    .then(|| handshake_protocol.clone().unwrap_or_default()),
                 auto_accept: *auto_accept,
                 multi_use: *multi_use,
                 qr: *qr,
+                attachments,
             };
-            agent.create_invitation(options).await.map(|response| {
+            OobModule::create_invitation(agent, options).await.map(|response| {
                 loader.stop();
                 info!({ "message": "Created invitation" });
                 info!({ "message_id": response.invitation_message_id });
+                if let Some(oob_id) = &response.oob_id {
+                    info!({ "oob_id": oob_id });
+                }
                 if *qr {
                     info!({ "message": "Scan this QR code to accept the invitation"});
                     print_qr_code(&response.invitation_url).unwrap();
@@ -89,22 +181,101 @@ pub async fn parse_oob_args(
                     info!({ "invitation_url": &response.invitation_url });
                 }
                 copy!("{}", response.invitation_url);
-            })
+            }).map_err(Into::into)
         }
-        OobSubcommands::Receive { url } => {
+        OobSubcommands::Receive {
+            url,
+            alias,
+            use_existing_connection,
+        } => {
             let invitation = invite_url_to_struct(url)?;
-            agent
-                .receive_invitation(invitation)
+            let options = OobReceiveOptions {
+                alias: alias.clone(),
+                use_existing_connection: *use_existing_connection,
+            };
+            OobModule::receive_invitation(agent, invitation, options)
                 .await
                 .map(|connection| {
                     debug!({ "connection": connection });
                     info!({ "message": "Fetched connection id" });
                     info!({ "connection_id": connection.connection_id });
                 })
+                .map_err(Into::into)
+        }
+        OobSubcommands::Connections { id } => {
+            let connections = invitation_connections(agent, id.clone()).await?;
+            loader.stop();
+            copy!("{}", pretty_stringify_obj(&connections));
+            output("connections", &connections)?;
+            Ok(())
         }
     }
 }
 
+/// Every connection that was created with the invitation of the message id, which is more than
+/// one for a multi use invitation
+async fn invitation_connections(
+    agent: &(impl ConnectionModule + Send + Sync + ?Sized),
+    message_id: String,
+) -> Result<Vec<Connection>> {
+    let connections = ConnectionModule::get_all(
+        agent,
+        ConnectionGetAllOptions {
+            invitation_msg_id: Some(message_id),
+            ..ConnectionGetAllOptions::default()
+        },
+    )
+    .await?;
+    Ok(connections)
+}
+
+/// Create the exchanges of the attachment files, so they can be attached to an invitation
+async fn create_attachments(
+    agent: &(impl CredentialModule + ProofModule + Send + Sync + ?Sized),
+    attach_proof_request: Option<&Path>,
+    attach_credential_offer: Option<&Path>,
+) -> Result<Vec<OobAttachment>> {
+    let mut attachments = vec![];
+    if let Some(path) = attach_proof_request {
+        let file: ProofRequestFile = read_attachment_file(path)?;
+        let predicates = parse_predicates(&file.predicates)?;
+        let exchange = agent
+            .create_request(ProofCreateRequestOptions {
+                name: file.name,
+                attributes: file.attributes,
+                predicates,
+            })
+            .await?;
+        info!({ "presentation_exchange_id": exchange.presentation_exchange_id });
+        attachments.push(OobAttachment::PresentationRequest(
+            exchange.presentation_exchange_id,
+        ));
+    }
+    if let Some(path) = attach_credential_offer {
+        let file: CredentialOfferFile = read_attachment_file(path)?;
+        let attributes: Vec<_> = file.attributes.into();
+        check_unique(&attributes)?;
+        let exchange = agent
+            .create_offer(CredentialCreateOfferOptions {
+                cred_def_id: file.cred_def_id,
+                attributes,
+            })
+            .await?;
+        info!({ "credential_exchange_id": exchange.credential_exchange_id });
+        attachments.push(OobAttachment::CredentialOffer(
+            exchange.credential_exchange_id,
+        ));
+    }
+    Ok(attachments)
+}
+
+/// Read the JSON file of an attachment of an invitation
+fn read_attachment_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let content = std::fs::read_to_string(path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| Error::InvalidAttachmentFile(path.display().to_string(), e.to_string()).into())
+}
+
 /// Create an invitation struct from an invitation url
 pub fn invite_url_to_struct(url: impl AsRef<str>) -> Result<OobConnectionReceiveInvitationOptions> {
     // Split the url
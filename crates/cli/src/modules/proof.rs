@@ -1,8 +1,14 @@
 use crate::error::{Error, Result};
 use crate::help_strings::HelpStrings;
 use crate::utils::loader::{Loader, LoaderVariant};
+use crate::utils::resolve::resolve_connection_id;
+use crate::utils::string_enum::string_enum_parser;
 use clap::{Args, Subcommand};
-use siera_agent::modules::proof::{Predicate, ProofModule, ProofRequestOptions};
+use siera_agent::modules::connection::ConnectionModule;
+use siera_agent::modules::proof::{
+    Predicate, PresentationExchangeState, ProofGetAllOptions, ProofModule, ProofRequestOptions,
+};
+use siera_logger::{copy, output, pretty_stringify_obj};
 
 /// Proof options and flags
 #[derive(Args)]
@@ -19,9 +25,9 @@ pub enum ProofSubcommands {
     /// Send a proof request to the connection id
     #[clap(about = HelpStrings::ProofRequest)]
     Request {
-        /// Connection id to send the proof request to
+        /// Connection id, alias or label to send the proof request to
         #[clap(short, long, help = HelpStrings::ProofRequestConnectionId)]
-        connection_id: String,
+        connection_id: Option<String>,
 
         /// The name of the proof request
         #[clap(short, long, default_value = "proof-request", help = HelpStrings::ProofRequestName)]
@@ -30,20 +36,51 @@ pub enum ProofSubcommands {
         /// A list of predicates that are supposed to be in the proof request
         /// e.g. age,>=,18
         #[clap(short, long, help = HelpStrings::ProofRequestPredicate)]
-        predicate: Vec<Predicate>,
+        predicate: Vec<String>,
 
         /// List of attributes that the receiver must send back to fulfill the request
         #[clap(short, long, help = HelpStrings::ProofRequestAttribute)]
         attribute: Vec<String>,
     },
+
+    /// List all presentation exchanges
+    #[clap(about = HelpStrings::ProofList)]
+    List {
+        /// Get a single presentation exchange by id
+        #[clap(long, short, help = HelpStrings::ProofListId)]
+        id: Option<String>,
+
+        /// Filter on the connection id, alias or label
+        #[clap(long, short, help = HelpStrings::ProofListConnectionId, conflicts_with = "id")]
+        connection_id: Option<String>,
+
+        /// Filter on your role
+        #[clap(long, short, help = HelpStrings::ProofListRole, conflicts_with = "id")]
+        role: Option<String>,
+
+        /// Filter on the state of the presentation exchange
+        #[clap(long, short, help = HelpStrings::ProofListState, conflicts_with = "id", value_parser = string_enum_parser::<PresentationExchangeState>())]
+        state: Option<PresentationExchangeState>,
+
+        /// Filter on the thread id
+        #[clap(long, short, help = HelpStrings::ProofListThreadId, conflicts_with = "id")]
+        thread_id: Option<String>,
+    },
+
+    /// Verify a presentation that was received
+    #[clap(about = HelpStrings::ProofVerify)]
+    Verify {
+        /// Presentation exchange id of the received presentation
+        #[clap(short, long, help = HelpStrings::ProofVerifyId)]
+        id: String,
+    },
 }
 
 /// Subcoammnd Proof parser
 pub async fn parse_proof_args(
     commands: &ProofSubcommands,
-    agent: impl ProofModule + Send + Sync,
+    agent: &(impl ProofModule + ConnectionModule + Send + Sync + ?Sized),
 ) -> Result<()> {
-    let loader = Loader::start(&LoaderVariant::default());
     match commands {
         ProofSubcommands::Request {
             connection_id,
@@ -51,20 +88,11 @@ pub async fn parse_proof_args(
             predicate,
             name,
         } => {
-            let predicates: Vec<(String, String, i32)> = predicate
-                .iter()
-                .map(|p| {
-                    (
-                        p.0.clone(),
-                        p.1.clone(),
-                        p.2.parse()
-                            .map_err(|_| Error::PredicateValueNonNumber(p.0.clone(), p.2.clone()))
-                            .unwrap(),
-                    )
-                })
-                .collect();
+            let predicates = parse_predicates(predicate)?;
+            let connection_id = resolve_connection_id(agent, connection_id.as_deref()).await?;
+            let loader = Loader::start(&LoaderVariant::default());
             let proof_request_options = ProofRequestOptions {
-                connection_id: connection_id.clone(),
+                connection_id,
                 name: name.clone(),
                 attributes: attribute.clone(),
                 predicates,
@@ -81,5 +109,58 @@ pub async fn parse_proof_args(
             loader.stop();
             Ok(())
         }
+        ProofSubcommands::List {
+            id,
+            connection_id,
+            role,
+            state,
+            thread_id,
+        } => {
+            let loader = Loader::start(&LoaderVariant::default());
+            if let Some(id) = id {
+                let exchange = ProofModule::get_by_id(agent, id.clone()).await?;
+                loader.stop();
+                copy!("{}", pretty_stringify_obj(&exchange));
+                return output("presentation_exchange", &exchange).map_err(Into::into);
+            }
+            let connection_id = match connection_id {
+                Some(c) => Some(resolve_connection_id(agent, Some(c)).await?),
+                None => None,
+            };
+            let options = ProofGetAllOptions {
+                connection_id,
+                role: role.clone(),
+                state: state.clone(),
+                thread_id: thread_id.clone(),
+            };
+            let exchanges = ProofModule::get_all(agent, options).await?;
+            loader.stop();
+            copy!("{}", pretty_stringify_obj(&exchanges));
+            output("presentation_exchanges", &exchanges).map_err(Into::into)
+        }
+        ProofSubcommands::Verify { id } => {
+            let exchange = agent.verify(id.clone()).await?;
+            debug!({ "proof": exchange });
+            if exchange.verified.as_deref() != Some("true") {
+                return Err(Error::ProofNotVerified(id.clone()).into());
+            }
+            info!({ "message": "Successfully verified the presentation" });
+            log!({ "presentation_exchange_id": &exchange.presentation_exchange_id, "verified": true });
+            Ok(())
+        }
     }
 }
+
+/// Parse a predicate of a proof request, e.g. `age,>=,18`, into its name, operator and value
+pub fn parse_predicate(predicate: &str) -> Result<(String, String, i32)> {
+    let Predicate(name, operator, value) = predicate.parse()?;
+    let value = value
+        .parse::<i32>()
+        .map_err(|_| Error::PredicateValueNonNumber(name.clone(), value.clone()))?;
+    Ok((name, operator, value))
+}
+
+/// Parse every predicate of a proof request
+pub fn parse_predicates(predicates: &[String]) -> Result<Vec<(String, String, i32)>> {
+    predicates.iter().map(|p| parse_predicate(p)).collect()
+}
@@ -0,0 +1,28 @@
+use crate::error::Result;
+use crate::flow::Flow;
+use crate::help_strings::HelpStrings;
+use clap::Args;
+use siera_agent::agent::Agent;
+use siera_logger::output;
+use std::path::PathBuf;
+
+/// Run options and flags
+#[derive(Args)]
+#[clap(about = HelpStrings::Run)]
+pub struct RunOptions {
+    /// Path to the flow file
+    #[clap(help = HelpStrings::RunFlow)]
+    flow: PathBuf,
+}
+
+/// Subcommand Run parser
+pub async fn parse_run_args(
+    options: &RunOptions,
+    build_agent: impl Fn(Option<&str>) -> Result<Box<dyn Agent>>,
+) -> Result<()> {
+    let flow = Flow::from_path(&options.flow)?;
+    let steps = flow.execute(build_agent).await?;
+    info!({ "message": format!("Finished {} steps", flow.steps.len()) });
+    output("steps", &steps)?;
+    Ok(())
+}
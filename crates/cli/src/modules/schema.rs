@@ -3,7 +3,7 @@ use crate::help_strings::HelpStrings;
 use crate::utils::loader::{Loader, LoaderVariant};
 use clap::{Args, Subcommand};
 use siera_agent::modules::schema::{SchemaCreateOptions, SchemaModule};
-use siera_logger::pretty_stringify_obj;
+use siera_logger::{output, pretty_stringify_obj};
 
 /// Schema options and flags
 #[derive(Args)]
@@ -46,7 +46,7 @@ pub enum SchemaSubcommands {
 /// Subcommand Schema parser
 pub async fn parse_schema_args(
     options: &SchemaOptions,
-    agent: impl SchemaModule + Send + Sync,
+    agent: &(impl SchemaModule + Send + Sync + ?Sized),
 ) -> Result<()> {
     let loader = Loader::start(&LoaderVariant::default());
     match &options.commands {
@@ -63,31 +63,36 @@ pub async fn parse_schema_args(
             if options.attributes.is_empty() {
                 return Err(Error::RequiredAttributes.into());
             }
-            agent.create(options).await.map(|schema| {
-                debug!({ "schema": schema });
-                info!({"message": "Created schema"});
-                schema
-                    .attr_names
-                    .into_iter()
-                    .for_each(|name| info!({ "schema_name": name }));
-                log!({ "schema_id": schema.id});
-                copy!("{}", schema.id);
-            })
+            agent
+                .create(options)
+                .await
+                .map(|schema| {
+                    debug!({ "schema": schema });
+                    info!({"message": "Created schema"});
+                    schema
+                        .attr_names
+                        .into_iter()
+                        .for_each(|name| info!({ "schema_name": name }));
+                    log!({ "schema_id": schema.id});
+                    copy!("{}", schema.id);
+                })
+                .map_err(Into::into)
         }
         SchemaSubcommands::List { id } => match id {
-            Some(i) => agent.get_by_id(i.clone()).await.map(|schema| {
+            Some(i) => {
+                let schema = agent.get_by_id(i.clone()).await?;
                 loader.stop();
-                log!({ "schema": schema });
+                output("schema", &schema)?;
                 copy!("{}", pretty_stringify_obj(&schema));
-            }),
-            None => agent.get_all().await.map(|schemas| {
+                Ok(())
+            }
+            None => {
+                let schemas = agent.get_all().await?;
                 loader.stop();
-                schemas
-                    .schema_ids
-                    .iter()
-                    .for_each(|schema_id| log!({ "schema_id": schema_id }));
+                output("schema_id", &schemas.schema_ids)?;
                 info!({ "message": "Successfully fetched schema IDs" });
-            }),
+                Ok(())
+            }
         },
     }
 }
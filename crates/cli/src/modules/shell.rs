@@ -0,0 +1,284 @@
+use crate::cli::Commands;
+use crate::error::Result;
+use crate::exit_code::ExitCode;
+use crate::help_strings::HelpStrings;
+use crate::register::{command_name, run_command};
+use crate::utils::config::get_config_path;
+use crate::utils::webhook::spawn_listener;
+use clap::{Args, CommandFactory, FromArgMatches, Parser};
+use colored::Colorize;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, ExternalPrinter, Helper};
+use serde_json::Value;
+use siera_agent::agent::{Agent, Capability};
+use siera_agent::modules::connection::{ConnectionGetAllOptions, ConnectionModule};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Shell options and flags
+#[derive(Args)]
+#[clap(about = HelpStrings::Shell)]
+pub struct ShellOptions {
+    /// Whether incoming webhook events should be printed between the prompts
+    #[clap(long, short, help = HelpStrings::ShellWebhooks)]
+    webhooks: bool,
+}
+
+/// A single line that is entered in the shell. It takes the same subcommands as the cli
+#[derive(Parser)]
+#[clap(name = "", no_binary_name = true, disable_help_subcommand = true)]
+struct ShellLine {
+    /// The subcommand that is executed
+    #[clap(subcommand)]
+    commands: Commands,
+}
+
+/// Commands of the shell itself, which are not sent to the agent
+const BUILTINS: &[&str] = &["exit", "quit"];
+
+/// Line editor helper that completes subcommands, flags and connection ids and aliases
+struct ShellHelper {
+    /// The command tree of a shell line
+    command: clap::Command,
+
+    /// Ids of the connections of the agent
+    connection_ids: Vec<String>,
+
+    /// Aliases of the connections of the agent
+    connection_aliases: Vec<String>,
+}
+
+impl ShellHelper {
+    /// Candidates for the value of `flag` of the subcommand at `path`
+    fn complete_value(&self, path: &[&str], command: &clap::Command, flag: &str) -> Vec<String> {
+        let Some(arg) = command.get_arguments().find(|arg| {
+            arg.get_long().map(|long| format!("--{long}")).as_deref() == Some(flag)
+                || arg.get_short().map(|short| format!("-{short}")).as_deref() == Some(flag)
+        }) else {
+            return vec![];
+        };
+        match arg.get_id().as_str() {
+            "connection_id" => [&self.connection_ids[..], &self.connection_aliases[..]].concat(),
+            "id" if path.first() == Some(&"connection") => self.connection_ids.clone(),
+            "alias" if path.first() == Some(&"connection") => self.connection_aliases.clone(),
+            _ => vec![],
+        }
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let text = &line[..pos];
+        let start = text.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &text[start..];
+        let tokens: Vec<&str> = text[..start].split_whitespace().collect();
+
+        let mut command = &self.command;
+        let mut path = vec![];
+        for token in &tokens {
+            if let Some(subcommand) = command.find_subcommand(token) {
+                command = subcommand;
+                path.push(*token);
+            }
+        }
+
+        let candidates: Vec<String> = match tokens.last() {
+            Some(flag) if flag.starts_with('-') => self.complete_value(&path, command, flag),
+            _ if word.starts_with('-') => command
+                .get_arguments()
+                .filter_map(|arg| arg.get_long().map(|long| format!("--{long}")))
+                .collect(),
+            _ => command
+                .get_subcommands()
+                .map(|subcommand| subcommand.get_name().to_owned())
+                .chain(
+                    BUILTINS
+                        .iter()
+                        .filter(|_| tokens.is_empty())
+                        .map(|builtin| (*builtin).to_owned()),
+                )
+                .collect(),
+        };
+
+        let pairs = candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .map(|candidate| Pair {
+                display: candidate.clone(),
+                replacement: format!("{candidate} "),
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+/// Subcommand Shell parser
+///
+/// Reads commands until `exit`, `quit` or end of input and executes them with the same agent
+pub async fn parse_shell_args(options: &ShellOptions, agent: Arc<dyn Agent>) -> Result<()> {
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(ShellHelper {
+        command: ShellLine::command(),
+        connection_ids: vec![],
+        connection_aliases: vec![],
+    }));
+
+    let history = history_path();
+    if let Some(history) = &history {
+        if editor.load_history(history).is_err() {
+            debug!({ "message": "No shell history found", "path": history });
+        }
+    }
+
+    if options.webhooks {
+        listen_for_webhooks(&mut editor, &agent)?;
+    }
+
+    info!({ "message": "Type a command, e.g. `connection list`, or `exit` to leave the shell" });
+    refresh_connections(&mut editor, agent.as_ref()).await;
+
+    let prompt = format!("{} ", "siera>".bold().cyan());
+    loop {
+        let line = match tokio::task::block_in_place(|| editor.readline(&prompt)) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line)?;
+
+        let args = match shell_words::split(line) {
+            Ok(args) => args,
+            Err(e) => {
+                error!({ "error": e.to_string() });
+                continue;
+            }
+        };
+        if args.len() == 1 && BUILTINS.contains(&args[0].as_str()) {
+            break;
+        }
+
+        let matches = match ShellLine::command().try_get_matches_from(&args) {
+            Ok(matches) => matches,
+            Err(e) => {
+                e.print()?;
+                continue;
+            }
+        };
+        let shell_line = ShellLine::from_arg_matches(&matches)?;
+
+        siera_logger::envelope::set_command(command_name(&matches));
+        match run_command(&shell_line.commands, agent.as_ref()).await {
+            Ok(()) => siera_logger::envelope::finish(None),
+            Err(e) => {
+                ExitCode::report(e.as_ref());
+            }
+        }
+        refresh_connections(&mut editor, agent.as_ref()).await;
+    }
+
+    if let Some(history) = &history {
+        if let Err(e) = editor.save_history(history) {
+            debug!({ "message": "Could not save the shell history", "error": e.to_string() });
+        }
+    }
+    Ok(())
+}
+
+/// Path of the shell history, next to the configuration file
+fn history_path() -> Option<PathBuf> {
+    let path = get_config_path().ok()?.with_file_name("history");
+    std::fs::create_dir_all(path.parent()?).ok()?;
+    Some(path)
+}
+
+/// Fetch the connections of the agent for completion. Failures are not fatal, the shell just
+/// can not complete connection ids and aliases
+async fn refresh_connections(editor: &mut Editor<ShellHelper, DefaultHistory>, agent: &dyn Agent) {
+    if !agent.supports(Capability::Connection) {
+        return;
+    }
+    match ConnectionModule::get_all(agent, ConnectionGetAllOptions::default()).await {
+        Ok(connections) => {
+            if let Some(helper) = editor.helper_mut() {
+                helper.connection_ids = connections.iter().map(|c| c.id.clone()).collect();
+                helper.connection_aliases =
+                    connections.into_iter().filter_map(|c| c.alias).collect();
+                helper.connection_aliases.sort();
+                helper.connection_aliases.dedup();
+            }
+        }
+        Err(e) => {
+            debug!({ "message": "Could not fetch connections for completion", "error": e.message() });
+        }
+    }
+}
+
+/// Listen for webhook events in the background and print them above the prompt
+fn listen_for_webhooks(
+    editor: &mut Editor<ShellHelper, DefaultHistory>,
+    agent: &Arc<dyn Agent>,
+) -> Result<()> {
+    if !agent.supports(Capability::Webhook) {
+        warn!({ "message": format!("Agent {agent} does not support webhooks") });
+        return Ok(());
+    }
+
+    let mut printer = editor.create_external_printer()?;
+    let (receiver, listener) = spawn_listener(agent);
+
+    std::thread::spawn(move || {
+        for event in receiver {
+            let topic = event
+                .get("topic")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown");
+            let state = event
+                .pointer("/payload/state")
+                .and_then(Value::as_str)
+                .map(|state| format!(", state: {}", state.cyan()))
+                .unwrap_or_default();
+            let message = format!(
+                "{} (topic: {}{state})",
+                "Received hook".green(),
+                topic.blue()
+            );
+            if printer.print(message).is_err() {
+                break;
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        if let Ok(Err(e)) = listener.await {
+            warn!({ "message": "Stopped listening for webhooks", "error": e.message() });
+        }
+    });
+    Ok(())
+}
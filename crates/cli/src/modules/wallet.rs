@@ -1,12 +1,13 @@
 use crate::error::Result;
 use crate::help_strings::HelpStrings;
 use crate::utils::loader::{Loader, LoaderVariant};
-use clap::builder::PossibleValuesParser;
+use crate::utils::string_enum::string_enum_parser;
 use clap::{Args, Subcommand};
 use siera_agent::modules::wallet::{
-    CreateLocalDidOptions, Did, DidList, KeyType, SetDidEndpointOptions, WalletModule,
+    CreateLocalDidOptions, Did, DidKeyType, DidList, DidMethod, DidPosture, KeyType,
+    SetDidEndpointOptions, WalletModule,
 };
-use siera_logger::pretty_stringify_obj;
+use siera_logger::{output, pretty_stringify_obj};
 
 /// Schema options and flags
 #[derive(Args)]
@@ -28,16 +29,16 @@ pub enum WalletSubcommands {
         did: Option<String>,
 
         /// The key type of the wallet e.g. ed25519, bls12381g2
-        #[clap(short, long, help=HelpStrings::WalletListKeyType, required = false, value_parser=PossibleValuesParser::new(["ed25519", "bls12381g2"]))]
-        key_type: Option<String>,
+        #[clap(short, long, help=HelpStrings::WalletListKeyType, required = false, value_parser=string_enum_parser::<DidKeyType>())]
+        key_type: Option<DidKeyType>,
 
         /// The did method to query for
-        #[clap(short, long, help=HelpStrings::WalletListMethod, required = false, value_parser=PossibleValuesParser::new(["did", "sov"]))]
-        method: Option<String>,
+        #[clap(short, long, help=HelpStrings::WalletListMethod, required = false, value_parser=string_enum_parser::<DidMethod>())]
+        method: Option<DidMethod>,
 
         /// Available values : public, posted, wallet_only
-        #[clap(short, long, help=HelpStrings::WalletListPosture, required = false, value_parser=PossibleValuesParser::new(["posted", "public", "wallet_only"])) ]
-        posture: Option<String>,
+        #[clap(short, long, help=HelpStrings::WalletListPosture, required = false, value_parser=string_enum_parser::<DidPosture>())]
+        posture: Option<DidPosture>,
 
         /// The verification key of interest
         #[clap(short, long, help=HelpStrings::WalletListVerkey, required = false)]
@@ -47,13 +48,13 @@ pub enum WalletSubcommands {
     /// Create a local DID
     #[clap(about = HelpStrings::WalletCreate)]
     CreateLocalDid {
-        /// The method to be used did or sov
-        #[clap(long, short, help=HelpStrings::WalletCreateMethod, required = true, default_value="did", value_parser=PossibleValuesParser::new(["did", "sov"]))]
-        method: String,
+        /// The method to be used key or sov
+        #[clap(long, short, help=HelpStrings::WalletCreateMethod, required = true, default_value="key", value_parser=string_enum_parser::<DidMethod>())]
+        method: DidMethod,
 
         /// The key type e.g. ed25519 or bls12381g2
-        #[clap(long, short, help=HelpStrings::WalletListKeyType, required = true, default_value="ed25519",value_parser=PossibleValuesParser::new(["ed25519", "bls12381g2"]))]
-        key_type: String,
+        #[clap(long, short, help=HelpStrings::WalletListKeyType, required = true, default_value="ed25519", value_parser=string_enum_parser::<DidKeyType>())]
+        key_type: DidKeyType,
     },
 
     /// Rotate the wallets key pair
@@ -101,10 +102,11 @@ pub enum WalletSubcommands {
     },
 }
 
+#[allow(clippy::too_many_lines)]
 /// Subcommand Schema parser
 pub async fn parse_wallet_args(
     options: &WalletOptions,
-    agent: impl WalletModule + Send + Sync,
+    agent: &(impl WalletModule + Send + Sync + ?Sized),
 ) -> Result<()> {
     let loader = Loader::start(&LoaderVariant::default());
     match &options.commands {
@@ -122,15 +124,12 @@ pub async fn parse_wallet_args(
                 posture: posture.clone(),
                 verkey: verkey.clone(),
             };
-            agent
-                .get_wallet_dids(options)
-                .await
-                .map(|response: DidList| {
-                    loader.stop();
-                    info!({ "message": "Found the following DID information"});
-                    log!({ "response": response });
-                    copy!("{}", pretty_stringify_obj(&response));
-                })
+            let response: DidList = agent.get_wallet_dids(options).await?;
+            loader.stop();
+            info!({ "message": "Found the following DID information"});
+            output("dids", &response)?;
+            copy!("{}", pretty_stringify_obj(&response));
+            Ok(())
         }
         WalletSubcommands::CreateLocalDid { method, key_type } => {
             let options = CreateLocalDidOptions {
@@ -144,38 +143,48 @@ pub async fn parse_wallet_args(
                 info!({ "message": format!("Successfully created local DID: {:?}", response.did) });
                 log!({ "response": response });
                 copy!("{}", pretty_stringify_obj(&response));
-            })
+            }).map_err(Into::into)
         }
-        WalletSubcommands::RotateKeyPair { did } => {
-            agent.rotate_keypair(did.clone()).await.map(|response| {
+        WalletSubcommands::RotateKeyPair { did } => agent
+            .rotate_keypair(did.clone())
+            .await
+            .map(|response| {
                 loader.stop();
                 info!({ "message": format!("Successfully rotated keypair for DID: {did}") });
                 log!({ "response": response });
                 copy!("{}", pretty_stringify_obj(response));
             })
-        }
-        WalletSubcommands::FetchPublicDid {} => agent.fetch_public_did().await.map(|response| {
-            loader.stop();
-            info!({ "message": "Wallet public DID" });
-            log!({ "response": response });
-            copy!("{}", pretty_stringify_obj(&response));
-        }),
-        WalletSubcommands::AssignPublicDid { did } => {
-            agent.assign_public_did(did.clone()).await.map(|response| {
+            .map_err(Into::into),
+        WalletSubcommands::FetchPublicDid {} => agent
+            .fetch_public_did()
+            .await
+            .map(|response| {
+                loader.stop();
+                info!({ "message": "Wallet public DID" });
+                log!({ "response": response });
+                copy!("{}", pretty_stringify_obj(&response));
+            })
+            .map_err(Into::into),
+        WalletSubcommands::AssignPublicDid { did } => agent
+            .assign_public_did(did.clone())
+            .await
+            .map(|response| {
                 loader.stop();
                 info!({ "message": "Successfully assigned public DID" });
                 log!({ "response": response });
                 copy!("{}", pretty_stringify_obj(&response));
             })
-        }
-        WalletSubcommands::FetchDidEndpoint { did } => {
-            agent.fetch_did_endpoint(did.clone()).await.map(|response| {
+            .map_err(Into::into),
+        WalletSubcommands::FetchDidEndpoint { did } => agent
+            .fetch_did_endpoint(did.clone())
+            .await
+            .map(|response| {
                 loader.stop();
                 info!({ "message": format!("DID endpoint for DID: {did}") });
                 log!({ "response": response });
                 copy!("{}", pretty_stringify_obj(&response));
             })
-        }
+            .map_err(Into::into),
         WalletSubcommands::SetDidEndpoint {
             did,
             endpoint,
@@ -186,12 +195,16 @@ pub async fn parse_wallet_args(
                 endpoint: endpoint.clone(),
                 endpoint_type: endpoint_type.clone(),
             };
-            agent.set_did_endpoint(options).await.map(|response| {
-                loader.stop();
-                info!({ "message": format!("Set DID endpoint for DID: {did}") });
-                log!({ "response": response });
-                copy!("{}", pretty_stringify_obj(response));
-            })
+            agent
+                .set_did_endpoint(options)
+                .await
+                .map(|response| {
+                    loader.stop();
+                    info!({ "message": format!("Set DID endpoint for DID: {did}") });
+                    log!({ "response": response });
+                    copy!("{}", pretty_stringify_obj(response));
+                })
+                .map_err(Into::into)
         }
     }
 }
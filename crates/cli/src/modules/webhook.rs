@@ -23,9 +23,14 @@ pub enum WebhookSubcommands {
 }
 
 /// Subcommand webhook parser
-pub async fn parse_webhook_args(agent: impl WebhookModule + Send + Sync) -> Result<()> {
+pub async fn parse_webhook_args(agent: &(impl WebhookModule + Send + Sync + ?Sized)) -> Result<()> {
     agent
         .listen(|event| {
+            // The listener never finishes, so every event is emitted as its own document
+            if siera_logger::STATE.read().unwrap().should_output_json {
+                siera_logger::envelope::event(serde_json::json!({ "event": event }));
+                return;
+            }
             let topic = event.get("topic");
             let incoming_webhook_message = topic.map_or_else(
                 || format!("{}:", "Received hook".green()),
@@ -40,4 +45,5 @@ pub async fn parse_webhook_args(agent: impl WebhookModule + Send + Sync) -> Resu
             log!({ "webhook_message": incoming_webhook_message, "event": event});
         })
         .await
+        .map_err(Into::into)
 }
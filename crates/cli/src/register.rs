@@ -1,28 +1,43 @@
 use crate::cli::{Cli, Commands};
 use crate::error::{Error, Result};
-use crate::modules::automation::parse_automation_args;
+use crate::modules::api::parse_api_args;
+use crate::modules::automation::{
+    parse_automation_args, parse_issue_and_verify_args, AutomationOptions, AutomationSubcommands,
+};
 use crate::modules::basic_message::parse_basic_message_args;
 use crate::modules::configuration::parse_configuration_args;
-use crate::modules::connection::parse_connection_args;
+use crate::modules::connection::{
+    parse_connection_args, parse_connection_establish_args, ConnectionOptions,
+    ConnectionSubcommands,
+};
 use crate::modules::credential::parse_credentials_args;
 use crate::modules::credential_definition::parse_credential_definition_args;
+use crate::modules::dashboard::parse_dashboard_args;
 use crate::modules::feature::parse_features_args;
+use crate::modules::mock_agent::parse_mock_agent_args;
 use crate::modules::multitenancy::parse_multitenancy_args;
 use crate::modules::oob::parse_oob_args;
 use crate::modules::proof::parse_proof_args;
+use crate::modules::run::parse_run_args;
 use crate::modules::schema::parse_schema_args;
+use crate::modules::shell::parse_shell_args;
 use crate::modules::wallet::parse_wallet_args;
 use crate::modules::webhook::parse_webhook_args;
-use crate::utils::config::{get_config_from_path, get_config_path};
-use clap::Parser;
+use crate::utils::config::{get_config_from_path, get_config_path, Environment};
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
 use siera_afj_rest::agent::{CloudAgentAfjRest, CloudAgentAfjRestVersion};
+use siera_agent::agent::{Agent, AgentOptions, AgentRegistry, HttpOptions, TlsOptions};
+use siera_agent::error::Error as AgentError;
 use siera_cloudagent_python::agent::{CloudAgentPython, CloudAgentPythonVersion};
-use siera_logger::LogLevel;
-use std::path::PathBuf;
+use siera_logger::{LogLevel, OutputFormat};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Register the subcommands on the cli
 pub async fn register() -> Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
     let level = if cli.quiet {
         LogLevel::Off
     } else {
@@ -34,93 +49,179 @@ pub async fn register() -> Result<()> {
         }
     };
 
-    siera_logger::init(level, cli.copy, cli.output_json);
+    let output = match cli.output {
+        Some(output) => output,
+        None if cli.output_json => OutputFormat::Json,
+        None => OutputFormat::default(),
+    };
+
+    siera_logger::init(level, cli.copy, output, cli.fields.clone());
+    siera_logger::envelope::set_command(command_name(&matches));
 
     trace!({ "message": "Parsed CLI options and initialized logger" });
 
-    // Commands where the agent is not required
-    if let Commands::Configuration(options) = &cli.commands {
-        parse_configuration_args(options)
-    } else {
-        let (agent_url, api_key, auth_token, agent) = transform_agent_data(
-            cli.agent,
-            cli.config,
-            cli.environment,
-            cli.agent_url,
-            cli.api_key,
-            cli.token,
-        )?;
-
-        debug!({
-            "message": "Loading agent with the following config",
-            "url": agent_url,
-            "api_key": api_key,
-            "agent": agent_url
-        });
+    if cli.dry_run && !cli.commands.supports_dry_run() {
+        return Err(Error::DryRunUnsupported((&cli.commands).into()).into());
+    }
 
-        // TODO: Ideally we would get an Agent<A> here that we would pass into the commands
-        match agent.as_str() {
-            "aca-py" => {
-                let version = CloudAgentPythonVersion::ZeroSevenThree;
-                let agent = CloudAgentPython::new(agent_url, version, api_key, auth_token);
-                // Commands that require the agent
-                match &cli.commands {
-                    Commands::Automate(options) => parse_automation_args(options, agent).await,
-                    Commands::Connection(options) => parse_connection_args(options, agent).await,
-                    Commands::Credential(options) => {
-                        parse_credentials_args(&options.commands, agent).await
-                    }
-                    Commands::CredentialDefinition(options) => {
-                        parse_credential_definition_args(options, agent).await
-                    }
-                    Commands::Feature(_) => parse_features_args(agent).await,
-                    Commands::Message(options) => parse_basic_message_args(options, agent).await,
-                    Commands::Multitenancy(options) => {
-                        parse_multitenancy_args(options, agent).await
-                    }
-                    Commands::Oob(options) => parse_oob_args(options, agent).await,
-                    Commands::Proof(options) => parse_proof_args(&options.commands, agent).await,
-                    Commands::Schema(options) => parse_schema_args(options, agent).await,
-                    Commands::Wallet(options) => parse_wallet_args(options, agent).await,
-                    Commands::Webhook(_) => parse_webhook_args(agent).await,
-                    Commands::Configuration(_) => Err(Error::SubcommandNotRegisteredForAgent(
-                        cli.commands.into(),
-                        "aca-py",
-                    )
-                    .into()),
-                }
-            }
-            "afj" => {
-                let version = CloudAgentAfjRestVersion::ZeroEightZero;
-                let agent = CloudAgentAfjRest::new(agent_url, version, api_key, auth_token);
-                match &cli.commands {
-                    // TODO: should accept struct that has a field that implements the module
-                    Commands::Schema(options) => parse_schema_args(options, agent).await,
-                    Commands::CredentialDefinition(options) => {
-                        parse_credential_definition_args(options, agent).await
-                    }
-                    Commands::Connection(options) => parse_connection_args(options, agent).await,
-                    Commands::Message(options) => parse_basic_message_args(options, agent).await,
-                    _ => Err(
-                        Error::SubcommandNotRegisteredForAgent(cli.commands.into(), "afj").into(),
-                    ),
-                }
+    // Every command builds the agents it needs, so commands that span several environments, or
+    // none at all, are dispatched like any other
+    let agents = |environment: Option<&str>| build_agent(&cli, environment);
+
+    let result = match &cli.commands {
+        Commands::Configuration(options) => parse_configuration_args(options),
+        // The mock agent is the agent, so there is nothing to connect to
+        Commands::MockAgent(options) => parse_mock_agent_args(options).await,
+        // Every step of a flow can use another environment
+        Commands::Run(options) => parse_run_args(options, agents).await,
+        // Both sides of the connection are an environment of their own
+        Commands::Connection(ConnectionOptions {
+            commands: ConnectionSubcommands::Establish(options),
+        }) => parse_connection_establish_args(options, agents).await,
+        // The issuer and the holder are an environment of their own
+        Commands::Automate(AutomationOptions {
+            commands: AutomationSubcommands::IssueAndVerify(options),
+        }) => parse_issue_and_verify_args(options, agents).await,
+        // The shell keeps the agent around for every command that is entered
+        Commands::Shell(options) => parse_shell_args(options, Arc::from(agents(None)?)).await,
+        // The dashboard shares the agent with the webhook listener
+        Commands::Dashboard(options) => {
+            let agent = agents(None)?;
+            check_capabilities(&cli.commands, agent.as_ref())?;
+            parse_dashboard_args(options, Arc::from(agent)).await
+        }
+        commands => run_command(commands, agents(None)?.as_ref()).await,
+    };
+
+    // A dry run stops at its first request, once it is printed. That is the whole command
+    match result {
+        Err(e) if cli.dry_run => match e.downcast_ref::<AgentError>() {
+            Some(dry_run @ AgentError::DryRun(_)) => {
+                warn!({ "message": dry_run.message() });
+                Ok(())
             }
-            _ => unreachable!(),
+            _ => Err(e),
+        },
+        result => result,
+    }
+}
+
+/// The invoked subcommands separated by a space, e.g. `connection list`
+pub fn command_name(matches: &ArgMatches) -> String {
+    let mut names = vec![];
+    let mut matches = matches;
+    while let Some((name, sub_matches)) = matches.subcommand() {
+        names.push(name);
+        matches = sub_matches;
+    }
+    names.join(" ")
+}
+
+/// Every agent that can be used by the cli
+fn agent_registry() -> AgentRegistry {
+    let mut registry = AgentRegistry::default();
+    registry
+        .register("aca-py", |options| {
+            Ok(Box::new(CloudAgentPython::new(
+                options.endpoint,
+                CloudAgentPythonVersion::ZeroSevenThree,
+                options.api_key,
+                options.auth_token,
+                options.http,
+            )?))
+        })
+        .register("afj", |options| {
+            Ok(Box::new(CloudAgentAfjRest::new(
+                options.endpoint,
+                CloudAgentAfjRestVersion::ZeroEightZero,
+                options.api_key,
+                options.auth_token,
+                options.http,
+            )?))
+        });
+    registry
+}
+
+/// Names of the registered agents, used for error reporting
+fn registry_names(registry: &AgentRegistry) -> Vec<String> {
+    registry
+        .names()
+        .into_iter()
+        .map(std::borrow::ToOwned::to_owned)
+        .collect()
+}
+
+/// Execute a command that requires an agent
+///
+/// The agent is checked for the modules the command needs before anything is sent
+pub async fn run_command(commands: &Commands, agent: &dyn Agent) -> Result<()> {
+    check_capabilities(commands, agent)?;
+
+    match commands {
+        Commands::Api(options) => parse_api_args(options, agent).await,
+        Commands::Automate(options) => parse_automation_args(options, agent).await,
+        Commands::Connection(options) => parse_connection_args(options, agent).await,
+        Commands::Credential(options) => parse_credentials_args(&options.commands, agent).await,
+        Commands::CredentialDefinition(options) => {
+            parse_credential_definition_args(options, agent).await
+        }
+        Commands::Feature(_) => parse_features_args(agent).await,
+        Commands::Message(options) => parse_basic_message_args(options, agent).await,
+        Commands::Multitenancy(options) => parse_multitenancy_args(options, agent).await,
+        Commands::Oob(options) => parse_oob_args(options, agent).await,
+        Commands::Proof(options) => parse_proof_args(&options.commands, agent).await,
+        Commands::Schema(options) => parse_schema_args(options, agent).await,
+        Commands::Wallet(options) => parse_wallet_args(options, agent).await,
+        Commands::Webhook(_) => parse_webhook_args(agent).await,
+        Commands::Configuration(options) => parse_configuration_args(options),
+        Commands::Shell(_) | Commands::Dashboard(_) | Commands::Run(_) | Commands::MockAgent(_) => {
+            Err(Error::UnavailableInShell(commands.into()).into())
         }
     }
 }
 
+/// Check that the agent supports every module the command needs
+fn check_capabilities(commands: &Commands, agent: &dyn Agent) -> Result<()> {
+    if let Some(capability) = commands
+        .capabilities()
+        .iter()
+        .find(|c| !agent.supports(**c))
+    {
+        return Err(Error::SubcommandNotRegisteredForAgent(
+            commands.into(),
+            agent.to_string(),
+            capability.to_string(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Build the agent for an environment of the configuration file, or for the environment and flags
+/// of the cli when none is supplied
+fn build_agent(cli: &Cli, environment: Option<&str>) -> Result<Box<dyn Agent>> {
+    let (agent, options) = transform_agent_data(cli, environment)?;
+
+    debug!({
+        "message": "Loading agent with the following config",
+        "url": options.endpoint,
+        "api_key": options.api_key,
+        "agent": agent
+    });
+
+    let registry = agent_registry();
+    registry
+        .build(&agent, options)
+        .ok_or_else(|| Error::InvalidAgent(agent, registry_names(&registry)))?
+        .map_err(Into::into)
+}
+
 /// Initialize any agent from the cli
-fn transform_agent_data(
-    agent: Option<String>,
-    config: Option<PathBuf>,
-    environment: String,
-    agent_url: Option<String>,
-    api_key: Option<String>,
-    auth_token: Option<String>,
-) -> Result<(String, Option<String>, Option<String>, String)> {
-    let config_path = config.map_or_else(
+///
+/// Flags take precedence over the environment in the configuration file. When an environment is
+/// supplied explicitly, only the http flags apply to it
+fn transform_agent_data(cli: &Cli, environment: Option<&str>) -> Result<(String, AgentOptions)> {
+    let config_path = cli.config.clone().map_or_else(
         || {
             let config = get_config_path();
             config.map_or(None, |c| if c.exists() { Some(c) } else { None })
@@ -128,31 +229,105 @@ fn transform_agent_data(
         Some,
     );
 
-    let (agent_url, api_key, auth_token, agent) = if let Some(cp) = config_path {
-        let configurations = get_config_from_path(&cp)?;
-        let configuration = configurations
-            .configurations
-            .get_key_value(&environment)
-            .ok_or(Error::InvalidEnvironment(environment))?;
-        let agent_url = agent_url.unwrap_or_else(|| configuration.1.endpoint.clone());
-        let api_key = api_key.or_else(|| configuration.1.api_key.clone());
-        let auth_token = auth_token.or_else(|| configuration.1.auth_token.clone());
-        let agent = agent.or_else(|| configuration.1.agent.clone());
-        (agent_url, api_key, auth_token, agent)
+    let configurations = config_path
+        .as_ref()
+        .map(|cp| get_config_from_path(cp))
+        .transpose()?;
+    let config_dir = config_path.as_deref().and_then(Path::parent);
+    let flags = if environment.is_some() {
+        None
     } else {
-        let agent_url = agent_url.ok_or(Error::NoAgentURLSupplied)?;
-        (agent_url, api_key, auth_token, agent)
+        Some(cli)
+    };
+    let name = environment.unwrap_or(&cli.environment);
+    let environment = match (&configurations, environment) {
+        (Some(c), _) => Some(
+            c.configurations
+                .get(name)
+                .ok_or_else(|| Error::InvalidEnvironment(name.to_owned()))?,
+        ),
+        (None, Some(name)) => return Err(Error::InvalidEnvironment(name.to_owned()).into()),
+        (None, None) => None,
     };
 
-    let agent = agent.or_else(|| Some(String::from("aca-py")));
+    let endpoint = match (flags.and_then(|c| c.agent_url.clone()), environment) {
+        (Some(agent_url), _) => agent_url,
+        (None, Some(environment)) => environment.endpoint.clone(),
+        (None, None) => return Err(Error::NoAgentURLSupplied.into()),
+    };
+    let api_key = flags
+        .and_then(|c| c.api_key.clone())
+        .or_else(|| environment.and_then(|e| e.api_key.clone()));
+    let auth_token = flags
+        .and_then(|c| c.token.clone())
+        .or_else(|| environment.and_then(|e| e.auth_token.clone()));
+    let agent = flags
+        .and_then(|c| c.agent.clone())
+        .or_else(|| environment.and_then(|e| e.agent.clone()))
+        .unwrap_or_else(|| String::from("aca-py"));
 
-    match agent {
-        Some(a) => {
-            if a != *"aca-py" && a != *"afj" {
-                return Err(Error::InvalidAgent(a).into());
-            }
-            Ok((agent_url, api_key, auth_token, a))
-        }
-        None => unreachable!(),
+    let options = AgentOptions {
+        endpoint,
+        api_key,
+        auth_token,
+        http: http_options(cli, environment, config_dir),
+    };
+
+    Ok((agent, options))
+}
+
+/// Options for the http client of the agent, falling back to the environment and the defaults
+fn http_options(
+    cli: &Cli,
+    environment: Option<&Environment>,
+    config_dir: Option<&Path>,
+) -> HttpOptions {
+    let default = HttpOptions::default();
+    let connect_timeout = cli
+        .connect_timeout
+        .or_else(|| environment.and_then(|e| e.connect_timeout));
+    let request_timeout = cli
+        .request_timeout
+        .or_else(|| environment.and_then(|e| e.request_timeout));
+
+    HttpOptions {
+        connect_timeout: connect_timeout.map_or(default.connect_timeout, Duration::from_secs),
+        request_timeout: request_timeout.map_or(default.request_timeout, Duration::from_secs),
+        retries: cli
+            .retries
+            .or_else(|| environment.and_then(|e| e.retries))
+            .unwrap_or(default.retries),
+        proxy: cli
+            .proxy
+            .clone()
+            .or_else(|| environment.and_then(|e| e.proxy.clone())),
+        user_agent: cli
+            .user_agent
+            .clone()
+            .or_else(|| environment.and_then(|e| e.user_agent.clone())),
+        tls: environment.map_or_else(TlsOptions::default, |e| tls_options(e, config_dir)),
+        dry_run: cli.dry_run,
+        ..default
+    }
+}
+
+/// TLS options of the environment, with the paths resolved from the configuration directory
+fn tls_options(environment: &Environment, config_dir: Option<&Path>) -> TlsOptions {
+    let resolve = |path: &Option<std::path::PathBuf>| {
+        path.as_ref().map(|p| match config_dir {
+            Some(dir) if p.is_relative() => dir.join(p),
+            _ => p.clone(),
+        })
+    };
+
+    if environment.insecure_skip_verify {
+        warn!({ "message": "TLS certificate verification is disabled for this environment" });
+    }
+
+    TlsOptions {
+        ca_cert: resolve(&environment.ca_cert),
+        client_cert: resolve(&environment.client_cert),
+        client_key: resolve(&environment.client_key),
+        insecure_skip_verify: environment.insecure_skip_verify,
     }
 }
@@ -0,0 +1,103 @@
+use crate::error::{Error, Result};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use siera_agent::modules::credential::CredentialAttribute;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Content of an attributes file
+pub enum AttributesFile {
+    /// Attribute names with their values, e.g. `{"name": "Alice"}`
+    Values(BTreeMap<String, Value>),
+
+    /// Attributes with an optional MIME type, e.g. `[{"name": "photo", "value": "...", "mime-type": "image/png"}]`
+    Attributes(Vec<FileAttribute>),
+}
+
+/// The form is chosen by the JSON type, so the error explains what is wrong with that form
+/// instead of only that no form matched
+impl<'de> Deserialize<'de> for AttributesFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        match value {
+            Value::Array(_) => serde_json::from_value(value).map(Self::Attributes),
+            value => serde_json::from_value(value).map(Self::Values),
+        }
+        .map_err(D::Error::custom)
+    }
+}
+
+/// A single attribute in the list form of an attributes file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileAttribute {
+    /// Name of the attribute
+    name: String,
+
+    /// Value of the attribute. Numbers and booleans are converted to text
+    value: Value,
+
+    /// MIME type of the value
+    #[serde(rename = "mime-type", alias = "mime_type", alias = "mimeType")]
+    mime_type: Option<String>,
+}
+
+/// Parse an attribute of the form `name=value`
+///
+/// Only the first `=` separates the name from the value, so the value may contain `=`, as base64
+/// encoded values often do
+pub fn parse_attribute(attribute: &str) -> std::result::Result<CredentialAttribute, Error> {
+    match attribute.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok(CredentialAttribute::new(name.trim(), value))
+        }
+        _ => Err(Error::InvalidAttribute(attribute.to_owned())),
+    }
+}
+
+/// Read the attributes from a JSON file. The file contains either an object with the names and
+/// values, or an array of objects with a `name`, a `value` and optionally a `mime-type`
+pub fn read_attributes_file(path: &Path) -> Result<Vec<CredentialAttribute>> {
+    let content = std::fs::read_to_string(path)?;
+    let file: AttributesFile = serde_json::from_str(&content)
+        .map_err(|e| Error::InvalidAttributesFile(path.display().to_string(), e.to_string()))?;
+    Ok(file.into())
+}
+
+impl From<AttributesFile> for Vec<CredentialAttribute> {
+    fn from(file: AttributesFile) -> Self {
+        match file {
+            AttributesFile::Values(values) => values
+                .into_iter()
+                .map(|(name, value)| CredentialAttribute::new(name, value_to_string(value)))
+                .collect(),
+            AttributesFile::Attributes(attributes) => attributes
+                .into_iter()
+                .map(|a| CredentialAttribute {
+                    name: a.name,
+                    value: value_to_string(a.value),
+                    mime_type: a.mime_type,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Check that no attribute name is used more than once
+pub fn check_unique(attributes: &[CredentialAttribute]) -> Result<()> {
+    for (index, attribute) in attributes.iter().enumerate() {
+        if attributes[..index].iter().any(|a| a.name == attribute.name) {
+            return Err(Error::DuplicateAttribute(attribute.name.clone()).into());
+        }
+    }
+    Ok(())
+}
+
+/// Text of a JSON value. Strings are used without their quotes
+fn value_to_string(value: Value) -> String {
+    match value {
+        Value::String(s) => s,
+        v => v.to_string(),
+    }
+}
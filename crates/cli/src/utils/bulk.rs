@@ -0,0 +1,146 @@
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// A single row of a bulk file
+#[derive(Debug)]
+pub struct Row {
+    /// Position of the row in the file, starting at 1 and excluding the CSV header
+    pub number: usize,
+
+    /// Column names and their values
+    pub columns: BTreeMap<String, String>,
+}
+
+/// Result of a single row, written as one line of the report
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReportEntry {
+    /// Position of the row in the file
+    pub row: usize,
+
+    /// Connection the row was sent to, if it could be resolved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection_id: Option<String>,
+
+    /// Id of the exchange that was started for the row
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_exchange_id: Option<String>,
+
+    /// Why the row failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// JSONL report to which the result of every row is appended as soon as it is known
+pub struct Report {
+    /// The opened report file
+    file: File,
+}
+
+/// Read the rows of a CSV file with a header or of a JSONL file with an object per line
+pub fn read_rows(path: &Path) -> Result<Vec<Row>> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("csv") => read_csv(path),
+        Some("jsonl" | "ndjson") => read_jsonl(path),
+        _ => Err(Error::InvalidBulkFile(format!(
+            "{} is not a .csv or .jsonl file",
+            path.display()
+        ))
+        .into()),
+    }
+}
+
+/// Read the rows of a CSV file. The first line contains the column names
+fn read_csv(path: &Path) -> Result<Vec<Row>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let headers = reader.headers()?.clone();
+    reader
+        .records()
+        .enumerate()
+        .map(|(index, record)| {
+            let record =
+                record.map_err(|e| Error::InvalidBulkFile(format!("row {}: {e}", index + 1)))?;
+            let columns = headers
+                .iter()
+                .zip(record.iter())
+                .map(|(name, value)| (name.trim().to_owned(), value.to_owned()))
+                .collect();
+            Ok(Row {
+                number: index + 1,
+                columns,
+            })
+        })
+        .collect()
+}
+
+/// Read the rows of a JSONL file. Empty lines are skipped, but still count as a row number
+fn read_jsonl(path: &Path) -> Result<Vec<Row>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut rows = vec![];
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let number = index + 1;
+        let object: BTreeMap<String, Value> = serde_json::from_str(&line)
+            .map_err(|e| Error::InvalidBulkFile(format!("row {number}: {e}")))?;
+        let columns = object
+            .into_iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    Value::String(s) => s,
+                    v => v.to_string(),
+                };
+                (name, value)
+            })
+            .collect();
+        rows.push(Row { number, columns });
+    }
+    Ok(rows)
+}
+
+impl Report {
+    /// Open the report. An existing report is appended to when resuming and replaced otherwise
+    pub fn open(path: &Path, resume: bool) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resume)
+            .truncate(!resume)
+            .open(path)?;
+        Ok(Self { file })
+    }
+
+    /// The rows that already succeeded according to an existing report
+    pub fn succeeded_rows(path: &Path) -> Result<BTreeSet<usize>> {
+        if !path.exists() {
+            return Ok(BTreeSet::new());
+        }
+        let reader = BufReader::new(File::open(path)?);
+        let mut rows = BTreeSet::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: ReportEntry = serde_json::from_str(&line)
+                .map_err(|e| Error::InvalidBulkFile(format!("report {}: {e}", path.display())))?;
+            if entry.error.is_none() {
+                rows.insert(entry.row);
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Append the result of a row and flush it, so it survives an interrupted run
+    pub fn write(&mut self, entry: &ReportEntry) -> Result<()> {
+        writeln!(self.file, "{}", serde_json::to_string(entry)?)?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
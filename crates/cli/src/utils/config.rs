@@ -7,7 +7,7 @@ use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
 
 /// Structure for an environment in the configuration
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Environment {
     /// The agent endpoint
     pub endpoint: String,
@@ -20,6 +20,43 @@ pub struct Environment {
 
     /// The cloudagent type
     pub agent: Option<String>,
+
+    /// Maximum amount of seconds it may take to connect to the agent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<u64>,
+
+    /// Maximum amount of seconds a single request to the agent may take
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_timeout: Option<u64>,
+
+    /// How often an idempotent request is retried
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+
+    /// Proxy that every request is sent through
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+
+    /// User agent that is sent with every request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+
+    /// PEM encoded CA bundle that is trusted next to the system roots
+    /// Relative paths are resolved from the directory of the configuration file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<PathBuf>,
+
+    /// PEM encoded client certificate for mutual TLS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<PathBuf>,
+
+    /// PEM encoded PKCS #8 private key of the client certificate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<PathBuf>,
+
+    /// Accept invalid certificates and hostnames of the agent
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub insecure_skip_verify: bool,
 }
 
 impl fmt::Display for Environment {
@@ -71,6 +108,7 @@ impl Configuration {
             api_key: None,
             agent: Some(String::from("aca-py")),
             auth_token: token,
+            ..Environment::default()
         };
         (String::from("default"), environment)
     }
@@ -1,3 +1,9 @@
+/// Module for parsing the attributes of a credential
+pub mod attributes;
+
+/// Module for reading bulk files and writing their reports
+pub mod bulk;
+
 /// Module for interacting with the config
 pub mod config;
 
@@ -6,3 +12,12 @@ pub mod loader;
 
 /// Module for priting a qr code to the cli
 pub mod qr;
+
+/// Module for resolving connections from their id, alias or label
+pub mod resolve;
+
+/// Module for parsing the string enums of the agent from the cli
+pub mod string_enum;
+
+/// Module for listening to webhook events in the background
+pub mod webhook;
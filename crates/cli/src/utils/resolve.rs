@@ -0,0 +1,95 @@
+use crate::error::{Error, Result};
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::FuzzySelect;
+use siera_agent::modules::connection::{Connection, ConnectionGetAllOptions, ConnectionModule};
+use std::io::IsTerminal;
+
+/// Resolve the connection a command is sent to
+///
+/// The reference can be a connection id, an alias or a label of the other party. Connection ids
+/// are used as is, so they do not need a request to the agent. Without a reference the user picks
+/// a connection when the cli is used interactively
+pub async fn resolve_connection_id(
+    agent: &(impl ConnectionModule + Send + Sync + ?Sized),
+    reference: Option<&str>,
+) -> Result<String> {
+    match reference {
+        Some(reference) if is_uuid(reference) => Ok(reference.to_owned()),
+        Some(reference) => {
+            let connections =
+                ConnectionModule::get_all(agent, ConnectionGetAllOptions::default()).await?;
+            find_connection(reference, &connections)
+        }
+        None if is_interactive() => {
+            let connections =
+                ConnectionModule::get_all(agent, ConnectionGetAllOptions::default()).await?;
+            tokio::task::block_in_place(|| pick_connection(&connections))
+        }
+        None => Err(Error::NoConnectionSupplied.into()),
+    }
+}
+
+/// Id of the only connection with the reference as its id, alias or label
+pub fn find_connection(reference: &str, connections: &[Connection]) -> Result<String> {
+    if is_uuid(reference) || connections.iter().any(|c| c.id == reference) {
+        return Ok(reference.to_owned());
+    }
+
+    let matches: Vec<&Connection> = connections
+        .iter()
+        .filter(|c| {
+            c.alias.as_deref() == Some(reference) || c.their_label.as_deref() == Some(reference)
+        })
+        .collect();
+    match matches.as_slice() {
+        [connection] => Ok(connection.id.clone()),
+        [] => Err(Error::UnknownConnection(reference.to_owned()).into()),
+        _ => Err(Error::AmbiguousConnection(
+            reference.to_owned(),
+            matches.iter().map(|c| describe(c)).collect(),
+        )
+        .into()),
+    }
+}
+
+/// Whether the value has the form of a UUID, which every connection id of the supported agents has
+pub fn is_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, length)| {
+            group.len() == length && group.chars().all(|c| c.is_ascii_hexdigit())
+        })
+}
+
+/// Whether the user can be asked to pick a connection
+fn is_interactive() -> bool {
+    let should_output_json = siera_logger::STATE.read().unwrap().should_output_json;
+    !should_output_json && std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+}
+
+/// Let the user pick a connection with a fuzzy search on the alias, label and id
+fn pick_connection(connections: &[Connection]) -> Result<String> {
+    if connections.is_empty() {
+        return Err(Error::NoConnectionSupplied.into());
+    }
+    let items: Vec<String> = connections.iter().map(describe).collect();
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Connection")
+        .items(&items)
+        .default(0)
+        .interact_opt()?;
+
+    selection
+        .map(|index| connections[index].id.clone())
+        .ok_or_else(|| Error::NoConnectionSupplied.into())
+}
+
+/// The connection as it is listed in errors and in the picker, e.g. `<id> (alice, Alice, active)`
+fn describe(connection: &Connection) -> String {
+    let details: Vec<String> = [connection.alias.clone(), connection.their_label.clone()]
+        .into_iter()
+        .flatten()
+        .chain([connection.state.to_string()])
+        .collect();
+    format!("{} ({})", connection.id, details.join(", "))
+}
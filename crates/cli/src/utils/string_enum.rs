@@ -0,0 +1,14 @@
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
+use siera_agent::string_enum::StringEnum;
+
+/// Value parser for a string enum of the agent
+///
+/// Only the known variants, and their aliases, are accepted and the variants are listed in the
+/// help output
+pub fn string_enum_parser<T: StringEnum>() -> impl TypedValueParser<Value = T> {
+    let values = T::VARIANTS
+        .iter()
+        .map(|(value, aliases)| PossibleValue::new(*value).aliases(aliases.iter().copied()));
+
+    PossibleValuesParser::new(values).map(|value| T::from(value.as_str()))
+}
@@ -0,0 +1,39 @@
+use serde_json::Value;
+use siera_agent::agent::Agent;
+use siera_agent::error::Result;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::task::JoinHandle;
+
+/// Sender of the webhook events of the current listener. The webhook listener only accepts a
+/// function pointer, so the channel has to be reachable from a static
+static WEBHOOK_EVENTS: OnceLock<Mutex<Option<Sender<Value>>>> = OnceLock::new();
+
+/// Listen for webhook events in the background
+///
+/// Every event is sent to the returned receiver. The handle resolves when the listener stops,
+/// which only happens on an error. Starting a new listener redirects the events of the previous
+/// one to the new receiver
+pub fn spawn_listener(agent: &Arc<dyn Agent>) -> (Receiver<Value>, JoinHandle<Result<()>>) {
+    let (sender, receiver) = channel::<Value>();
+    if let Ok(mut events) = WEBHOOK_EVENTS.get_or_init(|| Mutex::new(None)).lock() {
+        *events = Some(sender);
+    }
+
+    let agent = Arc::clone(agent);
+    // The listener blocks on the socket, so it gets a thread of its own
+    let handle = tokio::task::spawn_blocking(move || {
+        tokio::runtime::Handle::current().block_on(agent.listen(on_webhook_event))
+    });
+    (receiver, handle)
+}
+
+/// Forward a webhook event to the receiver of the current listener
+fn on_webhook_event(event: Value) {
+    if let Some(Ok(events)) = WEBHOOK_EVENTS.get().map(Mutex::lock) {
+        if let Some(sender) = events.as_ref() {
+            // The receiver is gone when the command that listens exits
+            let _ = sender.send(event);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use reqwest::Url;
+use siera_agent::agent::{Agent, Capability, HttpOptions};
 use siera_agent::error::{Error, Result};
+use siera_agent::http::HttpClient;
 
 /// Cloudagent Python Agent
 #[derive(Debug)]
@@ -15,6 +17,9 @@ pub struct CloudAgentPython {
 
     /// Agent version
     pub version: CloudAgentPythonVersion,
+
+    /// Http client that is reused for every request
+    pub http: HttpClient,
 }
 
 /// ACA-Py supported versions
@@ -25,21 +30,59 @@ pub enum CloudAgentPythonVersion {
     ZeroSevenThree,
 }
 
+impl std::fmt::Display for CloudAgentPythonVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let v = match self {
+            CloudAgentPythonVersion::ZeroSevenThree => "0.7.3",
+        };
+        write!(f, "{v}")
+    }
+}
+
+impl std::fmt::Display for CloudAgentPython {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ACA-Py {0}", self.version)
+    }
+}
+
+impl Agent for CloudAgentPython {
+    fn capabilities(&self) -> &'static [Capability] {
+        Capability::ALL
+    }
+
+    fn is_dry_run(&self) -> bool {
+        self.http.options.dry_run
+    }
+}
+
 impl CloudAgentPython {
     /// Create a new instance of an aries cloudagent python
-    #[must_use]
-    pub const fn new(
+    ///
+    /// # Errors
+    ///
+    /// When the http client could not be built from the http options
+    pub fn new(
         endpoint: String,
         version: CloudAgentPythonVersion,
         api_key: Option<String>,
         auth_token: Option<String>,
-    ) -> Self {
-        Self {
+        http: HttpOptions,
+    ) -> Result<Self> {
+        let mut headers = vec![];
+        if let Some(api_key) = &api_key {
+            headers.push(("X-API-KEY", api_key.clone()));
+        }
+        if let Some(token) = &auth_token {
+            headers.push(("Authorization", format!("Bearer {token}")));
+        }
+        let http = HttpClient::new(http, headers)?;
+        Ok(Self {
             endpoint,
             api_key,
             auth_token,
             version,
-        }
+            http,
+        })
     }
 
     /// Create a url based on the base url and a list of paths
@@ -49,7 +92,7 @@ impl CloudAgentPython {
     /// When the url could not be constructed
     pub fn create_url(&self, paths: &[&str]) -> Result<Url> {
         let mut url = Url::parse(&self.endpoint)
-            .map_err(|_| Box::new(Error::UnreachableUrl) as Box<dyn std::error::Error>)?;
+            .map_err(|_| Error::InvalidAgentUrl(self.endpoint.clone()))?;
         url.set_path(&paths.join("/"));
         Ok(url)
     }
@@ -13,7 +13,7 @@ impl BasicMessageModule for CloudAgentPython {
           "content": options.message,
         });
 
-        self.post::<Value>(url, None, Some(body)).await?;
+        self.http.post::<Value>(url, None, Some(body)).await?;
 
         Ok(())
     }
@@ -33,14 +33,22 @@ impl ConnectionModule for CloudAgentPython {
             their_role
         );
 
-        let connections: ConnectionGetAllResponse = self.get(url, Some(query)).await?;
+        let connections: ConnectionGetAllResponse = self.http.get(url, Some(query)).await?;
 
-        Ok(connections.results)
+        // ACA-Py 0.7.3 can not filter on the invitation message id, but does return it
+        Ok(connections
+            .results
+            .into_iter()
+            .filter(|c| {
+                options.invitation_msg_id.is_none()
+                    || c.invitation_msg_id == options.invitation_msg_id
+            })
+            .collect())
     }
 
     async fn get_by_id(&self, id: String) -> Result<Connection> {
         let url = self.create_url(&["connections", &id])?;
-        self.get::<Connection>(url, None).await
+        self.http.get::<Connection>(url, None).await
     }
 
     async fn create_invitation(
@@ -72,7 +80,7 @@ impl ConnectionModule for CloudAgentPython {
             }
             None
         };
-        self.post::<Invitation>(url, Some(query), body).await
+        self.http.post::<Invitation>(url, Some(query), body).await
     }
     async fn receive_invitation(
         &self,
@@ -80,7 +88,8 @@ impl ConnectionModule for CloudAgentPython {
     ) -> Result<Connection> {
         let url = self.create_url(&["connections", "receive-invitation"])?;
 
-        self.post(url, None, Some(serde_json::to_value(invitation)?))
+        self.http
+            .post(url, None, Some(serde_json::to_value(invitation)?))
             .await
     }
 }
@@ -1,31 +1,87 @@
 use crate::agent::CloudAgentPython;
+use crate::fill_query;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use siera_agent::error::Result;
 use siera_agent::modules::credential::{
-    CredentialModule, CredentialOfferOptions, CredentialOfferResponse,
+    CredentialCreateOfferOptions, CredentialGetAllOptions, CredentialModule,
+    CredentialOfferOptions, CredentialOfferResponse,
 };
 
+/// Response from the cloudagent that contains the credential exchanges
+#[derive(Debug, Serialize, Deserialize)]
+struct CredentialGetAllResponse {
+    /// Every credential exchange that matches the filters
+    results: Vec<CredentialOfferResponse>,
+}
+
 #[async_trait]
 impl CredentialModule for CloudAgentPython {
     async fn send_offer(&self, options: CredentialOfferOptions) -> Result<CredentialOfferResponse> {
         let url = self.create_url(&["issue-credential", "send-offer"])?;
 
-        let mut attributes = vec![];
-        for (i, key) in options.keys.iter().enumerate() {
-            let value = &options.values[i];
-            attributes.push(json!({"name": key, "value": value}));
-        }
-
         let body = json!({
           "connection_id": options.connection_id,
           "cred_def_id": options.cred_def_id,
           "credential_preview": {
             "@type": "issue-credential/1.0/credential-preview",
-            "attributes": attributes,
+            "attributes": options.attributes,
+          },
+        });
+
+        self.http.post(url, None, Some(body)).await
+    }
+
+    async fn create_offer(
+        &self,
+        options: CredentialCreateOfferOptions,
+    ) -> Result<CredentialOfferResponse> {
+        let url = self.create_url(&["issue-credential", "create-offer"])?;
+
+        // Nobody is around to issue the credential once the request arrives without a connection
+        let body = json!({
+          "auto_issue": true,
+          "cred_def_id": options.cred_def_id,
+          "credential_preview": {
+            "@type": "issue-credential/1.0/credential-preview",
+            "attributes": options.attributes,
           },
         });
 
-        self.post(url, None, Some(body)).await
+        self.http.post(url, None, Some(body)).await
+    }
+
+    async fn get_all(
+        &self,
+        options: CredentialGetAllOptions,
+    ) -> Result<Vec<CredentialOfferResponse>> {
+        let url = self.create_url(&["issue-credential", "records"])?;
+
+        let query = fill_query!(options, connection_id, role, state, thread_id);
+
+        let exchanges: CredentialGetAllResponse = self.http.get(url, Some(query)).await?;
+
+        Ok(exchanges.results)
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<CredentialOfferResponse> {
+        let url = self.create_url(&["issue-credential", "records", &id])?;
+        self.http.get(url, None).await
+    }
+
+    async fn accept_offer(&self, id: String) -> Result<CredentialOfferResponse> {
+        let url = self.create_url(&["issue-credential", "records", &id, "send-request"])?;
+        self.http.post(url, None, None).await
+    }
+
+    async fn issue(&self, id: String) -> Result<CredentialOfferResponse> {
+        let url = self.create_url(&["issue-credential", "records", &id, "issue"])?;
+        self.http.post(url, None, Some(json!({}))).await
+    }
+
+    async fn store(&self, id: String) -> Result<CredentialOfferResponse> {
+        let url = self.create_url(&["issue-credential", "records", &id, "store"])?;
+        self.http.post(url, None, Some(json!({}))).await
     }
 }
@@ -18,18 +18,18 @@ impl CredentialDefinitionModule for CloudAgentPython {
 
         let body = json!(options);
 
-        self.post(url, None, Some(body)).await
+        self.http.post(url, None, Some(body)).await
     }
 
     async fn get_by_id(&self, id: String) -> Result<CredentialDefinition> {
         let url = self.create_url(&["credential-definitions", &id])?;
-        let response: CredentialDefinitionGetByIdResponse = self.get(url, None).await?;
+        let response: CredentialDefinitionGetByIdResponse = self.http.get(url, None).await?;
 
         Ok(response.credential_definition)
     }
 
     async fn get_all(&self) -> Result<CredentialDefinitionGetAllResponse> {
         let url = self.create_url(&["credential-definitions", "created"])?;
-        self.get(url, None).await
+        self.http.get(url, None).await
     }
 }
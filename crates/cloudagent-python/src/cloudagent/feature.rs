@@ -8,6 +8,6 @@ impl FeatureModule for CloudAgentPython {
     async fn discover_features(&self) -> Result<DiscoverFeaturesResponse> {
         let url = self.create_url(&["discover-features/query"])?;
 
-        self.get::<DiscoverFeaturesResponse>(url, None).await
+        self.http.get::<DiscoverFeaturesResponse>(url, None).await
     }
 }
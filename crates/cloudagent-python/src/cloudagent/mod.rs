@@ -1,3 +1,6 @@
+/// Module for raw requests to an Aries cloudagent Python
+mod api;
+
 /// Module for basic messages sspecific for an Aries cloudagent Python
 mod basic_message;
 
@@ -10,14 +10,15 @@ impl MultitenancyModule for CloudAgentPython {
     async fn create(&self) -> Result<MultitenancyCreateResponse> {
         let url = self.create_url(&["multitenancy", "wallet"])?;
 
-        self.post::<MultitenancyCreateResponse>(url, None, Some(json!({})))
+        self.http
+            .post::<MultitenancyCreateResponse>(url, None, Some(json!({})))
             .await
     }
 
     async fn remove(&self, wallet_id: String) -> Result<()> {
         let url = self.create_url(&["multitenancy", "wallet", &wallet_id, "remove"])?;
 
-        self.post::<Value>(url, None, None).await?;
+        self.http.post::<Value>(url, None, None).await?;
 
         Ok(())
     }
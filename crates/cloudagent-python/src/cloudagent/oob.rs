@@ -4,7 +4,7 @@ use serde_json::json;
 use siera_agent::error::Result;
 use siera_agent::modules::oob::{
     OobConnection, OobConnectionCreateInvitationOptions, OobConnectionCreateInvitationResponse,
-    OobConnectionReceiveInvitationOptions, OobModule,
+    OobConnectionReceiveInvitationOptions, OobModule, OobReceiveOptions,
 };
 
 #[async_trait]
@@ -26,22 +26,34 @@ impl OobModule for CloudAgentPython {
             query.push(("alias", alias.clone()));
         }
 
-        let body = Some(json!({
-            "handshake_protocols": [
-                options.handshake_protocol,
-            ]
-        }));
+        let mut body = json!({ "attachments": options.attachments });
+        if let Some(protocol) = &options.handshake_protocol {
+            body["handshake_protocols"] = json!([protocol.message_type_uri()]);
+        }
+        let body = Some(body);
 
-        self.post::<OobConnectionCreateInvitationResponse>(url, Some(query), body)
+        self.http
+            .post::<OobConnectionCreateInvitationResponse>(url, Some(query), body)
             .await
     }
+
     async fn receive_invitation(
         &self,
         invitation: OobConnectionReceiveInvitationOptions,
+        options: OobReceiveOptions,
     ) -> Result<OobConnection> {
         let url = self.create_url(&["out-of-band", "receive-invitation"])?;
+        let mut query: Vec<(&str, String)> = vec![];
+
+        if options.use_existing_connection {
+            query.push(("use_existing_connection", true.to_string()));
+        }
+        if let Some(alias) = &options.alias {
+            query.push(("alias", alias.clone()));
+        }
 
-        self.post(url, None, Some(serde_json::to_value(invitation)?))
+        self.http
+            .post(url, Some(query), Some(serde_json::to_value(invitation)?))
             .await
     }
 }
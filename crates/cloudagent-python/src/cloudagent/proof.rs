@@ -1,39 +1,163 @@
 use crate::agent::CloudAgentPython;
+use crate::fill_query;
 use async_trait::async_trait;
-use serde_json::{json, Value};
-use siera_agent::error::Result;
-use siera_agent::modules::proof::{ProofModule, ProofRequestOptions, ProofRequestResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use siera_agent::error::{Error, Result};
+use siera_agent::modules::proof::{
+    ProofCreateRequestOptions, ProofGetAllOptions, ProofModule, ProofRequestOptions,
+    ProofRequestResponse,
+};
 use std::collections::BTreeMap;
 
+/// Response from the cloudagent that contains the presentation exchanges
+#[derive(Debug, Serialize, Deserialize)]
+struct ProofGetAllResponse {
+    /// Every presentation exchange that matches the filters
+    results: Vec<ProofRequestResponse>,
+}
+
+/// A credential of the wallet that can be used for a proof request
+#[derive(Debug, Serialize, Deserialize)]
+struct PresentationCredential {
+    /// Information about the credential
+    cred_info: CredentialInfo,
+
+    /// The referents of the proof request that the credential can satisfy
+    presentation_referents: Vec<String>,
+}
+
+/// Information about a credential of the wallet
+#[derive(Debug, Serialize, Deserialize)]
+struct CredentialInfo {
+    /// Id of the credential in the wallet
+    referent: String,
+}
+
 #[async_trait]
 impl ProofModule for CloudAgentPython {
     async fn send_request(&self, options: ProofRequestOptions) -> Result<ProofRequestResponse> {
         let url = self.create_url(&["present-proof", "send-request"])?;
 
-        let mut requested_attributes: BTreeMap<String, Value> = BTreeMap::new();
-        let mut requested_predicates: BTreeMap<String, Value> = BTreeMap::new();
-
-        options.attributes.iter().for_each(|a| {
-            requested_attributes.insert(a.clone(), json!({ "name": a, }));
+        let body = json!({
+          "connection_id": options.connection_id,
+          "proof_request": proof_request(&options.name, &options.attributes, &options.predicates),
         });
 
-        options.predicates.iter().for_each(|p| {
-            requested_predicates.insert(
-                p.0.clone(),
-                json!({ "name": p.0, "p_type": p.1, "p_value": p.2 }),
-            );
+        self.http.post(url, None, Some(body)).await
+    }
+
+    async fn create_request(
+        &self,
+        options: ProofCreateRequestOptions,
+    ) -> Result<ProofRequestResponse> {
+        let url = self.create_url(&["present-proof", "create-request"])?;
+
+        let body = json!({
+          "proof_request": proof_request(&options.name, &options.attributes, &options.predicates),
         });
 
+        self.http.post(url, None, Some(body)).await
+    }
+
+    async fn get_all(&self, options: ProofGetAllOptions) -> Result<Vec<ProofRequestResponse>> {
+        let url = self.create_url(&["present-proof", "records"])?;
+
+        let query = fill_query!(options, connection_id, role, state, thread_id);
+
+        let exchanges: ProofGetAllResponse = self.http.get(url, Some(query)).await?;
+
+        Ok(exchanges.results)
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<ProofRequestResponse> {
+        let url = self.create_url(&["present-proof", "records", &id])?;
+        self.http.get(url, None).await
+    }
+
+    async fn present(&self, id: String) -> Result<ProofRequestResponse> {
+        let exchange = ProofModule::get_by_id(self, id.clone()).await?;
+        let url = self.create_url(&["present-proof", "records", &id, "credentials"])?;
+        let credentials: Vec<PresentationCredential> = self.http.get(url, None).await?;
+
+        // The first credential that can satisfy a referent is used for it
+        let credential_for = |referent: &String| {
+            credentials
+                .iter()
+                .find(|c| c.presentation_referents.contains(referent))
+                .map(|c| c.cred_info.referent.clone())
+        };
+        let referents = |key: &str| {
+            exchange
+                .presentation_request
+                .get(key)
+                .and_then(Value::as_object)
+                .map(|referents| referents.keys().cloned().collect::<Vec<String>>())
+                .unwrap_or_default()
+        };
+
+        let mut unsatisfiable = vec![];
+        let mut requested_attributes = Map::new();
+        for referent in referents("requested_attributes") {
+            match credential_for(&referent) {
+                Some(cred_id) => {
+                    requested_attributes
+                        .insert(referent, json!({ "cred_id": cred_id, "revealed": true }));
+                }
+                None => unsatisfiable.push(referent),
+            }
+        }
+        let mut requested_predicates = Map::new();
+        for referent in referents("requested_predicates") {
+            match credential_for(&referent) {
+                Some(cred_id) => {
+                    requested_predicates.insert(referent, json!({ "cred_id": cred_id }));
+                }
+                None => unsatisfiable.push(referent),
+            }
+        }
+
+        // A partial presentation would only be rejected by the verifier
+        if !unsatisfiable.is_empty() {
+            return Err(Error::UnsatisfiableProofRequest(unsatisfiable));
+        }
+
+        let url = self.create_url(&["present-proof", "records", &id, "send-presentation"])?;
         let body = json!({
-          "connection_id": options.connection_id,
-          "proof_request": {
-            "name": options.name,
-            "version": "1.0",
-            "requested_attributes": requested_attributes,
-            "requested_predicates": requested_predicates,
-          }
+          "requested_attributes": requested_attributes,
+          "requested_predicates": requested_predicates,
+          "self_attested_attributes": {},
         });
 
-        self.post(url, None, Some(body)).await
+        self.http.post(url, None, Some(body)).await
     }
+
+    async fn verify(&self, id: String) -> Result<ProofRequestResponse> {
+        let url = self.create_url(&["present-proof", "records", &id, "verify-presentation"])?;
+        self.http.post(url, None, None).await
+    }
+}
+
+/// Indy proof request with a referent per attribute and predicate
+fn proof_request(name: &str, attributes: &[String], predicates: &[(String, String, i32)]) -> Value {
+    let mut requested_attributes: BTreeMap<String, Value> = BTreeMap::new();
+    let mut requested_predicates: BTreeMap<String, Value> = BTreeMap::new();
+
+    for a in attributes {
+        requested_attributes.insert(a.clone(), json!({ "name": a, }));
+    }
+
+    for p in predicates {
+        requested_predicates.insert(
+            p.0.clone(),
+            json!({ "name": p.0, "p_type": p.1, "p_value": p.2 }),
+        );
+    }
+
+    json!({
+      "name": name,
+      "version": "1.0",
+      "requested_attributes": requested_attributes,
+      "requested_predicates": requested_predicates,
+    })
 }
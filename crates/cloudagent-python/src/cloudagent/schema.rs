@@ -25,16 +25,20 @@ impl SchemaModule for CloudAgentPython {
           "schema_version": options.version
         });
 
-        Ok(self.post::<Response>(url, None, Some(body)).await?.schema)
+        Ok(self
+            .http
+            .post::<Response>(url, None, Some(body))
+            .await?
+            .schema)
     }
 
     async fn get_by_id(&self, id: String) -> Result<Schema> {
         let url = self.create_url(&["schemas", &id])?;
-        Ok(self.get::<Response>(url, None).await?.schema)
+        Ok(self.http.get::<Response>(url, None).await?.schema)
     }
 
     async fn get_all(&self) -> Result<SchemasGetAllResponse> {
         let url = self.create_url(&["schemas", "created"])?;
-        self.get(url, None).await
+        self.http.get(url, None).await
     }
 }
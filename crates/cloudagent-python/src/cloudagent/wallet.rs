@@ -30,7 +30,7 @@ impl WalletModule for CloudAgentPython {
 
         let query = fill_query!(options, did, key_type, method, posture, verkey);
 
-        let did_list: DidListResults = self.get(url, Some(query)).await?;
+        let did_list: DidListResults = self.http.get(url, Some(query)).await?;
 
         Ok(did_list.results)
     }
@@ -43,31 +43,33 @@ impl WalletModule for CloudAgentPython {
             "options": options.options
         });
 
-        self.post(url, None, Some(body)).await
+        self.http.post(url, None, Some(body)).await
     }
 
     async fn rotate_keypair(&self, did: String) -> Result<()> {
         let url = self.create_url(&["wallet", "did", "local", "rotate-keypair"])?;
 
-        self.patch(url, Some(Vec::from([("did", did)]))).await
+        self.http.patch(url, Some(Vec::from([("did", did)]))).await
     }
 
     async fn fetch_public_did(&self) -> Result<Did> {
         let url = self.create_url(&["wallet", "did", "public"])?;
 
-        self.get(url, None).await
+        self.http.get(url, None).await
     }
 
     async fn assign_public_did(&self, did: String) -> Result<Did> {
         let url = self.create_url(&["wallet", "did", "public"])?;
 
-        self.post(url, Some(Vec::from([("did", did)])), None).await
+        self.http
+            .post(url, Some(Vec::from([("did", did)])), None)
+            .await
     }
 
     async fn fetch_did_endpoint(&self, did: String) -> Result<DidEndpoint> {
         let url = self.create_url(&["wallet", "fetch-did-endpoint"])?;
 
-        self.get(url, Some(Vec::from([("did", did)]))).await
+        self.http.get(url, Some(Vec::from([("did", did)]))).await
     }
 
     async fn set_did_endpoint(&self, options: SetDidEndpointOptions) -> Result<()> {
@@ -79,6 +81,6 @@ impl WalletModule for CloudAgentPython {
             "endpoint_type": options.endpoint_type
         });
 
-        self.post(url, None, Some(body)).await
+        self.http.post(url, None, Some(body)).await
     }
 }
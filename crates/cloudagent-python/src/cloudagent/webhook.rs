@@ -1,8 +1,11 @@
 use crate::agent::CloudAgentPython;
 use async_trait::async_trait;
+use native_tls::{Certificate, Identity, TlsConnector};
+use reqwest::Url;
 use siera_agent::error::{Error, Result};
 use siera_agent::modules::webhook::WebhookModule;
-use tungstenite::connect;
+use std::net::TcpStream;
+use tungstenite::{client_tls_with_config, Connector};
 
 #[async_trait]
 impl WebhookModule for CloudAgentPython {
@@ -11,7 +14,7 @@ impl WebhookModule for CloudAgentPython {
         let (uses_tls, stripped_agent_url) = match &self.endpoint {
             s if s.starts_with("http://") => (false, &s[7..]),
             s if s.starts_with("https://") => (true, &s[8..]),
-            s => return Err(Error::InvalidAgentUrl(s.clone()).into()),
+            s => return Err(Error::InvalidAgentUrl(s.clone())),
         };
 
         let scheme = if uses_tls { "wss" } else { "ws" };
@@ -19,13 +22,56 @@ impl WebhookModule for CloudAgentPython {
         let listen_url = format!("{scheme}://{stripped_agent_url}/ws");
         info!({ "message": format!("Listening on {listen_url}") });
 
-        let (mut socket, _response) = connect(listen_url)?;
+        let url =
+            Url::parse(&listen_url).map_err(|_| Error::InvalidAgentUrl(self.endpoint.clone()))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| Error::InvalidAgentUrl(self.endpoint.clone()))?;
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| Error::InvalidAgentUrl(self.endpoint.clone()))?;
+
+        let connector = if uses_tls {
+            Connector::NativeTls(self.tls_connector()?)
+        } else {
+            Connector::Plain
+        };
+
+        let stream = TcpStream::connect((host, port))?;
+        let (mut socket, _response) = client_tls_with_config(url, stream, None, Some(connector))
+            .map_err(|e| e.to_string())?;
 
         // Loop forever, parse message to stdout
         loop {
-            let message = socket.read()?;
+            let message = socket.read().map_err(|e| e.to_string())?;
             let parsed: serde_json::Value = serde_json::from_str(&message.to_string())?;
             on_event(parsed);
         }
     }
 }
+
+impl CloudAgentPython {
+    /// Builds the TLS connector for the websocket from the same options as the http client
+    fn tls_connector(&self) -> Result<TlsConnector> {
+        let tls = &self.http.options.tls;
+        let mut builder = TlsConnector::builder();
+
+        for pem in tls.ca_certificates()? {
+            let certificate =
+                Certificate::from_pem(&pem).map_err(|e| Error::InvalidTlsOptions(e.to_string()))?;
+            builder.add_root_certificate(certificate);
+        }
+
+        if let Some((cert, key)) = tls.client_identity()? {
+            let identity = Identity::from_pkcs8(&cert, &key)
+                .map_err(|e| Error::InvalidTlsOptions(e.to_string()))?;
+            builder.identity(identity);
+        }
+
+        builder
+            .danger_accept_invalid_certs(tls.insecure_skip_verify)
+            .danger_accept_invalid_hostnames(tls.insecure_skip_verify)
+            .build()
+            .map_err(|e| Error::InvalidTlsOptions(e.to_string()))
+    }
+}
@@ -9,9 +9,6 @@ extern crate siera_logger;
 /// All of the submodule functionality of an Aries cloudagent Python
 pub mod cloudagent;
 
-/// Simple web bindings for the module to interact with the cloudagent
-mod web;
-
 /// TODO
 pub mod agent;
 
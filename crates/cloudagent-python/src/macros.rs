@@ -22,7 +22,7 @@ macro_rules! fill_query {
         {
             let mut query = Vec::new();
             $(
-                $options.$field.as_ref().map(|c| query.push((stringify!($field), c.to_owned())));
+                $options.$field.as_ref().map(|c| query.push((stringify!($field), c.to_string())));
             )*
             query
         }
@@ -0,0 +1,166 @@
+//! Tests of the modules against responses of an ACA-Py instance that are replayed from cassettes
+//!
+//! The cassettes are written by hand after the examples of the admin API of ACA-Py 0.7.3, so their ids do not
+//! come from a real agent. Edit them when the requests of a module change
+
+use siera_agent::agent::HttpOptions;
+use siera_agent::cassette::{CassetteMode, CassetteOptions};
+use siera_agent::error::Error;
+use siera_agent::modules::api::{ApiModule, ApiRequestOptions};
+use siera_agent::modules::basic_message::{BasicMessageModule, SendBasicMessageOptions};
+use siera_agent::modules::connection::{
+    ConnectionCreateInvitationOptions, ConnectionGetAllOptions, ConnectionModule, ConnectionState,
+};
+use siera_agent::modules::credential::{
+    CredentialAttribute, CredentialExchangeState, CredentialModule, CredentialOfferOptions,
+};
+use siera_agent::modules::credential_definition::{
+    CredentialDefinitionCreateOptions, CredentialDefinitionModule,
+};
+use siera_agent::modules::schema::{SchemaCreateOptions, SchemaModule};
+use siera_cloudagent_python::agent::{CloudAgentPython, CloudAgentPythonVersion};
+use std::path::Path;
+
+/// Agent that replays the cassette with the name
+fn agent(cassette: &str) -> CloudAgentPython {
+    let endpoint = String::from("http://localhost:8010");
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/cassettes")
+        .join(format!("{cassette}.json"));
+    let http = HttpOptions {
+        cassette: Some(CassetteOptions {
+            path,
+            mode: CassetteMode::Replay,
+        }),
+        ..HttpOptions::default()
+    };
+    CloudAgentPython::new(
+        endpoint,
+        CloudAgentPythonVersion::ZeroSevenThree,
+        None,
+        None,
+        http,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn create_and_list_connections() {
+    let agent = agent("connections");
+    let invitation = agent
+        .create_invitation(ConnectionCreateInvitationOptions {
+            auto_accept: true,
+            alias: Some(String::from("alice")),
+            ..ConnectionCreateInvitationOptions::default()
+        })
+        .await
+        .unwrap();
+    assert!(invitation.invitation_url.contains("c_i="));
+
+    let connections = ConnectionModule::get_all(&agent, ConnectionGetAllOptions::default())
+        .await
+        .unwrap();
+    let connection = connections.iter().find(|c| c.id == invitation.id).unwrap();
+    assert_eq!(connection.alias.as_deref(), Some("alice"));
+
+    let connection = ConnectionModule::get_by_id(&agent, invitation.id.clone())
+        .await
+        .unwrap();
+    assert_eq!(connection.state, ConnectionState::Invitation);
+}
+
+#[tokio::test]
+async fn unknown_connection_is_not_found() {
+    let agent = agent("unknown_connection");
+    let error = ConnectionModule::get_by_id(&agent, String::from("unknown"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::UrlDoesNotExist(_)));
+}
+
+#[tokio::test]
+async fn create_schema_and_credential_definition() {
+    let agent = agent("credential_definition");
+    let schema = SchemaModule::create(
+        &agent,
+        SchemaCreateOptions {
+            name: String::from("passport"),
+            version: String::from("1.0"),
+            attributes: vec![String::from("name"), String::from("age")],
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(schema.attr_names.len(), 2);
+
+    let created = CredentialDefinitionModule::create(
+        &agent,
+        CredentialDefinitionCreateOptions {
+            schema_id: schema.id.clone(),
+            tag: String::from("default"),
+            support_revocation: false,
+            revocation_registry_size: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let definition =
+        CredentialDefinitionModule::get_by_id(&agent, created.credential_definition_id.clone())
+            .await
+            .unwrap();
+    assert_eq!(definition.id, created.credential_definition_id);
+    assert_eq!(definition.tag, "default");
+}
+
+#[tokio::test]
+async fn offer_credential() {
+    let agent = agent("credential_offer");
+    let offer = agent
+        .send_offer(CredentialOfferOptions {
+            connection_id: String::from("3fa85f64-5717-4562-b3fc-2c963f66afa6"),
+            cred_def_id: String::from("WgWxqztrNooG92RXvxSTWv:3:CL:20:default"),
+            attributes: vec![
+                CredentialAttribute::new("name", "Alice"),
+                CredentialAttribute::new("age", "28"),
+            ],
+        })
+        .await
+        .unwrap();
+    assert_eq!(offer.state, CredentialExchangeState::OfferSent);
+    assert_eq!(offer.connection_id, "3fa85f64-5717-4562-b3fc-2c963f66afa6");
+}
+
+#[tokio::test]
+async fn send_message() {
+    let agent = agent("message");
+    agent
+        .send_message(SendBasicMessageOptions {
+            connection_id: String::from("3fa85f64-5717-4562-b3fc-2c963f66afa6"),
+            message: String::from("Hello Alice"),
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn raw_request() {
+    let agent = agent("api");
+    let status = agent
+        .request(ApiRequestOptions {
+            method: String::from("GET"),
+            path: String::from("/status/ready"),
+            query: vec![],
+            body: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(status["ready"], true);
+}
+
+#[tokio::test]
+async fn request_that_is_not_recorded() {
+    let agent = agent("api");
+    let error = SchemaModule::get_all(&agent).await.unwrap_err();
+    assert!(matches!(error, Error::UnrecordedRequest(_)));
+}
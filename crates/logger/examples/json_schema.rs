@@ -0,0 +1,10 @@
+//! Print the JSON Schema of the output envelope
+//!
+//! `cargo run --package siera-logger --example json_schema > docs/output.schema.json`
+
+fn main() {
+    println!(
+        "{}",
+        siera_logger::pretty_stringify_obj(siera_logger::envelope::json_schema())
+    );
+}
@@ -0,0 +1,172 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Version of the JSON output contract. Bumped whenever a field of the [`Envelope`] is removed
+/// or changes meaning
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Outcome of a command
+#[derive(Serialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// The command finished successfully
+    Success,
+
+    /// The command failed and `error` describes why
+    Error,
+
+    /// An event of a long running command, e.g. a received webhook. Every event is a separate
+    /// document
+    Event,
+}
+
+/// Error of a failed command
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct EnvelopeError {
+    /// Human readable description of the error
+    pub message: String,
+
+    /// Category of the error, e.g. `not_found` or `connectivity`
+    pub category: String,
+
+    /// Exit code of the process
+    pub exit_code: i32,
+
+    /// The request to the agent that failed and its response, when the error came from the agent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+}
+
+/// The single document that is written to stdout for every command in JSON mode
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct Envelope {
+    /// Version of this contract
+    pub schema_version: u32,
+
+    /// The invoked subcommands, e.g. `connection list`
+    pub command: String,
+
+    /// Outcome of the command
+    pub status: Status,
+
+    /// Result of the command. Every command documents its own keys, e.g. `connections` for
+    /// `connection list`. A key that is reported more than once holds an array of every value.
+    /// `null` when the command failed
+    pub data: Option<Map<String, Value>>,
+
+    /// Why the command failed. `null` when it succeeded
+    pub error: Option<EnvelopeError>,
+}
+
+/// Set the command that is reported in the envelope. This starts a new command, so its envelope
+/// can be emitted
+///
+/// # Panics
+///
+/// When the logger state is poisoned
+pub fn set_command(command: impl Into<String>) {
+    let mut state = crate::STATE.write().unwrap();
+    state.command = command.into();
+    state.finished = false;
+}
+
+/// Add the fields of a logged object to the data of the envelope. Values that are not an object
+/// are stored under `value`
+///
+/// # Panics
+///
+/// When the logger state is poisoned
+pub fn collect(value: Value) {
+    let mut state = crate::STATE.write().unwrap();
+    match value {
+        Value::Object(object) => state.data.extend(object),
+        Value::Null => (),
+        v => state.data.push((String::from("value"), v)),
+    }
+}
+
+/// The data of the envelope. A key that was logged once holds its value and a key that was
+/// logged more than once holds every value, in the order they were logged, so none is lost
+fn merge(data: Vec<(String, Value)>) -> Map<String, Value> {
+    let mut values: Map<String, Value> = Map::new();
+    let mut repeated: Vec<String> = vec![];
+    for (key, value) in data {
+        match values.get_mut(&key) {
+            Some(Value::Array(items)) if repeated.contains(&key) => items.push(value),
+            Some(existing) => {
+                *existing = Value::Array(vec![existing.take(), value]);
+                repeated.push(key);
+            }
+            None => {
+                values.insert(key, value);
+            }
+        }
+    }
+    values
+}
+
+/// Write the envelope of the command to stdout, when the logger outputs JSON. The data that was
+/// collected so far is only included when the command succeeded. The envelope is only written
+/// once per command
+///
+/// # Panics
+///
+/// When the logger state is poisoned
+pub fn finish(error: Option<EnvelopeError>) {
+    let mut state = crate::STATE.write().unwrap();
+    if !state.should_output_json || state.finished {
+        return;
+    }
+    state.finished = true;
+    let data = merge(std::mem::take(&mut state.data));
+    let envelope = match error {
+        Some(error) => Envelope {
+            schema_version: SCHEMA_VERSION,
+            command: state.command.clone(),
+            status: Status::Error,
+            data: None,
+            error: Some(error),
+        },
+        None => Envelope {
+            schema_version: SCHEMA_VERSION,
+            command: state.command.clone(),
+            status: Status::Success,
+            data: Some(data),
+            error: None,
+        },
+    };
+    drop(state);
+    println!("{}", crate::pretty_stringify_obj(envelope));
+}
+
+/// Write an event of a long running command to stdout as its own envelope, on a single line
+///
+/// # Panics
+///
+/// When the logger state is poisoned or the event could not be serialized
+pub fn event(value: Value) {
+    let command = crate::STATE.read().unwrap().command.clone();
+    let data = match value {
+        Value::Object(object) => object,
+        v => Map::from_iter([(String::from("value"), v)]),
+    };
+    let envelope = Envelope {
+        schema_version: SCHEMA_VERSION,
+        command,
+        status: Status::Event,
+        data: Some(data),
+        error: None,
+    };
+    println!("{}", serde_json::to_string(&envelope).unwrap());
+}
+
+/// JSON Schema of the [`Envelope`]
+///
+/// # Panics
+///
+/// When the schema could not be serialized
+#[must_use]
+pub fn json_schema() -> Value {
+    serde_json::to_value(schemars::schema_for!(Envelope)).unwrap()
+}
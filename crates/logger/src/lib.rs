@@ -8,6 +8,9 @@ use std::sync::RwLock;
 
 pub extern crate serde_json;
 
+/// Allows the exported macros to be used inside of this crate
+extern crate self as siera_logger;
+
 #[macro_use]
 extern crate lazy_static;
 
@@ -15,6 +18,14 @@ extern crate lazy_static;
 #[macro_use]
 pub mod macros;
 
+/// Output formats for the result of a command
+pub mod output;
+
+/// The versioned document that is emitted for every command in JSON mode
+pub mod envelope;
+
+pub use output::{output, OutputFormat};
+
 /// Loglevel in the cli
 #[derive(PartialEq, Eq, PartialOrd, Default, Debug)]
 pub enum LogLevel {
@@ -87,24 +98,45 @@ pub struct LoggerState {
     /// Whether the output should be logged as json
     pub should_output_json: bool,
 
+    /// Format in which the result of a command is printed
+    pub output: OutputFormat,
+
+    /// Fields of the result that are printed. All fields are printed when this is empty
+    pub fields: Vec<String>,
+
     /// The loglevel at the cli
     pub level: LogLevel,
+
+    /// The invoked subcommands, reported in the JSON envelope
+    pub command: String,
+
+    /// Result of the command that is collected for the JSON envelope, in the order it was logged
+    pub data: Vec<(String, serde_json::Value)>,
+
+    /// Whether the envelope of the current command is already emitted
+    pub finished: bool,
 }
 
 impl LoggerState {
     /// Initialize the logger state
     #[must_use]
-    pub const fn new(
+    pub fn new(
         init: bool,
         should_copy_relevant: bool,
-        should_output_json: bool,
+        output: OutputFormat,
+        fields: Vec<String>,
         log_level: LogLevel,
     ) -> Self {
         Self {
             init,
             should_copy_relevant,
-            should_output_json,
+            should_output_json: matches!(output, OutputFormat::Json),
+            output,
+            fields,
             level: log_level,
+            command: String::new(),
+            data: vec![],
+            finished: false,
         }
     }
 }
@@ -119,7 +151,7 @@ lazy_static! {
 /// # Panics
 ///
 /// When the logger is already initialized
-pub fn init(level: LogLevel, should_copy: bool, should_output_json: bool) {
+pub fn init(level: LogLevel, should_copy: bool, output: OutputFormat, fields: Vec<String>) {
     assert!(
         !STATE.read().unwrap().init,
         "Logger should only be initialized once!"
@@ -129,7 +161,9 @@ pub fn init(level: LogLevel, should_copy: bool, should_output_json: bool) {
     state.init = true;
     state.level = level;
     state.should_copy_relevant = should_copy;
-    state.should_output_json = should_output_json;
+    state.should_output_json = output == OutputFormat::Json;
+    state.output = output;
+    state.fields = fields;
 }
 
 /// Prettify any string that implements Serialize
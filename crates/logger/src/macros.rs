@@ -28,20 +28,22 @@ macro_rules! elog {
 #[macro_export]
 macro_rules! internal_log {
     ($level:expr, $($arg:tt)+) => {
-        if ::siera_logger::STATE.read().unwrap().level >= $level {
+        if ::siera_logger::STATE.read().unwrap().should_output_json && $level == ::siera_logger::LogLevel::None {
+            // The result of the command is emitted once, in the envelope
+            ::siera_logger::envelope::collect($crate::serde_json::json!($($arg)+));
+        } else if ::siera_logger::STATE.read().unwrap().level >= $level {
             let value: $crate::serde_json::Value = $crate::serde_json::json!($($arg)+);
             if ::siera_logger::STATE.read().unwrap().should_output_json {
                 let value = match value {
                     $crate::serde_json::Value::Object(o) => {
                         let mut o = o.clone();
-                        if $level != ::siera_logger::LogLevel::None {
-                            o.insert("level".to_string(), $crate::serde_json::Value::String($level.to_string_without_color()));
-                        }
+                        o.insert("level".to_string(), $crate::serde_json::Value::String($level.to_string_without_color()));
                         $crate::serde_json::Value::Object(o)
                     },
                     v => v,
                 };
-                println!("{}", $crate::serde_json::to_string_pretty(&value).unwrap());
+                // stdout is reserved for the envelope
+                eprintln!("{}", $crate::serde_json::to_string(&value).unwrap());
             } else {
                 match value {
                     $crate::serde_json::Value::Object(o) => {
@@ -72,7 +74,11 @@ macro_rules! internal_log {
                                     }
                                 }
                             };
-                            println!("{s}");
+                            if $level != ::siera_logger::LogLevel::None && ::siera_logger::STATE.read().unwrap().output.reserves_stdout() {
+                                eprintln!("{s}");
+                            } else {
+                                println!("{s}");
+                            }
                         }
                     },
                     _ => (),
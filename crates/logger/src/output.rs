@@ -0,0 +1,245 @@
+use crate::LogLevel;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt::{Display, Formatter};
+
+/// Format in which the result of a command is printed
+#[derive(PartialEq, Eq, Default, Debug, Clone, Copy)]
+pub enum OutputFormat {
+    /// Colored log lines. Objects are printed as pretty JSON
+    #[default]
+    Plain,
+
+    /// A single JSON document
+    Json,
+
+    /// An aligned table with a column per field
+    Table,
+
+    /// A single YAML document
+    Yaml,
+
+    /// Comma separated values with a header row
+    Csv,
+}
+
+impl OutputFormat {
+    /// Every output format, as it is supplied on the cli
+    pub const VALUES: &'static [&'static str] = &["plain", "json", "table", "yaml", "csv"];
+
+    /// The name of the format as it is supplied on the cli
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Plain => "plain",
+            Self::Json => "json",
+            Self::Table => "table",
+            Self::Yaml => "yaml",
+            Self::Csv => "csv",
+        }
+    }
+
+    /// Whether stdout is reserved for the result of the command, so log lines are written to
+    /// stderr instead
+    #[must_use]
+    pub const fn reserves_stdout(&self) -> bool {
+        matches!(self, Self::Yaml | Self::Csv)
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<&str> for OutputFormat {
+    fn from(s: &str) -> Self {
+        match s {
+            "json" => Self::Json,
+            "table" => Self::Table,
+            "yaml" => Self::Yaml,
+            "csv" => Self::Csv,
+            _ => Self::Plain,
+        }
+    }
+}
+
+/// Print the result of a command in the output format and with the fields of the logger
+///
+/// `key` names the result in the plain and JSON output and is the column of a list of scalars
+/// in the other formats. Like every log line, nothing is printed with `--quiet`.
+///
+/// # Errors
+///
+/// When the value could not be written as YAML
+///
+/// # Panics
+///
+/// When the logger state is poisoned
+pub fn output(key: &str, value: impl Serialize) -> Result<(), serde_yaml::Error> {
+    let state = crate::STATE.read().unwrap();
+    let value = select_fields(
+        serde_json::to_value(value).unwrap_or(Value::Null),
+        &state.fields,
+    );
+    let format = state.output;
+    let quiet = state.level < LogLevel::None;
+    drop(state);
+
+    match format {
+        OutputFormat::Plain => match &value {
+            Value::Array(items) if items.iter().all(is_scalar) => {
+                for item in items {
+                    log!({ key: item });
+                }
+            }
+            _ => {
+                log!({ key: value });
+            }
+        },
+        OutputFormat::Json => {
+            log!({ key: value });
+        }
+        _ if quiet => (),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&value)?),
+        OutputFormat::Table => print!("{}", render_table(&rows(key, value))),
+        OutputFormat::Csv => print!("{}", render_csv(&rows(key, value))),
+    }
+    Ok(())
+}
+
+/// Keep only the selected fields of every record. Nested fields are selected with a dot, e.g.
+/// `invitation.label`
+fn select_fields(value: Value, fields: &[String]) -> Value {
+    if fields.is_empty() {
+        return value;
+    }
+    match value {
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| select_fields(item, fields))
+                .collect(),
+        ),
+        Value::Object(object) => {
+            let object = Value::Object(object);
+            let selected: Map<String, Value> = fields
+                .iter()
+                .map(|field| {
+                    let pointer = format!("/{}", field.replace('.', "/"));
+                    let value = object.pointer(&pointer).cloned().unwrap_or(Value::Null);
+                    (field.clone(), value)
+                })
+                .collect();
+            Value::Object(selected)
+        }
+        scalar => scalar,
+    }
+}
+
+/// Whether the value is not an object or an array
+const fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::Object(_) | Value::Array(_))
+}
+
+/// Header and rows of a value. Every object is a row and every field a column
+fn rows(key: &str, value: Value) -> (Vec<String>, Vec<Vec<String>>) {
+    let records = match value {
+        Value::Array(items) => items,
+        Value::Null => vec![],
+        v => vec![v],
+    };
+
+    let mut header: Vec<String> = vec![];
+    for record in &records {
+        match record {
+            Value::Object(object) => object.keys().for_each(|k| {
+                if !header.contains(k) {
+                    header.push(k.clone());
+                }
+            }),
+            _ if !header.iter().any(|h| h == key) => header.push(key.to_owned()),
+            _ => (),
+        }
+    }
+
+    let rows = records
+        .iter()
+        .map(|record| {
+            header
+                .iter()
+                .map(|column| match record {
+                    Value::Object(object) => object.get(column).map(cell).unwrap_or_default(),
+                    scalar if column == key => cell(scalar),
+                    _ => String::new(),
+                })
+                .collect()
+        })
+        .collect();
+
+    (header, rows)
+}
+
+/// Text of a single cell. Nested values are written as compact JSON
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+/// Render the rows as a table with aligned columns
+fn render_table((header, rows): &(Vec<String>, Vec<Vec<String>>)) -> String {
+    let widths: Vec<usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(column.chars().count()))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let line = |cells: &[String]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        format!("{}\n", line.join("  ").trim_end())
+    };
+
+    let header: Vec<String> = header.iter().map(|h| h.to_uppercase()).collect();
+    let mut table = line(&header);
+    for row in rows {
+        table.push_str(&line(row));
+    }
+    table
+}
+
+/// Render the rows as CSV with a header row
+fn render_csv((header, rows): &(Vec<String>, Vec<Vec<String>>)) -> String {
+    let line = |cells: &[String]| {
+        let cells: Vec<String> = cells.iter().map(|c| escape_csv(c)).collect();
+        format!("{}\n", cells.join(","))
+    };
+
+    let mut csv = line(header);
+    for row in rows {
+        csv.push_str(&line(row));
+    }
+    csv
+}
+
+/// Quote a CSV cell when it contains a separator, quote or newline
+fn escape_csv(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_owned()
+    }
+}
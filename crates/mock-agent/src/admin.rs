@@ -0,0 +1,716 @@
+use crate::didcomm;
+use crate::server::ServerState;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use base64::engine::general_purpose;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use siera_agent::error::Error;
+use siera_agent::modules::basic_message::{BasicMessageModule, SendBasicMessageOptions};
+use siera_agent::modules::connection::{
+    Connection, ConnectionCreateInvitationOptions, ConnectionGetAllOptions, ConnectionModule,
+    ConnectionProtocol, ConnectionReceiveInvitationOptions, Invitation, Rfc23State,
+};
+use siera_agent::modules::credential::{
+    CredentialAttribute, CredentialCreateOfferOptions, CredentialGetAllOptions, CredentialModule,
+    CredentialOfferOptions, CredentialOfferResponse,
+};
+use siera_agent::modules::credential_definition::{
+    CredentialDefinitionCreateOptions, CredentialDefinitionCreateResponse,
+    CredentialDefinitionGetAllResponse, CredentialDefinitionGetByIdResponse,
+    CredentialDefinitionModule,
+};
+use siera_agent::modules::feature::{DiscoverFeaturesResponse, FeatureModule};
+use siera_agent::modules::multitenancy::{MultitenancyCreateResponse, MultitenancyModule};
+use siera_agent::modules::oob::{
+    OobAttachment, OobConnection, OobConnectionCreateInvitationOptions,
+    OobConnectionCreateInvitationResponse, OobModule, OobReceiveOptions,
+};
+use siera_agent::modules::proof::{
+    ProofCreateRequestOptions, ProofGetAllOptions, ProofModule, ProofRequestOptions,
+    ProofRequestResponse,
+};
+use siera_agent::modules::schema::{
+    Schema, SchemaCreateOptions, SchemaModule, SchemasGetAllResponse,
+};
+use siera_agent::modules::wallet::{
+    CreateLocalDidOptions, Did, DidEndpoint, SetDidEndpointOptions, WalletModule,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Shared state as it is extracted by the handlers
+type AppState = State<Arc<ServerState>>;
+
+/// Result of a handler. Errors of the agent are returned with the status code and body the
+/// agent gave them
+pub(crate) type ApiResult<T> = std::result::Result<T, ApiError>;
+
+/// Error of the agent, returned as the response to a request
+pub(crate) struct ApiError(Error);
+
+impl From<Error> for ApiError {
+    fn from(e: Error) -> Self {
+        Self(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self.0.http_context() {
+            Some(context) => (
+                context.status.unwrap_or(500),
+                match &context.body {
+                    Some(Value::String(body)) => body.clone(),
+                    Some(body) => body.to_string(),
+                    None => self.0.message(),
+                },
+            ),
+            None => (500, self.0.message()),
+        };
+        let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, body).into_response()
+    }
+}
+
+/// Query parameters when an invitation is created
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct InvitationQuery {
+    /// Alias of the connection
+    alias: Option<String>,
+
+    /// Whether the connection is accepted automatically
+    #[serde(default)]
+    auto_accept: bool,
+
+    /// Whether the invitation can be used more than once
+    #[serde(default)]
+    multi_use: bool,
+}
+
+/// Query parameter that selects a DID
+#[derive(Debug, Deserialize)]
+pub(crate) struct DidQuery {
+    /// The DID
+    did: String,
+}
+
+/// Information about the agent
+pub(crate) async fn status(State(state): AppState) -> Json<Value> {
+    Json(json!({
+        "label": state.agent.label(),
+        "version": env!("CARGO_PKG_VERSION"),
+        "endpoint": state.endpoint,
+    }))
+}
+
+/// Every connection that matches the filters
+pub(crate) async fn connections(
+    State(state): AppState,
+    Query(options): Query<ConnectionGetAllOptions>,
+) -> ApiResult<Json<Value>> {
+    // ACA-Py 0.7.3 can not filter on the invitation message id and ignores it
+    let options = ConnectionGetAllOptions {
+        invitation_msg_id: None,
+        ..options
+    };
+    let connections = ConnectionModule::get_all(&state.agent, options).await?;
+    Ok(Json(json!({ "results": connections })))
+}
+
+/// A single connection
+pub(crate) async fn connection(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> ApiResult<Json<Connection>> {
+    Ok(Json(ConnectionModule::get_by_id(&state.agent, id).await?))
+}
+
+/// Create a connections 1.0 invitation
+pub(crate) async fn create_invitation(
+    State(state): AppState,
+    Query(query): Query<InvitationQuery>,
+) -> ApiResult<Json<Invitation>> {
+    let mut invitation = ConnectionModule::create_invitation(
+        &state.agent,
+        ConnectionCreateInvitationOptions {
+            auto_accept: query.auto_accept,
+            multi_use: query.multi_use,
+            alias: query.alias,
+            ..ConnectionCreateInvitationOptions::default()
+        },
+    )
+    .await?;
+    invitation.invitation_url = invitation_url(&state, "c_i", &invitation.invitation);
+    Ok(Json(invitation))
+}
+
+/// Receive a connections 1.0 invitation and connect with the agent that created it
+pub(crate) async fn receive_invitation(
+    State(state): AppState,
+    Json(invitation): Json<ConnectionReceiveInvitationOptions>,
+) -> ApiResult<Json<Connection>> {
+    let endpoint = invitation.service_endpoint.clone();
+    let key = invitation
+        .recipient_keys
+        .as_ref()
+        .and_then(|keys| keys.first().cloned());
+    let connection = ConnectionModule::receive_invitation(&state.agent, invitation).await?;
+
+    match (endpoint, key) {
+        (Some(endpoint), Some(key)) => Ok(Json(
+            didcomm::connect(&state, &connection.id, &endpoint, &key)
+                .await
+                .unwrap_or(connection),
+        )),
+        _ => Ok(Json(connection)),
+    }
+}
+
+/// Body when an out of band invitation is created
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct OobInvitationBody {
+    /// Message type uris of the handshake protocols
+    #[serde(default)]
+    handshake_protocols: Vec<String>,
+
+    /// Exchanges that are attached to the invitation
+    #[serde(default)]
+    attachments: Vec<OobAttachment>,
+}
+
+/// Create an out of band invitation
+pub(crate) async fn create_oob_invitation(
+    State(state): AppState,
+    Query(query): Query<InvitationQuery>,
+    body: Option<Json<OobInvitationBody>>,
+) -> ApiResult<Json<OobConnectionCreateInvitationResponse>> {
+    let body = body.map(|Json(body)| body).unwrap_or_default();
+    // An invitation without a handshake is only created when it has attachments
+    let handshake_protocol = match body.handshake_protocols.first() {
+        Some(uri) => Some(ConnectionProtocol::from(uri.as_str())),
+        None if body.attachments.is_empty() => Some(ConnectionProtocol::default()),
+        None => None,
+    };
+    let mut invitation = OobModule::create_invitation(
+        &state.agent,
+        OobConnectionCreateInvitationOptions {
+            auto_accept: query.auto_accept,
+            handshake_protocol,
+            multi_use: query.multi_use,
+            alias: query.alias,
+            attachments: body.attachments,
+            ..OobConnectionCreateInvitationOptions::default()
+        },
+    )
+    .await?;
+    invitation.invitation_url = invitation_url(&state, "oob", &invitation.invitation);
+    Ok(Json(invitation))
+}
+
+/// Receive an out of band invitation and connect with the agent that created it, unless an
+/// existing connection with it is reused
+pub(crate) async fn receive_oob_invitation(
+    State(state): AppState,
+    Query(options): Query<OobReceiveOptions>,
+    Json(invitation): Json<Value>,
+) -> ApiResult<Json<OobConnection>> {
+    let service = invitation.pointer("/services/0").cloned();
+    let mut record = OobModule::receive_invitation(&state.agent, invitation, options).await?;
+    if record.rfc23_state == Rfc23State::Completed {
+        return Ok(Json(record));
+    }
+
+    let endpoint = service
+        .as_ref()
+        .and_then(|s| s.get("serviceEndpoint"))
+        .and_then(Value::as_str);
+    let key = service
+        .as_ref()
+        .and_then(|s| s.pointer("/recipientKeys/0"))
+        .and_then(Value::as_str);
+    if let (Some(endpoint), Some(key)) = (endpoint, key) {
+        if let Some(connection) =
+            didcomm::connect(&state, &record.connection_id, endpoint, key).await
+        {
+            record.state = connection.state;
+            record.their_did = connection.their_did;
+            record.their_label = connection.their_label;
+            record.rfc23_state = Rfc23State::Completed;
+        }
+    }
+    Ok(Json(record))
+}
+
+/// Body when a basic message is sent
+#[derive(Debug, Deserialize)]
+pub(crate) struct SendMessageBody {
+    /// Text of the message
+    content: String,
+}
+
+/// Send a basic message over the connection
+pub(crate) async fn send_message(
+    State(state): AppState,
+    Path(connection_id): Path<String>,
+    Json(body): Json<SendMessageBody>,
+) -> ApiResult<Json<Value>> {
+    state
+        .agent
+        .send_message(SendBasicMessageOptions {
+            connection_id: connection_id.clone(),
+            message: body.content.clone(),
+        })
+        .await?;
+    didcomm::send_message(&state, &connection_id, body.content).await;
+    Ok(Json(json!({})))
+}
+
+/// Body when a schema is created
+#[derive(Debug, Deserialize)]
+pub(crate) struct CreateSchemaBody {
+    /// Name of the schema
+    schema_name: String,
+
+    /// Version of the schema
+    schema_version: String,
+
+    /// Names of the attributes
+    attributes: Vec<String>,
+}
+
+/// Create a schema
+pub(crate) async fn create_schema(
+    State(state): AppState,
+    Json(body): Json<CreateSchemaBody>,
+) -> ApiResult<Json<Value>> {
+    let schema = SchemaModule::create(
+        &state.agent,
+        SchemaCreateOptions {
+            name: body.schema_name,
+            version: body.schema_version,
+            attributes: body.attributes,
+        },
+    )
+    .await?;
+    Ok(Json(
+        json!({ "schema_id": schema.id, "schema": ledger_schema(&schema) }),
+    ))
+}
+
+/// Ids of every schema that was created
+pub(crate) async fn schemas(State(state): AppState) -> ApiResult<Json<SchemasGetAllResponse>> {
+    Ok(Json(SchemaModule::get_all(&state.agent).await?))
+}
+
+/// A single schema
+pub(crate) async fn schema(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> ApiResult<Json<Value>> {
+    let schema = SchemaModule::get_by_id(&state.agent, id).await?;
+    Ok(Json(json!({ "schema": ledger_schema(&schema) })))
+}
+
+/// The schema as it is stored on the ledger. `Schema` only reads these field names
+fn ledger_schema(schema: &Schema) -> Value {
+    json!({
+        "ver": schema.ver,
+        "id": schema.id,
+        "name": schema.name,
+        "version": schema.version,
+        "attrNames": schema.attr_names,
+        "seqNo": schema.seq_no,
+    })
+}
+
+/// Create a credential definition
+pub(crate) async fn create_credential_definition(
+    State(state): AppState,
+    Json(options): Json<CredentialDefinitionCreateOptions>,
+) -> ApiResult<Json<CredentialDefinitionCreateResponse>> {
+    Ok(Json(
+        CredentialDefinitionModule::create(&state.agent, options).await?,
+    ))
+}
+
+/// Ids of every credential definition that was created
+pub(crate) async fn credential_definitions(
+    State(state): AppState,
+) -> ApiResult<Json<CredentialDefinitionGetAllResponse>> {
+    Ok(Json(
+        CredentialDefinitionModule::get_all(&state.agent).await?,
+    ))
+}
+
+/// A single credential definition
+pub(crate) async fn credential_definition(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> ApiResult<Json<CredentialDefinitionGetByIdResponse>> {
+    let credential_definition = CredentialDefinitionModule::get_by_id(&state.agent, id).await?;
+    Ok(Json(CredentialDefinitionGetByIdResponse {
+        credential_definition,
+    }))
+}
+
+/// Preview of the credential that is offered
+#[derive(Debug, Deserialize)]
+pub(crate) struct CredentialPreview {
+    /// Attributes of the credential
+    attributes: Vec<CredentialAttribute>,
+}
+
+/// Body when a credential is offered
+#[derive(Debug, Deserialize)]
+pub(crate) struct SendOfferBody {
+    /// Connection the credential is offered over
+    connection_id: String,
+
+    /// Credential definition of the credential
+    cred_def_id: String,
+
+    /// Preview of the credential
+    credential_preview: CredentialPreview,
+}
+
+/// Offer a credential over a connection. The offer is returned before the other agent issued
+/// it, like aca-py does
+pub(crate) async fn send_offer(
+    State(state): AppState,
+    Json(body): Json<SendOfferBody>,
+) -> ApiResult<Json<CredentialOfferResponse>> {
+    let exchange = state
+        .agent
+        .send_offer(CredentialOfferOptions {
+            connection_id: body.connection_id,
+            cred_def_id: body.cred_def_id,
+            attributes: body.credential_preview.attributes,
+        })
+        .await?;
+    didcomm::offer_credential(&state, &exchange).await;
+    Ok(Json(exchange))
+}
+
+/// Body when a credential offer is created without a connection
+#[derive(Debug, Deserialize)]
+pub(crate) struct CreateOfferBody {
+    /// Credential definition of the credential
+    cred_def_id: String,
+
+    /// Preview of the credential
+    credential_preview: CredentialPreview,
+}
+
+/// Create a credential offer that is not sent, e.g. to attach it to an invitation
+pub(crate) async fn create_offer(
+    State(state): AppState,
+    Json(body): Json<CreateOfferBody>,
+) -> ApiResult<Json<CredentialOfferResponse>> {
+    let exchange = state
+        .agent
+        .create_offer(CredentialCreateOfferOptions {
+            cred_def_id: body.cred_def_id,
+            attributes: body.credential_preview.attributes,
+        })
+        .await?;
+    Ok(Json(exchange))
+}
+
+/// Every credential exchange
+pub(crate) async fn credential_exchanges(
+    State(state): AppState,
+    Query(options): Query<CredentialGetAllOptions>,
+) -> ApiResult<Json<Value>> {
+    let exchanges = CredentialModule::get_all(&state.agent, options).await?;
+    Ok(Json(json!({ "results": exchanges })))
+}
+
+/// A single credential exchange
+pub(crate) async fn credential_exchange(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> ApiResult<Json<CredentialOfferResponse>> {
+    Ok(Json(CredentialModule::get_by_id(&state.agent, id).await?))
+}
+
+/// Request the credential of a received offer
+pub(crate) async fn accept_offer(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> ApiResult<Json<CredentialOfferResponse>> {
+    Ok(Json(state.agent.accept_offer(id).await?))
+}
+
+/// Issue the credential of a received request
+pub(crate) async fn issue(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> ApiResult<Json<CredentialOfferResponse>> {
+    Ok(Json(state.agent.issue(id).await?))
+}
+
+/// Store a received credential
+pub(crate) async fn store(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> ApiResult<Json<CredentialOfferResponse>> {
+    Ok(Json(state.agent.store(id).await?))
+}
+
+/// A requested attribute of a proof request
+#[derive(Debug, Deserialize)]
+pub(crate) struct RequestedAttribute {
+    /// Name of the attribute
+    name: String,
+}
+
+/// A requested predicate of a proof request
+#[derive(Debug, Deserialize)]
+pub(crate) struct RequestedPredicate {
+    /// Name of the attribute
+    name: String,
+
+    /// Operator of the predicate, e.g. `>=`
+    p_type: String,
+
+    /// Value the attribute is compared with
+    p_value: i32,
+}
+
+/// Proof request as it is sent by the client
+#[derive(Debug, Deserialize)]
+pub(crate) struct ProofRequestBody {
+    /// Name of the proof request
+    name: String,
+
+    /// Attributes that are requested, keyed by their referent
+    #[serde(default)]
+    requested_attributes: BTreeMap<String, RequestedAttribute>,
+
+    /// Predicates that are requested, keyed by their referent
+    #[serde(default)]
+    requested_predicates: BTreeMap<String, RequestedPredicate>,
+}
+
+impl ProofRequestBody {
+    /// Names of the requested attributes and the requested predicates
+    fn into_requested(self) -> (Vec<String>, Vec<(String, String, i32)>) {
+        let attributes = self
+            .requested_attributes
+            .into_values()
+            .map(|a| a.name)
+            .collect();
+        let predicates = self
+            .requested_predicates
+            .into_values()
+            .map(|p| (p.name, p.p_type, p.p_value))
+            .collect();
+        (attributes, predicates)
+    }
+}
+
+/// Body when a proof is requested
+#[derive(Debug, Deserialize)]
+pub(crate) struct SendRequestBody {
+    /// Connection the proof is requested over
+    connection_id: String,
+
+    /// The proof request
+    proof_request: ProofRequestBody,
+}
+
+/// Request a proof over a connection. The request is returned before the other agent presented
+/// the proof, like aca-py does
+pub(crate) async fn send_request(
+    State(state): AppState,
+    Json(body): Json<SendRequestBody>,
+) -> ApiResult<Json<ProofRequestResponse>> {
+    let name = body.proof_request.name.clone();
+    let (attributes, predicates) = body.proof_request.into_requested();
+    let exchange = state
+        .agent
+        .send_request(ProofRequestOptions {
+            connection_id: body.connection_id,
+            name,
+            attributes,
+            predicates,
+        })
+        .await?;
+    didcomm::request_proof(&state, &exchange).await;
+    Ok(Json(exchange))
+}
+
+/// Body when a proof request is created without a connection
+#[derive(Debug, Deserialize)]
+pub(crate) struct CreateRequestBody {
+    /// The proof request
+    proof_request: ProofRequestBody,
+}
+
+/// Create a proof request that is not sent, e.g. to attach it to an invitation
+pub(crate) async fn create_request(
+    State(state): AppState,
+    Json(body): Json<CreateRequestBody>,
+) -> ApiResult<Json<ProofRequestResponse>> {
+    let name = body.proof_request.name.clone();
+    let (attributes, predicates) = body.proof_request.into_requested();
+    let exchange = state
+        .agent
+        .create_request(ProofCreateRequestOptions {
+            name,
+            attributes,
+            predicates,
+        })
+        .await?;
+    Ok(Json(exchange))
+}
+
+/// Every presentation exchange
+pub(crate) async fn proof_exchanges(
+    State(state): AppState,
+    Query(options): Query<ProofGetAllOptions>,
+) -> ApiResult<Json<Value>> {
+    let exchanges = ProofModule::get_all(&state.agent, options).await?;
+    Ok(Json(json!({ "results": exchanges })))
+}
+
+/// A single presentation exchange
+pub(crate) async fn proof_exchange(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> ApiResult<Json<ProofRequestResponse>> {
+    Ok(Json(ProofModule::get_by_id(&state.agent, id).await?))
+}
+
+/// The held credentials that can be used for the proof request, with the referents they satisfy
+pub(crate) async fn presentation_credentials(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> ApiResult<Json<Vec<Value>>> {
+    let exchange = ProofModule::get_by_id(&state.agent, id).await?;
+    let credentials = didcomm::held_credentials(&state)
+        .into_iter()
+        .map(|(credential_id, attributes)| {
+            let referents: Vec<&String> = ["requested_attributes", "requested_predicates"]
+                .iter()
+                .filter_map(|key| exchange.presentation_request.get(key))
+                .filter_map(Value::as_object)
+                .flatten()
+                .filter(|(_, item)| {
+                    item.get("name")
+                        .and_then(Value::as_str)
+                        .is_some_and(|name| attributes.contains_key(name))
+                })
+                .map(|(referent, _)| referent)
+                .collect();
+            json!({
+                "cred_info": { "referent": credential_id, "attrs": attributes },
+                "presentation_referents": referents,
+            })
+        })
+        .collect();
+    Ok(Json(credentials))
+}
+
+/// Present a received proof request
+pub(crate) async fn send_presentation(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> ApiResult<Json<ProofRequestResponse>> {
+    Ok(Json(state.agent.present(id).await?))
+}
+
+/// Verify a received presentation
+pub(crate) async fn verify_presentation(
+    State(state): AppState,
+    Path(id): Path<String>,
+) -> ApiResult<Json<ProofRequestResponse>> {
+    Ok(Json(state.agent.verify(id).await?))
+}
+
+/// Every DID in the wallet that matches the filters
+pub(crate) async fn dids(
+    State(state): AppState,
+    Query(options): Query<Did>,
+) -> ApiResult<Json<Value>> {
+    let dids = state.agent.get_wallet_dids(options).await?;
+    Ok(Json(json!({ "results": dids })))
+}
+
+/// Create a DID in the wallet
+pub(crate) async fn create_did(
+    State(state): AppState,
+    Json(options): Json<CreateLocalDidOptions>,
+) -> ApiResult<Json<Did>> {
+    Ok(Json(state.agent.create_local_did(options).await?))
+}
+
+/// Rotate the keypair of a DID
+pub(crate) async fn rotate_keypair(
+    State(state): AppState,
+    Query(query): Query<DidQuery>,
+) -> ApiResult<()> {
+    Ok(state.agent.rotate_keypair(query.did).await?)
+}
+
+/// The public DID
+pub(crate) async fn public_did(State(state): AppState) -> ApiResult<Json<Did>> {
+    Ok(Json(state.agent.fetch_public_did().await?))
+}
+
+/// Make a DID the public DID
+pub(crate) async fn assign_public_did(
+    State(state): AppState,
+    Query(query): Query<DidQuery>,
+) -> ApiResult<Json<Did>> {
+    Ok(Json(state.agent.assign_public_did(query.did).await?))
+}
+
+/// The endpoint of a DID
+pub(crate) async fn did_endpoint(
+    State(state): AppState,
+    Query(query): Query<DidQuery>,
+) -> ApiResult<Json<DidEndpoint>> {
+    Ok(Json(state.agent.fetch_did_endpoint(query.did).await?))
+}
+
+/// Set the endpoint of a DID
+pub(crate) async fn set_did_endpoint(
+    State(state): AppState,
+    Json(options): Json<SetDidEndpointOptions>,
+) -> ApiResult<()> {
+    Ok(state.agent.set_did_endpoint(options).await?)
+}
+
+/// Create a sub wallet
+pub(crate) async fn create_wallet(
+    State(state): AppState,
+) -> ApiResult<Json<MultitenancyCreateResponse>> {
+    Ok(Json(MultitenancyModule::create(&state.agent).await?))
+}
+
+/// Remove a sub wallet
+pub(crate) async fn remove_wallet(
+    State(state): AppState,
+    Path(wallet_id): Path<String>,
+) -> ApiResult<Json<Value>> {
+    MultitenancyModule::remove(&state.agent, wallet_id).await?;
+    Ok(Json(json!({})))
+}
+
+/// The protocols the agent supports
+pub(crate) async fn discover_features(
+    State(state): AppState,
+) -> ApiResult<Json<DiscoverFeaturesResponse>> {
+    Ok(Json(state.agent.discover_features().await?))
+}
+
+/// Url of an invitation, with the invitation base64 encoded in the query parameter
+fn invitation_url(state: &ServerState, parameter: &str, invitation: &Value) -> String {
+    let encoded = general_purpose::STANDARD.encode(invitation.to_string());
+    format!("{}?{parameter}={encoded}", state.endpoint)
+}
@@ -0,0 +1,503 @@
+use crate::admin::ApiResult;
+use crate::server::ServerState;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use siera_agent::modules::connection::{Connection, ConnectionRole, ConnectionState};
+use siera_agent::modules::credential::{CredentialExchangeState, CredentialOfferResponse};
+use siera_agent::modules::proof::{PresentationExchangeState, ProofRequestResponse};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Another instance on the other side of a connection
+#[derive(Debug, Clone)]
+pub(crate) struct Peer {
+    /// Endpoint that receives its messages
+    pub endpoint: String,
+
+    /// Its id of the connection
+    pub connection_id: String,
+}
+
+/// A message from one instance to another. Unlike real `DIDComm` messages they are neither
+/// encrypted nor signed, as the instances only imitate the protocols
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub(crate) enum Envelope {
+    /// Request to connect with the invitation that has the recipient key
+    ConnectionRequest {
+        /// Recipient key of the invitation
+        invitation_key: String,
+
+        /// Connection id of the sender
+        connection_id: String,
+
+        /// DID of the sender in the connection
+        did: String,
+
+        /// Label of the sender
+        label: String,
+
+        /// Endpoint that receives the messages of the sender
+        endpoint: String,
+    },
+
+    /// Basic message over a connection
+    BasicMessage {
+        /// Connection id of the receiver
+        connection_id: String,
+
+        /// Text of the message
+        content: String,
+    },
+
+    /// Offer of a credential, which the receiver accepts right away
+    CredentialOffer {
+        /// Connection id of the receiver
+        connection_id: String,
+
+        /// Thread of the exchange
+        thread_id: String,
+
+        /// The credential exchange of the issuer
+        exchange: Box<CredentialOfferResponse>,
+    },
+
+    /// Request for a proof, which the receiver presents right away when it holds the attributes
+    PresentationRequest {
+        /// Connection id of the receiver
+        connection_id: String,
+
+        /// Thread of the exchange
+        thread_id: String,
+
+        /// The presentation request, as it is in the exchange of the verifier
+        presentation_request: Value,
+    },
+}
+
+/// Response to a connection request
+#[derive(Debug, Serialize, Deserialize)]
+struct ConnectionResponse {
+    /// Connection id of the inviter
+    connection_id: String,
+
+    /// DID of the inviter in the connection
+    did: String,
+
+    /// Label of the inviter
+    label: String,
+}
+
+/// Response to a presentation request
+#[derive(Debug, Serialize, Deserialize)]
+struct Presentation {
+    /// Values of the requested attributes, keyed by their referent
+    revealed: BTreeMap<String, String>,
+}
+
+/// Send a message to another instance and read its response
+async fn send<T: DeserializeOwned>(
+    state: &ServerState,
+    endpoint: &str,
+    message: &Envelope,
+) -> std::result::Result<T, String> {
+    let response = state
+        .client
+        .post(endpoint)
+        .json(message)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    let text = response.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!("{status}: {text}"));
+    }
+    serde_json::from_str(&text).map_err(|e| e.to_string())
+}
+
+/// Connect with the agent that created the invitation. Returns the connection once the other
+/// agent accepted it, or `None` when it could not be reached
+pub(crate) async fn connect(
+    state: &ServerState,
+    connection_id: &str,
+    endpoint: &str,
+    invitation_key: &str,
+) -> Option<Connection> {
+    let did = state.agent.next_did();
+    let request = Envelope::ConnectionRequest {
+        invitation_key: invitation_key.to_owned(),
+        connection_id: connection_id.to_owned(),
+        did: did.clone(),
+        label: state.agent.label().to_owned(),
+        endpoint: state.didcomm_endpoint(),
+    };
+    let response: ConnectionResponse = match send(state, endpoint, &request).await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!({ "message": format!("Unable to connect with {endpoint}"), "error": e });
+            return None;
+        }
+    };
+
+    state.add_peer(
+        connection_id.to_owned(),
+        Peer {
+            endpoint: endpoint.to_owned(),
+            connection_id: response.connection_id,
+        },
+    );
+    state
+        .agent
+        .update_connection(connection_id, |c| {
+            c.state = ConnectionState::Active;
+            c.did = Some(did);
+            c.their_did = Some(response.did);
+            c.their_label = Some(response.label);
+        })
+        .ok()
+}
+
+/// Send a basic message to the other instance of the connection, if there is one
+pub(crate) async fn send_message(state: &ServerState, connection_id: &str, content: String) {
+    let Some(peer) = state.peer(connection_id) else {
+        return;
+    };
+    let message = Envelope::BasicMessage {
+        connection_id: peer.connection_id,
+        content,
+    };
+    if let Err(e) = send::<Value>(state, &peer.endpoint, &message).await {
+        warn!({ "message": "Unable to deliver the basic message", "error": e });
+    }
+}
+
+/// Offer the credential to the other instance of the connection, if there is one, and move
+/// the exchange along as the other instance accepts it
+pub(crate) async fn offer_credential(state: &ServerState, exchange: &CredentialOfferResponse) {
+    let Some(peer) = state.peer(&exchange.connection_id) else {
+        return;
+    };
+    let message = Envelope::CredentialOffer {
+        connection_id: peer.connection_id,
+        thread_id: exchange.thread_id.clone(),
+        exchange: Box::new(exchange.clone()),
+    };
+    let id = &exchange.credential_exchange_id;
+    match send::<Value>(state, &peer.endpoint, &message).await {
+        Ok(_) => {
+            for _ in 0..3 {
+                if let Err(e) = state.agent.advance_credential_exchange(id) {
+                    warn!({ "error": e.to_string() });
+                }
+            }
+        }
+        Err(e) => {
+            warn!({ "message": "The credential offer was not accepted", "error": e });
+            let _ = state.agent.update_credential_exchange(id, |e| {
+                e.state = CredentialExchangeState::Abandoned;
+            });
+        }
+    }
+}
+
+/// Request the proof from the other instance of the connection, if there is one, and verify it
+/// once the other instance presented it
+pub(crate) async fn request_proof(state: &ServerState, exchange: &ProofRequestResponse) {
+    let Some(peer) = state.peer(&exchange.connection_id) else {
+        return;
+    };
+    let message = Envelope::PresentationRequest {
+        connection_id: peer.connection_id,
+        thread_id: exchange.thread_id.clone(),
+        presentation_request: exchange.presentation_request.clone(),
+    };
+    let id = &exchange.presentation_exchange_id;
+    match send::<Presentation>(state, &peer.endpoint, &message).await {
+        Ok(presentation) => {
+            debug!({ "message": "Received a presentation", "revealed": presentation.revealed });
+            let revealed: serde_json::Map<String, Value> = presentation
+                .revealed
+                .into_iter()
+                .map(|(referent, raw)| (referent, json!({ "raw": raw })))
+                .collect();
+            let _ = state.agent.update_proof_exchange(id, |e| {
+                e.presentation = json!({ "requested_proof": { "revealed_attrs": revealed } });
+            });
+            for _ in 0..2 {
+                if let Err(e) = state.agent.advance_proof_exchange(id) {
+                    warn!({ "error": e.to_string() });
+                }
+            }
+            let _ = state.agent.update_proof_exchange(id, |e| {
+                e.verified = Some(String::from("true"));
+            });
+        }
+        Err(e) => {
+            warn!({ "message": "The proof request was not presented", "error": e });
+            let _ = state.agent.update_proof_exchange(id, |e| {
+                e.state = PresentationExchangeState::Abandoned;
+            });
+        }
+    }
+}
+
+/// Receive a message of another instance
+pub(crate) async fn receive(
+    State(state): State<Arc<ServerState>>,
+    Json(message): Json<Envelope>,
+) -> ApiResult<Response> {
+    match message {
+        Envelope::ConnectionRequest {
+            invitation_key,
+            connection_id,
+            did,
+            label,
+            endpoint,
+        } => accept_connection(
+            &state,
+            &invitation_key,
+            Peer {
+                endpoint,
+                connection_id,
+            },
+            did,
+            label,
+        ),
+        Envelope::BasicMessage {
+            connection_id,
+            content,
+        } => {
+            state.agent.push_event(json!({
+                "topic": "basicmessages",
+                "payload": {
+                    "connection_id": connection_id,
+                    "message_id": state.agent.next_id(),
+                    "content": content,
+                    "state": "received",
+                },
+            }));
+            Ok(Json(json!({})).into_response())
+        }
+        Envelope::CredentialOffer {
+            connection_id,
+            thread_id,
+            exchange,
+        } => accept_credential(&state, connection_id, thread_id, *exchange),
+        Envelope::PresentationRequest {
+            connection_id,
+            thread_id,
+            presentation_request,
+        } => present_proof(&state, connection_id, thread_id, presentation_request),
+    }
+}
+
+/// Accept the connection request for the invitation with the recipient key
+fn accept_connection(
+    state: &ServerState,
+    invitation_key: &str,
+    peer: Peer,
+    their_did: String,
+    their_label: String,
+) -> ApiResult<Response> {
+    let Some(invitation) = state.agent.connection_by_invitation_key(invitation_key) else {
+        return Ok(rejected(StatusCode::NOT_FOUND, "Unknown invitation"));
+    };
+    // A multi use invitation results in a new connection for every request
+    let id = if invitation.state == ConnectionState::Invitation {
+        invitation.id
+    } else {
+        state.agent.add_connection(ConnectionState::Request).id
+    };
+    let did = state.agent.next_did();
+    state.agent.update_connection(&id, |c| {
+        c.state = ConnectionState::Active;
+        c.role = ConnectionRole::Invitee;
+        c.did = Some(did.clone());
+        c.their_did = Some(their_did);
+        c.their_label = Some(their_label);
+        c.alias = c.alias.take().or_else(|| invitation.alias.clone());
+        c.invitation_msg_id = c
+            .invitation_msg_id
+            .take()
+            .or_else(|| invitation.invitation_msg_id.clone());
+    })?;
+    state.add_peer(id.clone(), peer);
+    Ok(Json(json!(ConnectionResponse {
+        connection_id: id,
+        did,
+        label: state.agent.label().to_owned(),
+    }))
+    .into_response())
+}
+
+/// Store the offered credential as a holder and accept it right away
+fn accept_credential(
+    state: &ServerState,
+    connection_id: String,
+    thread_id: String,
+    exchange: CredentialOfferResponse,
+) -> ApiResult<Response> {
+    let id = state.agent.next_id();
+    state
+        .agent
+        .add_credential_exchange(CredentialOfferResponse {
+            auto_issue: false,
+            auto_offer: false,
+            connection_id,
+            credential_exchange_id: id.clone(),
+            initiator: String::from("external"),
+            role: String::from("holder"),
+            state: CredentialExchangeState::OfferReceived,
+            thread_id,
+            ..exchange
+        });
+    for next in [
+        CredentialExchangeState::RequestSent,
+        CredentialExchangeState::CredentialReceived,
+        CredentialExchangeState::CredentialAcked,
+    ] {
+        state
+            .agent
+            .update_credential_exchange(&id, |e| e.state = next)?;
+    }
+    Ok(Json(json!({})).into_response())
+}
+
+/// Present the requested attributes from the held credentials, or abandon the request when
+/// they do not satisfy it
+fn present_proof(
+    state: &ServerState,
+    connection_id: String,
+    thread_id: String,
+    presentation_request: Value,
+) -> ApiResult<Response> {
+    let presentation = present(state, &presentation_request);
+    let id = state.agent.next_id();
+    state.agent.add_proof_exchange(ProofRequestResponse {
+        state: PresentationExchangeState::RequestReceived,
+        presentation_request,
+        connection_id,
+        thread_id,
+        role: String::from("prover"),
+        auto_present: true,
+        presentation_exchange_id: id.clone(),
+        initiator: String::from("external"),
+        ..ProofRequestResponse::default()
+    });
+    match presentation {
+        Ok(revealed) => {
+            for next in [
+                PresentationExchangeState::PresentationSent,
+                PresentationExchangeState::PresentationAcked,
+            ] {
+                state.agent.update_proof_exchange(&id, |e| e.state = next)?;
+            }
+            Ok(Json(json!(Presentation { revealed })).into_response())
+        }
+        Err(reason) => {
+            state.agent.update_proof_exchange(&id, |e| {
+                e.state = PresentationExchangeState::Abandoned;
+            })?;
+            Ok(rejected(StatusCode::BAD_REQUEST, &reason))
+        }
+    }
+}
+
+/// Response for a message that is not accepted
+fn rejected(status: StatusCode, reason: &str) -> Response {
+    (status, reason.to_owned()).into_response()
+}
+
+/// The attributes of every credential the agent holds, keyed by the id of its exchange
+pub(crate) fn held_credentials(state: &ServerState) -> Vec<(String, BTreeMap<String, String>)> {
+    state
+        .agent
+        .credential_exchanges()
+        .into_iter()
+        .filter(|e| e.role == "holder" && e.state == CredentialExchangeState::CredentialAcked)
+        .map(|exchange| {
+            let attributes = exchange
+                .credential_offer_dict
+                .pointer("/credential_preview/attributes")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|attribute| {
+                    let name = attribute.get("name").and_then(Value::as_str)?;
+                    let value = attribute.get("value").and_then(Value::as_str)?;
+                    Some((name.to_owned(), value.to_owned()))
+                })
+                .collect();
+            (exchange.credential_exchange_id, attributes)
+        })
+        .collect()
+}
+
+/// The values of the requested attributes from the credentials the agent holds, keyed by their
+/// referent
+///
+/// # Errors
+///
+/// When an attribute is not in any credential or a predicate is not satisfied
+fn present(
+    state: &ServerState,
+    request: &Value,
+) -> std::result::Result<BTreeMap<String, String>, String> {
+    let held: BTreeMap<String, String> = held_credentials(state)
+        .into_iter()
+        .flat_map(|(_, attributes)| attributes)
+        .collect();
+
+    let field = |item: &Value, name: &str| {
+        item.get(name)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned()
+    };
+    let mut revealed = BTreeMap::new();
+    for (referent, attribute) in object_entries(request, "requested_attributes") {
+        let name = field(attribute, "name");
+        let value = held
+            .get(&name)
+            .ok_or_else(|| format!("No credential contains the attribute {name}"))?;
+        revealed.insert(referent.clone(), value.clone());
+    }
+    for (_, predicate) in object_entries(request, "requested_predicates") {
+        let name = field(predicate, "name");
+        let operator = field(predicate, "p_type");
+        let expected = predicate.get("p_value").and_then(Value::as_i64);
+        let value = held.get(&name).and_then(|v| v.parse::<i64>().ok());
+        let satisfied = match (value, expected) {
+            (Some(value), Some(expected)) => match operator.as_str() {
+                ">=" => value >= expected,
+                ">" => value > expected,
+                "<=" => value <= expected,
+                "<" => value < expected,
+                _ => false,
+            },
+            _ => false,
+        };
+        if !satisfied {
+            return Err(format!("The predicate {name} {operator} is not satisfied"));
+        }
+    }
+    Ok(revealed)
+}
+
+/// Entries of an object in the request, like the requested attributes keyed by their referent
+fn object_entries<'a>(
+    request: &'a Value,
+    key: &str,
+) -> impl Iterator<Item = (&'a String, &'a Value)> {
+    request
+        .get(key)
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+}
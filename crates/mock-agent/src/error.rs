@@ -0,0 +1,28 @@
+use std::fmt::{Display, Formatter};
+
+/// User-level errors that can be thrown at runtime
+#[derive(Debug)]
+pub enum Error {
+    /// The server could not listen on the address
+    UnableToBind(String, String),
+
+    /// The server stopped because of an error
+    ServerFailed(String),
+}
+
+impl std::error::Error for Error {}
+
+/// Generic result type which binds the error to be an instance of the `Error` enum
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnableToBind(address, reason) => write!(
+                f,
+                "Unable to listen on {address}. Is another agent running on this port? Additional info: {reason}"
+            ),
+            Self::ServerFailed(reason) => write!(f, "The mock agent stopped: {reason}"),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Crate that contains a local stand-in for the admin api of an aries-cloudagent-python agent.
+//! The records are kept by the `MockAgent` of `siera_agent`, so the responses are built from the
+//! same structures that the clients parse. Instances that run next to each other can connect,
+//! issue credentials and request proofs over plain http
+
+#[macro_use]
+extern crate siera_logger;
+
+/// Handlers of the admin api
+mod admin;
+
+/// Messages that instances exchange with each other
+mod didcomm;
+
+/// Error module that includes the user-level errors and the result type
+pub mod error;
+
+/// Http server that serves the admin api, the event socket and the messages of other instances
+pub mod server;
@@ -0,0 +1,318 @@
+use crate::admin;
+use crate::didcomm::{self, Peer};
+use crate::error::{Error, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::http::{Request, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, patch, post};
+use axum::Router;
+use serde_json::Value;
+use siera_agent::mock::MockAgent;
+use std::collections::HashMap;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Amount of webhook events that are buffered for a slow socket before it misses them
+const EVENT_BUFFER: usize = 256;
+
+/// Options supplied by the frontend to start a mock agent
+#[derive(Debug, Clone)]
+pub struct MockAgentServerOptions {
+    /// Address the server listens on. Port `0` picks a free port
+    pub address: SocketAddr,
+
+    /// Label of the agent, as it is shown to the agents that connect to it
+    pub label: String,
+
+    /// Api key that every request to the admin api must supply, if any
+    pub api_key: Option<String>,
+
+    /// Url on which other agents reach this agent. Defaults to the address it listens on
+    pub endpoint: Option<String>,
+}
+
+/// State that is shared by every request
+pub(crate) struct ServerState {
+    /// Records of the agent
+    pub agent: MockAgent,
+
+    /// Url on which other agents reach this agent
+    pub endpoint: String,
+
+    /// Api key that every request to the admin api must supply, if any
+    pub api_key: Option<String>,
+
+    /// The agents on the other side of the connections, keyed by our connection id
+    pub peers: Mutex<HashMap<String, Peer>>,
+
+    /// Http client to send messages to the other agents
+    pub client: reqwest::Client,
+
+    /// Webhook events for every connected socket
+    pub events: broadcast::Sender<Value>,
+}
+
+impl ServerState {
+    /// Endpoint that receives the messages of other agents
+    pub fn didcomm_endpoint(&self) -> String {
+        format!("{}/didcomm", self.endpoint)
+    }
+
+    /// The agent on the other side of the connection, if it is another instance
+    pub fn peer(&self, connection_id: &str) -> Option<Peer> {
+        self.peers
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(connection_id)
+            .cloned()
+    }
+
+    /// Remember the agent on the other side of the connection
+    pub fn add_peer(&self, connection_id: String, peer: Peer) {
+        self.peers
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(connection_id, peer);
+    }
+}
+
+/// Local stand-in for the admin api of an aries-cloudagent-python agent
+///
+/// It serves the endpoints that siera uses, the `/ws` event socket and a `/didcomm` endpoint
+/// that other instances send their messages to. Every connection, credential offer and proof
+/// request is accepted by the other instance right away
+pub struct MockAgentServer {
+    /// Socket the server accepts connections on
+    listener: TcpListener,
+
+    /// State that is shared by every request
+    state: Arc<ServerState>,
+}
+
+impl MockAgentServer {
+    /// Listen on the address of the options. Requests are served once `run` is called
+    ///
+    /// # Errors
+    ///
+    /// When the server could not listen on the address
+    pub fn bind(options: MockAgentServerOptions) -> Result<Self> {
+        let bind_error =
+            |e: std::io::Error| Error::UnableToBind(options.address.to_string(), e.to_string());
+        let listener = TcpListener::bind(options.address).map_err(bind_error)?;
+        listener.set_nonblocking(true).map_err(bind_error)?;
+        let address = listener.local_addr().map_err(bind_error)?;
+
+        let endpoint = options.endpoint.unwrap_or_else(|| {
+            let host = if address.ip().is_unspecified() {
+                String::from("localhost")
+            } else {
+                address.ip().to_string()
+            };
+            format!("http://{host}:{}", address.port())
+        });
+        let endpoint = endpoint.trim_end_matches('/').to_owned();
+        let agent = MockAgent::with_endpoint(format!("{endpoint}/didcomm"), options.label);
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+
+        let state = ServerState {
+            agent,
+            endpoint,
+            api_key: options.api_key,
+            peers: Mutex::default(),
+            client: reqwest::Client::new(),
+            events,
+        };
+        Ok(Self {
+            listener,
+            state: Arc::new(state),
+        })
+    }
+
+    /// Url of the admin api, which is also the url other agents reach this agent on
+    #[must_use]
+    pub fn endpoint(&self) -> &str {
+        &self.state.endpoint
+    }
+
+    /// Serve requests until the process stops
+    ///
+    /// # Errors
+    ///
+    /// When the server stopped because of an error
+    pub async fn run(self) -> Result<()> {
+        info!({ "message": format!("Mock agent '{}' is listening on {}", self.state.agent.label(), self.state.endpoint) });
+        axum::Server::from_tcp(self.listener)
+            .map_err(|e| Error::ServerFailed(e.to_string()))?
+            .serve(router(self.state).into_make_service())
+            .await
+            .map_err(|e| Error::ServerFailed(e.to_string()).into())
+    }
+}
+
+/// Every route of the server
+fn router(state: Arc<ServerState>) -> Router {
+    let admin = Router::new()
+        .route("/status", get(admin::status))
+        .route("/connections", get(admin::connections))
+        .route(
+            "/connections/create-invitation",
+            post(admin::create_invitation),
+        )
+        .route(
+            "/connections/receive-invitation",
+            post(admin::receive_invitation),
+        )
+        .route("/connections/:id", get(admin::connection))
+        .route("/connections/:id/send-message", post(admin::send_message))
+        .route(
+            "/out-of-band/create-invitation",
+            post(admin::create_oob_invitation),
+        )
+        .route(
+            "/out-of-band/receive-invitation",
+            post(admin::receive_oob_invitation),
+        )
+        .route("/schemas", post(admin::create_schema))
+        .route("/schemas/created", get(admin::schemas))
+        .route("/schemas/:id", get(admin::schema))
+        .route(
+            "/credential-definitions",
+            post(admin::create_credential_definition),
+        )
+        .route(
+            "/credential-definitions/created",
+            get(admin::credential_definitions),
+        )
+        .route(
+            "/credential-definitions/:id",
+            get(admin::credential_definition),
+        )
+        .route("/issue-credential/send-offer", post(admin::send_offer))
+        .route("/issue-credential/create-offer", post(admin::create_offer))
+        .route(
+            "/issue-credential/records",
+            get(admin::credential_exchanges),
+        )
+        .route(
+            "/issue-credential/records/:id",
+            get(admin::credential_exchange),
+        )
+        .route(
+            "/issue-credential/records/:id/send-request",
+            post(admin::accept_offer),
+        )
+        .route("/issue-credential/records/:id/issue", post(admin::issue))
+        .route("/issue-credential/records/:id/store", post(admin::store))
+        .route("/present-proof/send-request", post(admin::send_request))
+        .route("/present-proof/create-request", post(admin::create_request))
+        .route("/present-proof/records", get(admin::proof_exchanges))
+        .route("/present-proof/records/:id", get(admin::proof_exchange))
+        .route(
+            "/present-proof/records/:id/credentials",
+            get(admin::presentation_credentials),
+        )
+        .route(
+            "/present-proof/records/:id/send-presentation",
+            post(admin::send_presentation),
+        )
+        .route(
+            "/present-proof/records/:id/verify-presentation",
+            post(admin::verify_presentation),
+        )
+        .route("/wallet/did", get(admin::dids))
+        .route("/wallet/did/create", post(admin::create_did))
+        .route(
+            "/wallet/did/local/rotate-keypair",
+            patch(admin::rotate_keypair),
+        )
+        .route(
+            "/wallet/did/public",
+            get(admin::public_did).post(admin::assign_public_did),
+        )
+        .route("/wallet/fetch-did-endpoint", get(admin::did_endpoint))
+        .route("/wallet/set-did-endpoint", post(admin::set_did_endpoint))
+        .route("/multitenancy/wallet", post(admin::create_wallet))
+        .route(
+            "/multitenancy/wallet/:id/remove",
+            post(admin::remove_wallet),
+        )
+        .route("/discover-features/query", get(admin::discover_features))
+        .route("/ws", get(events))
+        .layer(middleware::from_fn_with_state(state.clone(), authorize));
+
+    Router::new()
+        .merge(admin)
+        .route("/didcomm", post(didcomm::receive))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            publish_events,
+        ))
+        .with_state(state)
+}
+
+/// Reject requests to the admin api without the api key, if one is configured
+async fn authorize<B>(
+    State(state): State<Arc<ServerState>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let authorized = state.api_key.as_ref().is_none_or(|key| {
+        request
+            .headers()
+            .get("x-api-key")
+            .is_some_and(|value| value.as_bytes() == key.as_bytes())
+    });
+    if authorized {
+        next.run(request).await
+    } else {
+        (StatusCode::UNAUTHORIZED, "Unauthorized").into_response()
+    }
+}
+
+/// Log the request and send the events of the records it changed to every socket
+async fn publish_events<B>(
+    State(state): State<Arc<ServerState>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let method = request.method().to_string();
+    let path = request.uri().path().to_owned();
+    let response = next.run(request).await;
+    info!({ "method": method, "path": path, "status": response.status().as_u16() });
+
+    for event in state.agent.take_events() {
+        debug!({ "event": event });
+        // Nobody is listening when there are no sockets, which is fine
+        let _ = state.events.send(event);
+    }
+    response
+}
+
+/// Upgrade to the socket that receives every webhook event
+async fn events(
+    State(state): State<Arc<ServerState>>,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let receiver = state.events.subscribe();
+    upgrade.on_upgrade(|socket| forward_events(socket, receiver))
+}
+
+/// Forward the events to the socket until it is closed
+async fn forward_events(mut socket: WebSocket, mut receiver: broadcast::Receiver<Value>) {
+    loop {
+        match receiver.recv().await {
+            Ok(event) => {
+                if socket.send(Message::Text(event.to_string())).await.is_err() {
+                    return;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                warn!({ "message": format!("Event socket missed {missed} events") });
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! Tests of two mock agents that are driven by the ACA-Py client, like siera drives them
+
+use serde_json::Value;
+use siera_agent::agent::HttpOptions;
+use siera_agent::modules::api::{ApiModule, ApiRequestOptions};
+use siera_agent::modules::basic_message::{BasicMessageModule, SendBasicMessageOptions};
+use siera_agent::modules::connection::{
+    ConnectionCreateInvitationOptions, ConnectionGetAllOptions, ConnectionModule,
+    ConnectionProtocol, ConnectionReceiveInvitationOptions, ConnectionState,
+};
+use siera_agent::modules::credential::{
+    CredentialAttribute, CredentialExchangeState, CredentialModule, CredentialOfferOptions,
+};
+use siera_agent::modules::credential_definition::{
+    CredentialDefinitionCreateOptions, CredentialDefinitionModule,
+};
+use siera_agent::modules::oob::{
+    OobAttachment, OobConnectionCreateInvitationOptions, OobModule, OobReceiveOptions,
+};
+use siera_agent::modules::proof::{ProofCreateRequestOptions, ProofModule, ProofRequestOptions};
+use siera_agent::modules::schema::{SchemaCreateOptions, SchemaModule};
+use siera_agent::modules::webhook::WebhookModule;
+use siera_cloudagent_python::agent::{CloudAgentPython, CloudAgentPythonVersion};
+use siera_mock_agent::server::{MockAgentServer, MockAgentServerOptions};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Events received by the listener, as it only accepts a function pointer
+static EVENTS: Mutex<Vec<Value>> = Mutex::new(Vec::new());
+
+/// Start a mock agent on a free port and return a client for it
+fn serve(label: &str, api_key: Option<&str>) -> CloudAgentPython {
+    let server = MockAgentServer::bind(MockAgentServerOptions {
+        address: "127.0.0.1:0".parse().unwrap(),
+        label: label.to_owned(),
+        api_key: api_key.map(ToOwned::to_owned),
+        endpoint: None,
+    })
+    .unwrap();
+    let endpoint = server.endpoint().to_owned();
+    tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    CloudAgentPython::new(
+        endpoint,
+        CloudAgentPythonVersion::ZeroSevenThree,
+        api_key.map(ToOwned::to_owned),
+        None,
+        HttpOptions::default(),
+    )
+    .unwrap()
+}
+
+/// Connect the holder to the issuer and return the connection ids of both
+async fn connect(issuer: &CloudAgentPython, holder: &CloudAgentPython) -> (String, String) {
+    let invitation = ConnectionModule::create_invitation(
+        issuer,
+        ConnectionCreateInvitationOptions {
+            alias: Some(String::from("holder")),
+            ..ConnectionCreateInvitationOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+    let invitation: ConnectionReceiveInvitationOptions =
+        serde_json::from_value(invitation.invitation).unwrap();
+    let connection = ConnectionModule::receive_invitation(holder, invitation)
+        .await
+        .unwrap();
+    assert_eq!(connection.state, ConnectionState::Active);
+    assert_eq!(connection.their_label.as_deref(), Some("Issuer"));
+
+    let connections = ConnectionModule::get_all(issuer, ConnectionGetAllOptions::default())
+        .await
+        .unwrap();
+    (connections[0].id.clone(), connection.id)
+}
+
+/// Fetch a record through the raw api
+async fn record(agent: &CloudAgentPython, path: String) -> Value {
+    agent
+        .request(ApiRequestOptions {
+            method: String::from("GET"),
+            path,
+            query: vec![],
+            body: None,
+        })
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn connects_two_instances() {
+    let issuer = serve("Issuer", None);
+    let holder = serve("Holder", None);
+    let (issuer_connection, _) = connect(&issuer, &holder).await;
+
+    let connection = ConnectionModule::get_by_id(&issuer, issuer_connection)
+        .await
+        .unwrap();
+    assert_eq!(connection.state, ConnectionState::Active);
+    assert_eq!(connection.their_label.as_deref(), Some("Holder"));
+    assert_eq!(connection.alias.as_deref(), Some("holder"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn issues_a_credential_and_verifies_a_proof() {
+    let issuer = serve("Issuer", None);
+    let holder = serve("Holder", None);
+    let (issuer_connection, _) = connect(&issuer, &holder).await;
+
+    let schema = SchemaModule::create(
+        &issuer,
+        SchemaCreateOptions {
+            name: String::from("passport"),
+            version: String::from("1.0"),
+            attributes: vec![String::from("name"), String::from("age")],
+        },
+    )
+    .await
+    .unwrap();
+    let definition = CredentialDefinitionModule::create(
+        &issuer,
+        CredentialDefinitionCreateOptions {
+            schema_id: schema.id,
+            tag: String::from("default"),
+            support_revocation: false,
+            revocation_registry_size: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let offer = issuer
+        .send_offer(CredentialOfferOptions {
+            connection_id: issuer_connection.clone(),
+            cred_def_id: definition.credential_definition_id,
+            attributes: vec![
+                CredentialAttribute::new("name", "Alice"),
+                CredentialAttribute::new("age", "28"),
+            ],
+        })
+        .await
+        .unwrap();
+    assert_eq!(offer.state, CredentialExchangeState::OfferSent);
+    let exchange = record(
+        &issuer,
+        format!("/issue-credential/records/{}", offer.credential_exchange_id),
+    )
+    .await;
+    assert_eq!(exchange["state"], "credential_acked");
+    let held = record(&holder, String::from("/issue-credential/records")).await;
+    assert_eq!(held["results"][0]["role"], "holder");
+
+    let proof = issuer
+        .send_request(ProofRequestOptions {
+            connection_id: issuer_connection.clone(),
+            name: String::from("adult"),
+            attributes: vec![String::from("name")],
+            predicates: vec![(String::from("age"), String::from(">="), 18)],
+        })
+        .await
+        .unwrap();
+    let exchange = record(
+        &issuer,
+        format!("/present-proof/records/{}", proof.presentation_exchange_id),
+    )
+    .await;
+    assert_eq!(exchange["state"], "verified");
+
+    let proof = issuer
+        .send_request(ProofRequestOptions {
+            connection_id: issuer_connection,
+            name: String::from("senior"),
+            attributes: vec![],
+            predicates: vec![(String::from("age"), String::from(">="), 65)],
+        })
+        .await
+        .unwrap();
+    let exchange = record(
+        &issuer,
+        format!("/present-proof/records/{}", proof.presentation_exchange_id),
+    )
+    .await;
+    assert_eq!(exchange["state"], "abandoned");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn attaches_a_proof_request_to_an_invitation_without_handshake() {
+    let verifier = serve("Verifier", None);
+
+    let request = verifier
+        .create_request(ProofCreateRequestOptions {
+            name: String::from("adult"),
+            attributes: vec![String::from("name")],
+            predicates: vec![(String::from("age"), String::from(">="), 18)],
+        })
+        .await
+        .unwrap();
+    assert_eq!(request.connection_id, "");
+
+    let invitation = OobModule::create_invitation(
+        &verifier,
+        OobConnectionCreateInvitationOptions {
+            auto_accept: true,
+            qr: false,
+            handshake_protocol: None,
+            multi_use: false,
+            alias: None,
+            attachments: vec![OobAttachment::PresentationRequest(
+                request.presentation_exchange_id,
+            )],
+        },
+    )
+    .await
+    .unwrap();
+    assert!(invitation.invitation.get("handshake_protocols").is_none());
+    let attachment = &invitation.invitation["requests~attach"][0];
+    assert_eq!(attachment["mime-type"], "application/json");
+    assert_eq!(
+        attachment["data"]["json"]["request_presentations~attach"][0]["data"]["json"]["name"],
+        "adult"
+    );
+
+    let connections = ConnectionModule::get_all(&verifier, ConnectionGetAllOptions::default())
+        .await
+        .unwrap();
+    assert!(connections.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reports_and_reuses_out_of_band_invitations() {
+    let issuer = serve("Issuer", None);
+    let alice = serve("Alice", None);
+    let bob = serve("Bob", None);
+
+    let invitation = OobModule::create_invitation(
+        &issuer,
+        OobConnectionCreateInvitationOptions {
+            auto_accept: true,
+            handshake_protocol: Some(ConnectionProtocol::default()),
+            multi_use: true,
+            ..OobConnectionCreateInvitationOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+    // Like ACA-Py 0.7.3, the id of the record of the invitation is not returned
+    assert!(invitation.oob_id.is_none());
+    for holder in [&alice, &bob] {
+        let connection = OobModule::receive_invitation(
+            holder,
+            invitation.invitation.clone(),
+            OobReceiveOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(connection.state, ConnectionState::Active);
+    }
+
+    let connections = ConnectionModule::get_all(
+        &issuer,
+        ConnectionGetAllOptions {
+            invitation_msg_id: Some(invitation.invitation_message_id.clone()),
+            ..ConnectionGetAllOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+    let mut labels: Vec<_> = connections
+        .into_iter()
+        .filter_map(|c| c.their_label)
+        .collect();
+    labels.sort();
+    assert_eq!(labels, ["Alice", "Bob"]);
+
+    // A second invitation of the issuer reuses the connection alice already has
+    let existing = ConnectionModule::get_all(&alice, ConnectionGetAllOptions::default())
+        .await
+        .unwrap();
+    let second = OobModule::create_invitation(
+        &issuer,
+        OobConnectionCreateInvitationOptions {
+            handshake_protocol: Some(ConnectionProtocol::default()),
+            ..OobConnectionCreateInvitationOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+    let reused = OobModule::receive_invitation(
+        &alice,
+        second.invitation,
+        OobReceiveOptions {
+            use_existing_connection: true,
+            ..OobReceiveOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(reused.connection_id, existing[0].id);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn delivers_basic_messages_as_events() {
+    let issuer = serve("Issuer", None);
+    let holder = serve("Holder", None);
+    let (issuer_connection, holder_connection) = connect(&issuer, &holder).await;
+
+    // The socket of the client blocks, so it gets a thread of its own
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(holder.listen(|event| EVENTS.lock().unwrap().push(event)))
+    });
+    // Wait until the socket is connected, as earlier events are not delivered
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    issuer
+        .send_message(SendBasicMessageOptions {
+            connection_id: issuer_connection,
+            message: String::from("Hello Alice"),
+        })
+        .await
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let event = loop {
+        if let Some(event) = EVENTS.lock().unwrap().first() {
+            break event.clone();
+        }
+        assert!(Instant::now() < deadline, "no event was delivered");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    };
+    assert_eq!(event["topic"], "basicmessages");
+    assert_eq!(
+        event["payload"]["connection_id"],
+        holder_connection.as_str()
+    );
+    assert_eq!(event["payload"]["content"], "Hello Alice");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn requires_the_api_key() {
+    let agent = serve("Issuer", Some("secret"));
+    assert!(SchemaModule::get_all(&agent).await.is_ok());
+
+    let endpoint = agent.endpoint.clone();
+    let unauthorized = CloudAgentPython::new(
+        endpoint,
+        CloudAgentPythonVersion::ZeroSevenThree,
+        None,
+        None,
+        HttpOptions::default(),
+    )
+    .unwrap();
+    assert!(SchemaModule::get_all(&unauthorized).await.is_err());
+}
@@ -6,14 +6,16 @@ mod macros;
 
 mod utils;
 
+/// The document that is written for every command with `--json`
 #[derive(Deserialize)]
-struct ConnectionListWrapper {
-    connections: Vec<Connection>,
+struct Envelope<T> {
+    status: String,
+    data: T,
 }
 
 #[derive(Deserialize)]
-struct BasicMessageWrapper {
-    message: String,
+struct ConnectionListWrapper {
+    connections: Vec<Connection>,
 }
 
 #[derive(Deserialize)]
@@ -28,9 +30,7 @@ struct ConnectionIdWrapper {
 
 #[cfg(test)]
 mod e2e_tests {
-    use crate::{
-        BasicMessageWrapper, ConnectionIdWrapper, ConnectionListWrapper, InvitationWrapper,
-    };
+    use crate::{ConnectionIdWrapper, ConnectionListWrapper, Envelope, InvitationWrapper};
 
     use super::utils::helpers::{run_test, REGEX_UUID};
     use regex::Regex;
@@ -44,33 +44,29 @@ mod e2e_tests {
 
     test! { create_a_connection_and_list_connections |cli| {
         let connections = siera!(cli, "connection list");
-        let connections: ConnectionListWrapper = serde_json::from_str(&connections).unwrap();
-        assert_that(&connections.connections).has_length(0);
+        let connections: Envelope<ConnectionListWrapper> = serde_json::from_str(&connections).unwrap();
+        assert_that(&connections.data.connections).has_length(0);
         siera!(cli, "connection invite");
         let connections = siera!(cli, "connection list");
-        let connections: ConnectionListWrapper = serde_json::from_str(&connections).unwrap();
-        assert_that(&connections.connections).has_length(1);
+        let connections: Envelope<ConnectionListWrapper> = serde_json::from_str(&connections).unwrap();
+        assert_that(&connections.data.connections).has_length(1);
     }}
 
     test! { create_connection_and_send_a_message |cli| {
         siera!(cli, "connection invite");
         let connections_str = siera!(cli, "connection list");
-        let connection: ConnectionListWrapper = serde_json::from_str(&connections_str).unwrap();
-        let result = siera!(cli, "message --connection-id={} --message={}", connection.connections[0].id, "bar");
-        let result:BasicMessageWrapper = serde_json::from_str(&result).unwrap();
-        assert_that(&result.message).is_equal_to(String::from("Successfully sent message"));
+        let connection: Envelope<ConnectionListWrapper> = serde_json::from_str(&connections_str).unwrap();
+        let result = siera!(cli, "message --connection-id={} --message={}", connection.data.connections[0].id, "bar");
+        let result: Envelope<serde_json::Value> = serde_json::from_str(&result).unwrap();
+        assert_that(&result.status).is_equal_to(String::from("success"));
     }}
 
     test! { create_invitation_and_receive_invitation |cli| {
          let invitation_str = siera!(cli, "connection invite");
-         let invitation_str: Vec<&str> = invitation_str.split_inclusive("}").collect();
-         let invitation_str = &invitation_str[3];
-         let invitation: InvitationWrapper = serde_json::from_str(invitation_str).unwrap();
-         let result = siera!(cli, "connection receive --url={}", invitation.invitation_url);
-         let result: Vec<&str> = result.split_inclusive("}").collect();
-         let result: ConnectionIdWrapper = serde_json::from_str(&result[1].replace("\n", "")).unwrap();
-         let connection_id = result
-             .connection_id;
+         let invitation: Envelope<InvitationWrapper> = serde_json::from_str(&invitation_str).unwrap();
+         let result = siera!(cli, "connection receive --url={}", invitation.data.invitation_url);
+         let result: Envelope<ConnectionIdWrapper> = serde_json::from_str(&result).unwrap();
+         let connection_id = result.data.connection_id;
          let re = Regex::new(REGEX_UUID).unwrap();
          assert_that(&connection_id).matches(|c| re.is_match(c) );
     }}
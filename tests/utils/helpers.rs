@@ -27,13 +27,17 @@ struct MultiTenancyCreateResponseWrapper {
     response: MultitenancyCreateResponse,
 }
 
+#[derive(Serialize, Deserialize)]
+struct MultiTenancyCreateEnvelope {
+    data: MultiTenancyCreateResponseWrapper,
+}
+
 async fn setup() -> (TestAgentCli, String) {
     let mut siera_cli = TestAgentCli::new(None);
     let response_str = siera_cli.exec("multitenancy create");
-    let wallet_response: MultiTenancyCreateResponseWrapper =
-        serde_json::from_str(&response_str).unwrap();
-    siera_cli.scope_to_wallet(wallet_response.response.token);
-    (siera_cli, wallet_response.response.wallet_id)
+    let wallet_response: MultiTenancyCreateEnvelope = serde_json::from_str(&response_str).unwrap();
+    siera_cli.scope_to_wallet(wallet_response.data.response.token);
+    (siera_cli, wallet_response.data.response.wallet_id)
 }
 
 fn teardown(siera_cli: &mut TestAgentCli, wallet_id: String) {